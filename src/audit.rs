@@ -0,0 +1,99 @@
+use std::future::{ready, Ready};
+
+use actix_web::{dev::Payload, web, FromRequest, HttpMessage, HttpRequest};
+use log::error;
+use serde_json::Value;
+
+use crate::{
+    auth::Principal,
+    domain::{
+        audit::{AuditAction, AuditEntry},
+        certificate::Certificate,
+    },
+    error::CrsError,
+    repository::{AuditRepository, RepositoryError},
+};
+
+/// Attempts at appending an entry while other entries of the same certificate are appended
+const MAX_APPEND_ATTEMPTS: usize = 3;
+
+/// Records the certificate changes of one actor in the audit log.
+///
+/// Handlers extract it from the request, the actor being the subject of the
+/// authenticated [`Principal`]. Background jobs create it with their own name.
+#[derive(Clone)]
+pub struct Auditor {
+    log: web::Data<dyn AuditRepository>,
+    actor: String,
+}
+
+impl Auditor {
+    pub fn new(log: web::Data<dyn AuditRepository>, actor: impl Into<String>) -> Self {
+        Auditor {
+            log,
+            actor: actor.into(),
+        }
+    }
+
+    /// Appends a change to the history of the certificate, given its state before the
+    /// change and the stored state after it.
+    ///
+    /// The change is already stored when it is recorded, so a failure to record it is
+    /// logged rather than reported, a retry of a committed change would fail.
+    pub async fn record(&self, action: AuditAction, before: Option<Value>, after: &Certificate) {
+        if let Err(err) = self.append(action, before, after).await {
+            error!(
+                "Could not record {} of certificate {} in the audit log: {}",
+                action,
+                after.id.as_uuid(),
+                err
+            );
+        }
+    }
+
+    async fn append(
+        &self,
+        action: AuditAction,
+        before: Option<Value>,
+        after: &Certificate,
+    ) -> Result<AuditEntry, RepositoryError> {
+        let certificate_id = after.id.as_uuid();
+        for _ in 0..MAX_APPEND_ATTEMPTS {
+            let previous = self.log.find_last(certificate_id).await?;
+            let entry = AuditEntry::chain(
+                previous.as_ref(),
+                action,
+                &self.actor,
+                before.clone(),
+                after,
+            );
+            if self.log.append(&entry).await? {
+                return Ok(entry);
+            }
+        }
+        Err(RepositoryError(format!(
+            "history of certificate {certificate_id} kept changing while recording a change"
+        )))
+    }
+
+    fn from_principal(req: &HttpRequest) -> Result<Self, CrsError> {
+        let log = req
+            .app_data::<web::Data<dyn AuditRepository>>()
+            .cloned()
+            .ok_or_else(|| CrsError::Internal("audit log storage is not configured".to_string()))?;
+        let extensions = req.extensions();
+        let principal = extensions
+            .get::<Principal>()
+            .ok_or_else(|| CrsError::Unauthorized("request is not authenticated".to_string()))?;
+        Ok(Auditor::new(log, principal.subject.clone()))
+    }
+}
+
+impl FromRequest for Auditor {
+    type Error = CrsError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Auditor::from_principal(req))
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web, Error, HttpMessage,
+};
+use futures::future::LocalBoxFuture;
+use log::info;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        api_key::{hash_secret, ApiKey, Scope},
+        base::Id,
+    },
+    error::CrsError,
+    jwt::JwtVerifier,
+    repository::{ApiKeyRepository, RepositoryError},
+};
+
+/// Header carrying the API key secret of a request
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// The authenticated caller of a request, attached to the request by [`Require`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Principal {
+    /// Who the caller is, `key:<key id>` for API keys and `user:<user id>` for users
+    pub subject: String,
+    /// The account the caller acts for, if any
+    pub account_id: Option<u32>,
+    /// The user whose certificates alone the caller may reach, if it is restricted
+    pub user_id: Option<Uuid>,
+    pub scopes: Vec<Scope>,
+}
+
+impl From<ApiKey> for Principal {
+    fn from(key: ApiKey) -> Self {
+        Principal {
+            subject: format!("key:{}", key.id.as_uuid()),
+            account_id: key.account_id,
+            user_id: None,
+            scopes: key.scopes,
+        }
+    }
+}
+
+impl Principal {
+    /// Ensures the caller was granted the scope
+    pub fn ensure_scope(&self, scope: Scope) -> Result<(), CrsError> {
+        if self.scopes.contains(&scope) {
+            Ok(())
+        } else {
+            Err(CrsError::Forbidden(format!(
+                "{} lacks the {scope} scope",
+                self.subject
+            )))
+        }
+    }
+}
+
+/// Authenticates the bearer token of a request
+fn authenticate_bearer(req: &ServiceRequest, token: &str) -> Result<Principal, CrsError> {
+    let verifier = req
+        .app_data::<web::Data<JwtVerifier>>()
+        .ok_or_else(|| CrsError::Unauthorized("bearer tokens are not accepted".to_string()))?;
+    verifier
+        .verify(token)
+        .map_err(|err| CrsError::Unauthorized(err.to_string()))
+}
+
+/// Authenticates the bearer token or else the API key of a request
+async fn authenticate(req: &ServiceRequest) -> Result<Principal, CrsError> {
+    if let Some(authorization) = req.headers().get(header::AUTHORIZATION) {
+        let token = authorization
+            .to_str()
+            .ok()
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                CrsError::Unauthorized("authorization must be a bearer token".to_string())
+            })?;
+        return authenticate_bearer(req, token.trim());
+    }
+    let secret = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|secret| secret.to_str().ok())
+        .map(str::trim)
+        .filter(|secret| !secret.is_empty())
+        .ok_or_else(|| {
+            CrsError::Unauthorized(format!("missing {API_KEY_HEADER} header or bearer token"))
+        })?;
+    let repository = req
+        .app_data::<web::Data<dyn ApiKeyRepository>>()
+        .ok_or_else(|| CrsError::Internal("API key storage is not configured".to_string()))?;
+    repository
+        .find_by_secret_hash(&hash_secret(secret))
+        .await?
+        .map(Principal::from)
+        .ok_or_else(|| CrsError::Unauthorized("invalid API key".to_string()))
+}
+
+/// Middleware letting a request through only when it is authenticated with a bearer
+/// token or an API key granted the scope.
+///
+/// Wraps single routes, e.g. `web::get().to(handler).wrap(Require(Scope::CertificatesRead))`.
+/// The [`Principal`] of the request is stored in its extensions for the handlers.
+#[derive(Debug, Clone, Copy)]
+pub struct Require(pub Scope);
+
+impl<S, B> Transform<S, ServiceRequest> for Require
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequireMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireMiddleware {
+            service: Rc::new(service),
+            scope: self.0,
+        }))
+    }
+}
+
+pub struct RequireMiddleware<S> {
+    service: Rc<S>,
+    scope: Scope,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let scope = self.scope;
+        Box::pin(async move {
+            let principal = authenticate(&req)
+                .await
+                .and_then(|principal| principal.ensure_scope(scope).map(|_| principal));
+            match principal {
+                Ok(principal) => {
+                    req.extensions_mut().insert(principal);
+                    Ok(service.call(req).await?.map_into_left_body())
+                }
+                // answered right away, so outer middleware sees the refusal
+                Err(err) => Ok(req.error_response(err).map_into_right_body()),
+            }
+        })
+    }
+}
+
+/// Registers the key managing the other keys of the account under the given secret,
+/// unless it is registered already. A key registered with the secret is bound to the
+/// account, keys registered before keys were bound to accounts had none.
+pub async fn ensure_admin_key(
+    repository: &dyn ApiKeyRepository,
+    account_id: u32,
+    secret: &str,
+) -> Result<(), RepositoryError> {
+    match repository.find_by_secret_hash(&hash_secret(secret)).await? {
+        Some(key) if key.account_id == Some(account_id) => {}
+        Some(mut key) => {
+            key.account_id = Some(account_id);
+            repository.update(&key).await?;
+            info!("Bound the admin API key to account {}", account_id);
+        }
+        None => {
+            let id = Id::parse(Uuid::new_v4()).map_err(|err| RepositoryError(err.to_string()))?;
+            repository
+                .insert(&ApiKey::admin(id, account_id, secret))
+                .await?;
+            info!("Registered the admin API key of account {}", account_id);
+        }
+    }
+    Ok(())
+}
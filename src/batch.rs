@@ -0,0 +1,129 @@
+use std::error::Error;
+
+use actix_web::{body::BoxBody, HttpRequest, HttpResponse, Responder};
+use log::info;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    error::{CrsError, FieldError},
+    helpers::respond_with_json,
+};
+
+/// Number of certificates accepted in one batch unless `CRS_MAX_BATCH_SIZE` says otherwise
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 500;
+
+/// Upper bound of a batch request body, large enough for the biggest batches we expect
+pub const BATCH_PAYLOAD_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Limits of bulk certificate issuance
+pub struct BatchConfig {
+    pub max_size: usize,
+}
+
+#[derive(Debug)]
+pub struct BatchConfigError(pub String);
+
+impl Error for BatchConfigError {}
+
+impl std::fmt::Display for BatchConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid batch configuration: {}", self.0)
+    }
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        BatchConfig {
+            max_size: DEFAULT_MAX_BATCH_SIZE,
+        }
+    }
+}
+
+impl BatchConfig {
+    /// The configured limits, or the defaults when none are configured
+    pub fn or_default(config: Option<&BatchConfig>) -> &BatchConfig {
+        const DEFAULT: BatchConfig = BatchConfig {
+            max_size: DEFAULT_MAX_BATCH_SIZE,
+        };
+        config.unwrap_or(&DEFAULT)
+    }
+
+    /// Rejects batches with more than `max_size` items
+    pub fn ensure_fits(&self, len: usize) -> Result<(), CrsError> {
+        if len > self.max_size {
+            return Err(CrsError::PayloadTooLarge(format!(
+                "a batch holds at most {} certificates, got {}",
+                self.max_size, len
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reads the maximum batch size from `CRS_MAX_BATCH_SIZE`, falling back to the default
+    pub fn from_env() -> Result<BatchConfig, BatchConfigError> {
+        match dotenvy::var("CRS_MAX_BATCH_SIZE") {
+            Ok(value) => match value.parse::<usize>() {
+                Ok(max_size) if max_size > 0 => {
+                    info!("Certificate batches are limited to {} items", max_size);
+                    Ok(BatchConfig { max_size })
+                }
+                _ => Err(BatchConfigError(format!(
+                    "CRS_MAX_BATCH_SIZE must be a positive number, got `{}`",
+                    value
+                ))),
+            },
+            Err(_) => Ok(BatchConfig::default()),
+        }
+    }
+}
+
+/// Outcome of a single batch item
+#[derive(Serialize, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchItemOutcome {
+    Created { id: Uuid },
+    Rejected { errors: Vec<FieldError> },
+}
+
+#[derive(Serialize, Debug)]
+pub struct BatchItemResult {
+    /// Position of the item in the submitted batch, or its line for CSV imports
+    pub index: usize,
+    #[serde(flatten)]
+    pub outcome: BatchItemOutcome,
+}
+
+/// Per item report of a batch issuance, rejected items do not affect the others
+#[derive(Serialize, Debug, Default)]
+pub struct BatchReport {
+    pub created: usize,
+    pub rejected: usize,
+    pub items: Vec<BatchItemResult>,
+}
+
+impl BatchReport {
+    pub fn created(&mut self, index: usize, id: Uuid) {
+        self.created += 1;
+        self.items.push(BatchItemResult {
+            index,
+            outcome: BatchItemOutcome::Created { id },
+        });
+    }
+
+    pub fn rejected(&mut self, index: usize, errors: Vec<FieldError>) {
+        self.rejected += 1;
+        self.items.push(BatchItemResult {
+            index,
+            outcome: BatchItemOutcome::Rejected { errors },
+        });
+    }
+}
+
+impl Responder for BatchReport {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
+}
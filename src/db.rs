@@ -1,14 +1,18 @@
 use futures::TryStreamExt;
 use mongodb::{
-    bson::{doc, Uuid},
-    options::ClientOptions,
-    results::InsertOneResult,
-    Client, Database,
+    bson::{doc, Bson, DateTime, Document, Uuid},
+    error::Result,
+    options::{ClientOptions, IndexOptions, ReturnDocument},
+    results::{DeleteResult, InsertManyResult, InsertOneResult, UpdateResult},
+    Client, Database, IndexModel,
 };
 
 use log::{error, info};
 
-use crate::model::CertificateModel;
+use crate::model::{
+    ApiKeyModel, AuditEntryModel, CertificateModel, ProductModel, RecipientModel,
+    RegisteredOrganizationModel,
+};
 
 pub const DB_NAME: &str = "crs";
 
@@ -37,38 +41,471 @@ pub async fn init_db() -> Option<Database> {
     None
 }
 
-pub async fn store_one(db: &Database, doc: &CertificateModel) -> Option<InsertOneResult> {
+/// Creates the indexes backing certificate, organization, recipient, product, API key and
+/// audit log lookups and listings, certificates are looked up within their account
+pub async fn init_indexes(db: &Database) -> Result<()> {
+    let coll = db.collection::<CertificateModel>("certificates");
+    coll.create_indexes([
+        IndexModel::builder()
+            .keys(doc! {"certificate_id": 1})
+            .options(IndexOptions::builder().unique(true).build())
+            .build(),
+        IndexModel::builder()
+            .keys(doc! {"account_id": 1, "certificate_id": 1})
+            .build(),
+        IndexModel::builder().keys(doc! {"user_id": 1}).build(),
+        IndexModel::builder()
+            .keys(doc! {"account_id": 1, "user_id": 1, "created_date": -1, "certificate_id": -1})
+            .build(),
+        IndexModel::builder()
+            .keys(doc! {"account_id": 1, "created_date": -1, "certificate_id": -1})
+            .build(),
+        IndexModel::builder()
+            .keys(doc! {"account_id": 1, "product_id": 1, "created_date": -1, "certificate_id": -1})
+            .build(),
+        IndexModel::builder()
+            .keys(doc! {"validity.valid_until": 1})
+            .build(),
+        IndexModel::builder()
+            .keys(doc! {"accreditation.end_date": 1})
+            .build(),
+    ])
+    .await?;
+
+    let organizations = db.collection::<RegisteredOrganizationModel>("organizations");
+    organizations
+        .create_indexes([
+            IndexModel::builder()
+                .keys(doc! {"account_id": 1, "organization_id": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"account_id": 1, "name": 1})
+                .build(),
+        ])
+        .await?;
+
+    let recipients = db.collection::<RecipientModel>("recipients");
+    recipients
+        .create_indexes([
+            IndexModel::builder()
+                .keys(doc! {"account_id": 1, "recipient_id": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"account_id": 1, "email_key": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        ])
+        .await?;
+
+    let products = db.collection::<ProductModel>("products");
+    products
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! {"account_id": 1, "product_id": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        )
+        .await?;
+
+    let api_keys = db.collection::<ApiKeyModel>("api_keys");
+    api_keys
+        .create_indexes([
+            IndexModel::builder()
+                .keys(doc! {"key_id": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"secret_hash": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"account_id": 1, "created_date": 1})
+                .build(),
+        ])
+        .await?;
+
+    let audit_log = db.collection::<AuditEntryModel>("audit_log");
+    audit_log
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! {"certificate_id": 1, "sequence": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn store_one(db: &Database, doc: &CertificateModel) -> Result<InsertOneResult> {
     let coll = db.collection::<CertificateModel>("certificates");
-    (coll.insert_one(doc).await).ok()
+    coll.insert_one(doc).await
 }
 
+pub async fn store_many(db: &Database, docs: &[CertificateModel]) -> Result<InsertManyResult> {
+    let coll = db.collection::<CertificateModel>("certificates");
+    coll.insert_many(docs).await
+}
+
+pub async fn replace_one(
+    db: &Database,
+    doc: &CertificateModel,
+    expected_version: u32,
+) -> Result<UpdateResult> {
+    let coll = db.collection::<CertificateModel>("certificates");
+    // documents stored before versioning have no version field
+    let version = if expected_version == 0 {
+        doc! {"$in": [0, Bson::Null]}
+    } else {
+        doc! {"$eq": expected_version}
+    };
+    coll.replace_one(
+        doc! {
+            "account_id": doc.account_id as i64,
+            "certificate_id": doc.certificate_id,
+            "version": version,
+        },
+        doc,
+    )
+    .await
+}
+
+/// Finds a certificate by its id, within the account when one is given
 pub async fn find_certificate_by_id(
     db: &Database,
+    account_id: Option<u32>,
     certificate_id: uuid::Uuid,
-) -> Option<CertificateModel> {
+) -> Result<Option<CertificateModel>> {
     let coll = db.collection::<CertificateModel>("certificates");
-    coll.find_one(doc! {"certificate_id": Uuid::from_uuid_1(certificate_id)})
-        .await
-        .unwrap_or_default()
+    let mut filter = doc! {"certificate_id": Uuid::from_uuid_1(certificate_id)};
+    if let Some(account_id) = account_id {
+        filter.insert("account_id", account_id as i64);
+    }
+    coll.find_one(filter).await
 }
 
 pub async fn find_certificates_by_user_id(
     db: &Database,
+    account_id: u32,
     user_id: uuid::Uuid,
-) -> Option<Vec<CertificateModel>> {
+) -> Result<Vec<CertificateModel>> {
+    let coll = db.collection::<CertificateModel>("certificates");
+    let cursor = coll
+        .find(doc! {"account_id": account_id as i64, "user_id": Uuid::from_uuid_1(user_id)})
+        .await?;
+    cursor.try_collect().await
+}
+
+/// Finds up to `limit` certificates matching the filter, newest first
+pub async fn find_certificates(
+    db: &Database,
+    filter: Document,
+    limit: i64,
+) -> Result<Vec<CertificateModel>> {
+    let coll = db.collection::<CertificateModel>("certificates");
+    let cursor = coll
+        .find(filter)
+        .sort(doc! {"created_date": -1, "certificate_id": -1})
+        .limit(limit)
+        .await?;
+    cursor.try_collect().await
+}
+
+/// Finds up to `limit` active certificates past their validity, or with an active
+/// accreditation past its end date
+pub async fn find_expiring_certificates(
+    db: &Database,
+    at: DateTime,
+    limit: i64,
+) -> Result<Vec<CertificateModel>> {
     let coll = db.collection::<CertificateModel>("certificates");
-    match coll
-        .find(doc! {"user_id": Uuid::from_uuid_1(user_id)})
+    // documents written before the status was stored are active
+    let cursor = coll
+        .find(doc! {"$or": [
+            {"status": {"$in": ["Active", Bson::Null]}, "validity.valid_until": {"$lte": at}},
+            {"accreditation.status": "Active", "accreditation.end_date": {"$lte": at}},
+        ]})
+        .limit(limit)
+        .await?;
+    cursor.try_collect().await
+}
+
+pub async fn store_organization(
+    db: &Database,
+    doc: &RegisteredOrganizationModel,
+) -> Result<InsertOneResult> {
+    let coll = db.collection::<RegisteredOrganizationModel>("organizations");
+    coll.insert_one(doc).await
+}
+
+pub async fn replace_organization(
+    db: &Database,
+    doc: &RegisteredOrganizationModel,
+) -> Result<UpdateResult> {
+    let coll = db.collection::<RegisteredOrganizationModel>("organizations");
+    coll.replace_one(
+        doc! {
+            "account_id": doc.account_id as i64,
+            "organization_id": doc.organization.organization_id,
+        },
+        doc,
+    )
+    .await
+}
+
+pub async fn delete_certificate(
+    db: &Database,
+    account_id: u32,
+    certificate_id: uuid::Uuid,
+) -> Result<DeleteResult> {
+    let coll = db.collection::<CertificateModel>("certificates");
+    coll.delete_one(doc! {
+        "account_id": account_id as i64,
+        "certificate_id": Uuid::from_uuid_1(certificate_id),
+    })
+    .await
+}
+
+pub async fn delete_organization(
+    db: &Database,
+    account_id: u32,
+    organization_id: uuid::Uuid,
+) -> Result<DeleteResult> {
+    let coll = db.collection::<RegisteredOrganizationModel>("organizations");
+    coll.delete_one(doc! {
+        "account_id": account_id as i64,
+        "organization_id": Uuid::from_uuid_1(organization_id),
+    })
+    .await
+}
+
+pub async fn find_organization_by_id(
+    db: &Database,
+    account_id: u32,
+    organization_id: uuid::Uuid,
+) -> Result<Option<RegisteredOrganizationModel>> {
+    let coll = db.collection::<RegisteredOrganizationModel>("organizations");
+    coll.find_one(doc! {
+        "account_id": account_id as i64,
+        "organization_id": Uuid::from_uuid_1(organization_id),
+    })
+    .await
+}
+
+/// Finds every organization of the account, ordered by name
+pub async fn find_organizations(
+    db: &Database,
+    account_id: u32,
+) -> Result<Vec<RegisteredOrganizationModel>> {
+    let coll = db.collection::<RegisteredOrganizationModel>("organizations");
+    let cursor = coll
+        .find(doc! {"account_id": account_id as i64})
+        .sort(doc! {"name": 1})
+        .await?;
+    cursor.try_collect().await
+}
+
+/// Stores the recipient unless one with the same normalized email exists in its
+/// account, returning the stored recipient either way
+pub async fn register_recipient(
+    db: &Database,
+    doc: &RecipientModel,
+) -> Result<Option<RecipientModel>> {
+    let coll = db.collection::<RecipientModel>("recipients");
+    let mut recipient = mongodb::bson::to_document(doc)?;
+    // the filter sets the account and email key of an inserted document
+    recipient.remove("account_id");
+    recipient.remove("email_key");
+    coll.find_one_and_update(
+        doc! {"account_id": doc.account_id as i64, "email_key": &doc.email_key},
+        doc! {"$setOnInsert": recipient},
+    )
+    .upsert(true)
+    .return_document(ReturnDocument::After)
+    .await
+}
+
+pub async fn replace_recipient(db: &Database, doc: &RecipientModel) -> Result<UpdateResult> {
+    let coll = db.collection::<RecipientModel>("recipients");
+    coll.replace_one(
+        doc! {"account_id": doc.account_id as i64, "recipient_id": doc.recipient_id},
+        doc,
+    )
+    .await
+}
+
+pub async fn find_recipient_by_id(
+    db: &Database,
+    account_id: u32,
+    recipient_id: uuid::Uuid,
+) -> Result<Option<RecipientModel>> {
+    let coll = db.collection::<RecipientModel>("recipients");
+    coll.find_one(doc! {
+        "account_id": account_id as i64,
+        "recipient_id": Uuid::from_uuid_1(recipient_id),
+    })
+    .await
+}
+
+pub async fn find_recipient_by_email_key(
+    db: &Database,
+    account_id: u32,
+    email_key: &str,
+) -> Result<Option<RecipientModel>> {
+    let coll = db.collection::<RecipientModel>("recipients");
+    coll.find_one(doc! {"account_id": account_id as i64, "email_key": email_key})
         .await
-    {
-        Ok(find_result) => {
-            let mut certificates = Vec::new();
-            let mut cursor = find_result;
-            while let Some(result) = cursor.try_next().await.unwrap() {
-                certificates.push(result);
-            }
-            Some(certificates)
-        }
-        Err(_) => None,
-    }
+}
+
+/// Finds every recipient of the account, ordered by last and first name
+pub async fn find_recipients(db: &Database, account_id: u32) -> Result<Vec<RecipientModel>> {
+    let coll = db.collection::<RecipientModel>("recipients");
+    let cursor = coll
+        .find(doc! {"account_id": account_id as i64})
+        .sort(doc! {"last_name": 1, "first_name": 1})
+        .await?;
+    cursor.try_collect().await
+}
+
+/// Stores the product unless one with the same id exists in its account, the result
+/// tells whether it was stored
+pub async fn register_product(db: &Database, doc: &ProductModel) -> Result<UpdateResult> {
+    let coll = db.collection::<ProductModel>("products");
+    let mut product = mongodb::bson::to_document(doc)?;
+    // the filter sets the account and id of an inserted document
+    product.remove("account_id");
+    product.remove("product_id");
+    coll.update_one(
+        doc! {"account_id": doc.account_id as i64, "product_id": doc.product_id as i64},
+        doc! {"$setOnInsert": product},
+    )
+    .upsert(true)
+    .await
+}
+
+pub async fn replace_product(db: &Database, doc: &ProductModel) -> Result<UpdateResult> {
+    let coll = db.collection::<ProductModel>("products");
+    coll.replace_one(
+        doc! {"account_id": doc.account_id as i64, "product_id": doc.product_id as i64},
+        doc,
+    )
+    .await
+}
+
+pub async fn delete_product(
+    db: &Database,
+    account_id: u32,
+    product_id: u32,
+) -> Result<DeleteResult> {
+    let coll = db.collection::<ProductModel>("products");
+    coll.delete_one(doc! {"account_id": account_id as i64, "product_id": product_id as i64})
+        .await
+}
+
+pub async fn find_product_by_id(
+    db: &Database,
+    account_id: u32,
+    product_id: u32,
+) -> Result<Option<ProductModel>> {
+    let coll = db.collection::<ProductModel>("products");
+    coll.find_one(doc! {"account_id": account_id as i64, "product_id": product_id as i64})
+        .await
+}
+
+/// Finds every product of the account, ordered by id
+pub async fn find_products(db: &Database, account_id: u32) -> Result<Vec<ProductModel>> {
+    let coll = db.collection::<ProductModel>("products");
+    let cursor = coll
+        .find(doc! {"account_id": account_id as i64})
+        .sort(doc! {"product_id": 1})
+        .await?;
+    cursor.try_collect().await
+}
+
+pub async fn store_api_key(db: &Database, doc: &ApiKeyModel) -> Result<InsertOneResult> {
+    let coll = db.collection::<ApiKeyModel>("api_keys");
+    coll.insert_one(doc).await
+}
+
+pub async fn replace_api_key(db: &Database, doc: &ApiKeyModel) -> Result<UpdateResult> {
+    let coll = db.collection::<ApiKeyModel>("api_keys");
+    coll.replace_one(doc! {"key_id": doc.key_id}, doc).await
+}
+
+pub async fn delete_api_key(
+    db: &Database,
+    account_id: u32,
+    key_id: uuid::Uuid,
+) -> Result<DeleteResult> {
+    let coll = db.collection::<ApiKeyModel>("api_keys");
+    coll.delete_one(doc! {"account_id": account_id as i64, "key_id": Uuid::from_uuid_1(key_id)})
+        .await
+}
+
+pub async fn find_api_key_by_id(
+    db: &Database,
+    account_id: u32,
+    key_id: uuid::Uuid,
+) -> Result<Option<ApiKeyModel>> {
+    let coll = db.collection::<ApiKeyModel>("api_keys");
+    coll.find_one(doc! {"account_id": account_id as i64, "key_id": Uuid::from_uuid_1(key_id)})
+        .await
+}
+
+pub async fn find_api_key_by_secret_hash(
+    db: &Database,
+    secret_hash: &str,
+) -> Result<Option<ApiKeyModel>> {
+    let coll = db.collection::<ApiKeyModel>("api_keys");
+    coll.find_one(doc! {"secret_hash": secret_hash}).await
+}
+
+/// Finds every API key of the account, oldest first
+pub async fn find_api_keys(db: &Database, account_id: u32) -> Result<Vec<ApiKeyModel>> {
+    let coll = db.collection::<ApiKeyModel>("api_keys");
+    let cursor = coll
+        .find(doc! {"account_id": account_id as i64})
+        .sort(doc! {"created_date": 1})
+        .await?;
+    cursor.try_collect().await
+}
+
+/// Stores the audit entry unless its certificate history already holds one with the
+/// same sequence number, the result tells whether it was stored
+pub async fn append_audit_entry(db: &Database, doc: &AuditEntryModel) -> Result<UpdateResult> {
+    let coll = db.collection::<AuditEntryModel>("audit_log");
+    let mut entry = mongodb::bson::to_document(doc)?;
+    // the filter sets the position of an inserted document
+    entry.remove("certificate_id");
+    entry.remove("sequence");
+    coll.update_one(
+        doc! {"certificate_id": doc.certificate_id, "sequence": doc.sequence as i64},
+        doc! {"$setOnInsert": entry},
+    )
+    .upsert(true)
+    .await
+}
+
+pub async fn find_last_audit_entry(
+    db: &Database,
+    certificate_id: uuid::Uuid,
+) -> Result<Option<AuditEntryModel>> {
+    let coll = db.collection::<AuditEntryModel>("audit_log");
+    coll.find_one(doc! {"certificate_id": Uuid::from_uuid_1(certificate_id)})
+        .sort(doc! {"sequence": -1})
+        .await
+}
+
+/// Finds the history of a certificate, oldest entry first
+pub async fn find_audit_entries(
+    db: &Database,
+    certificate_id: uuid::Uuid,
+) -> Result<Vec<AuditEntryModel>> {
+    let coll = db.collection::<AuditEntryModel>("audit_log");
+    let cursor = coll
+        .find(doc! {"certificate_id": Uuid::from_uuid_1(certificate_id)})
+        .sort(doc! {"sequence": 1})
+        .await?;
+    cursor.try_collect().await
 }
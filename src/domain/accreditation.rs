@@ -1,7 +1,14 @@
-use chrono::{DateTime, Utc};
+use actix_web::{body::BoxBody, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, SubsecRound, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::error::AccreditationStatusError;
+use crate::{
+    dto::certificate_metadata_dto::AccreditationDto,
+    helpers::respond_with_json,
+    model::{AccreditationModel, AccreditationStatusChangeModel},
+};
+
+use super::error::{AccreditationStatusError, AccreditationTransitionError};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Accreditation {
@@ -10,9 +17,23 @@ pub struct Accreditation {
     pub start_date: DateTime<Utc>,
     pub end_date: Option<DateTime<Utc>>,
     pub status: AccreditationStatus,
+    /// Status when the certificate was issued, `None` when the accreditation was
+    /// attached afterwards
+    pub status_at_issuance: Option<AccreditationStatus>,
+    /// Every status change since the accreditation was attached, oldest first
+    pub history: Vec<AccreditationStatusChange>,
 }
 
+/// Record of an accreditation changing status
 #[derive(Serialize, Deserialize, Debug)]
+pub struct AccreditationStatusChange {
+    pub from: AccreditationStatus,
+    pub to: AccreditationStatus,
+    pub changed_at: DateTime<Utc>,
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum AccreditationStatus {
     Pending,
     Active,
@@ -35,6 +56,90 @@ impl AccreditationStatus {
             _ => Err(AccreditationStatusError),
         }
     }
+
+    /// Indicates if an accreditation can start out with this status
+    pub fn is_initial(&self) -> bool {
+        matches!(
+            self,
+            AccreditationStatus::Pending | AccreditationStatus::Active
+        )
+    }
+
+    /// Indicates if the lifecycle allows moving to the `next` status.
+    ///
+    /// Pending accreditations become active, active ones expire or are revoked,
+    /// expired and revoked are final.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::domain::accreditation::AccreditationStatus;
+    ///
+    /// assert!(AccreditationStatus::Pending.can_become(&AccreditationStatus::Active));
+    /// assert!(AccreditationStatus::Active.can_become(&AccreditationStatus::Revoked));
+    /// assert!(!AccreditationStatus::Pending.can_become(&AccreditationStatus::Expired));
+    /// assert!(!AccreditationStatus::Revoked.can_become(&AccreditationStatus::Active));
+    /// ```
+    pub fn can_become(&self, next: &AccreditationStatus) -> bool {
+        matches!(
+            (self, next),
+            (AccreditationStatus::Pending, AccreditationStatus::Active)
+                | (AccreditationStatus::Active, AccreditationStatus::Expired)
+                | (AccreditationStatus::Active, AccreditationStatus::Revoked)
+        )
+    }
+}
+
+impl Accreditation {
+    /// Indicates if the certificate was issued with an active accreditation
+    pub fn accredited_at_issuance(&self) -> bool {
+        self.status_at_issuance == Some(AccreditationStatus::Active)
+    }
+
+    /// Moves the accreditation to the `next` status, recording the change
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lifecycle does not allow the change
+    pub fn transition(
+        &mut self,
+        next: AccreditationStatus,
+        comment: Option<String>,
+    ) -> Result<(), AccreditationTransitionError> {
+        if !self.status.can_become(&next) {
+            return Err(AccreditationTransitionError {
+                from: self.status.clone(),
+                to: next,
+            });
+        }
+        self.history.push(AccreditationStatusChange {
+            from: std::mem::replace(&mut self.status, next.clone()),
+            to: next,
+            changed_at: Utc::now().trunc_subsecs(3),
+            comment,
+        });
+        Ok(())
+    }
+}
+
+impl Accreditation {
+    /// Marks an active accreditation expired by the expiry job
+    pub(super) fn expire_at(&mut self, at: DateTime<Utc>) {
+        self.history.push(AccreditationStatusChange {
+            from: std::mem::replace(&mut self.status, AccreditationStatus::Expired),
+            to: AccreditationStatus::Expired,
+            changed_at: at,
+            comment: Some("end date passed".to_string()),
+        });
+    }
+}
+
+impl Responder for Accreditation {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
 }
 
 impl std::fmt::Display for AccreditationStatus {
@@ -47,3 +152,57 @@ impl std::fmt::Display for AccreditationStatus {
         }
     }
 }
+
+impl TryFrom<AccreditationStatusChangeModel> for AccreditationStatusChange {
+    type Error = AccreditationStatusError;
+
+    fn try_from(change: AccreditationStatusChangeModel) -> Result<Self, Self::Error> {
+        Ok(AccreditationStatusChange {
+            from: AccreditationStatus::from_status_str(&change.from)?,
+            to: AccreditationStatus::from_status_str(&change.to)?,
+            changed_at: change.changed_at.into(),
+            comment: change.comment,
+        })
+    }
+}
+
+impl TryFrom<AccreditationModel> for Accreditation {
+    type Error = AccreditationStatusError;
+
+    fn try_from(accreditation: AccreditationModel) -> Result<Self, Self::Error> {
+        Ok(Accreditation {
+            status: AccreditationStatus::from_status_str(&accreditation.status)?,
+            name: accreditation.name,
+            institution: accreditation.institution,
+            start_date: accreditation.start_date.into(),
+            end_date: accreditation.end_date.map(|dt| dt.into()),
+            status_at_issuance: accreditation
+                .status_at_issuance
+                .as_deref()
+                .map(AccreditationStatus::from_status_str)
+                .transpose()?,
+            history: accreditation
+                .history
+                .into_iter()
+                .map(AccreditationStatusChange::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Builds a newly attached accreditation, without a status at issuance
+impl TryFrom<AccreditationDto> for Accreditation {
+    type Error = AccreditationStatusError;
+
+    fn try_from(accreditation: AccreditationDto) -> Result<Self, Self::Error> {
+        Ok(Accreditation {
+            status: AccreditationStatus::from_status_str(&accreditation.status)?,
+            name: accreditation.name,
+            institution: accreditation.institution,
+            start_date: accreditation.start_date,
+            end_date: accreditation.end_date,
+            status_at_issuance: None,
+            history: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,223 @@
+use actix_web::{body::BoxBody, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{dto::api_key_dto::ApiKeyDto, helpers::respond_with_json, model::ApiKeyModel};
+
+use super::{
+    base::Id,
+    error::{CertificateParseError, ScopeParseError},
+};
+
+/// Prefix of every API key secret, so leaked keys are easy to recognize
+const SECRET_PREFIX: &str = "crs_";
+
+/// A privilege an API key can be granted
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    #[serde(rename = "certificates:read")]
+    CertificatesRead,
+    #[serde(rename = "certificates:issue")]
+    CertificatesIssue,
+    #[serde(rename = "certificates:update")]
+    CertificatesUpdate,
+    #[serde(rename = "certificates:revoke")]
+    CertificatesRevoke,
+    #[serde(rename = "registry:read")]
+    RegistryRead,
+    #[serde(rename = "registry:write")]
+    RegistryWrite,
+    #[serde(rename = "jobs:run")]
+    JobsRun,
+    #[serde(rename = "keys:manage")]
+    KeysManage,
+}
+
+impl Scope {
+    /// Parses a scope from its name, e.g. `certificates:read`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::domain::api_key::Scope;
+    ///
+    /// assert_eq!(
+    ///     Scope::from_scope_str("certificates:issue").unwrap(),
+    ///     Scope::CertificatesIssue
+    /// );
+    /// assert!(Scope::from_scope_str("certificates:delete").is_err());
+    /// ```
+    pub fn from_scope_str(scope: &str) -> Result<Scope, ScopeParseError> {
+        match scope {
+            "certificates:read" => Ok(Scope::CertificatesRead),
+            "certificates:issue" => Ok(Scope::CertificatesIssue),
+            "certificates:update" => Ok(Scope::CertificatesUpdate),
+            "certificates:revoke" => Ok(Scope::CertificatesRevoke),
+            "registry:read" => Ok(Scope::RegistryRead),
+            "registry:write" => Ok(Scope::RegistryWrite),
+            "jobs:run" => Ok(Scope::JobsRun),
+            "keys:manage" => Ok(Scope::KeysManage),
+            _ => Err(ScopeParseError),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::CertificatesRead => "certificates:read",
+            Scope::CertificatesIssue => "certificates:issue",
+            Scope::CertificatesUpdate => "certificates:update",
+            Scope::CertificatesRevoke => "certificates:revoke",
+            Scope::RegistryRead => "registry:read",
+            Scope::RegistryWrite => "registry:write",
+            Scope::JobsRun => "jobs:run",
+            Scope::KeysManage => "keys:manage",
+        }
+    }
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// Hashes an API key secret the way it is stored.
+///
+/// Secrets are long random strings, so a plain digest is enough to keep them from
+/// being usable when the database leaks.
+pub fn hash_secret(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Generates a new API key secret
+fn generate_secret() -> String {
+    // v4 uuids are drawn from a cryptographically secure generator
+    let mut random = [0u8; 32];
+    random[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+    random[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+    format!("{SECRET_PREFIX}{}", bs58::encode(random).into_string())
+}
+
+/// An API key, only the hash of its secret is ever stored
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    pub id: Id,
+    pub name: String,
+    /// The account whose certificates the key gives access to, keys without one can
+    /// only manage registries, jobs and keys
+    pub account_id: Option<u32>,
+    pub scopes: Vec<Scope>,
+    pub created_date: DateTime<Utc>,
+    pub rotated_date: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    pub secret_hash: String,
+}
+
+impl ApiKey {
+    /// Mints a key with the given id out of its dto, returning the key along with its
+    /// secret, which cannot be recovered later
+    pub fn mint(id: Id, key: ApiKeyDto) -> Result<(ApiKey, String), ScopeParseError> {
+        let secret = generate_secret();
+        let api_key = ApiKey {
+            id,
+            name: key.name,
+            account_id: key.account_id,
+            scopes: key
+                .scopes
+                .iter()
+                .map(|scope| Scope::from_scope_str(scope))
+                .collect::<Result<_, _>>()?,
+            created_date: Utc::now(),
+            rotated_date: None,
+            secret_hash: hash_secret(&secret),
+        };
+        Ok((api_key, secret))
+    }
+
+    /// Key managing the other keys of an account, authenticated by a secret configured
+    /// at startup
+    pub fn admin(id: Id, account_id: u32, secret: &str) -> ApiKey {
+        ApiKey {
+            id,
+            name: "admin".to_string(),
+            account_id: Some(account_id),
+            scopes: vec![Scope::KeysManage],
+            created_date: Utc::now(),
+            rotated_date: None,
+            secret_hash: hash_secret(secret),
+        }
+    }
+
+    /// Replaces the secret of the key, the previous secret stops working at once.
+    ///
+    /// Returns the new secret, which cannot be recovered later.
+    pub fn rotate(&mut self) -> String {
+        let secret = generate_secret();
+        self.secret_hash = hash_secret(&secret);
+        self.rotated_date = Some(Utc::now());
+        secret
+    }
+
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+impl TryFrom<ApiKeyModel> for ApiKey {
+    type Error = CertificateParseError;
+
+    fn try_from(key: ApiKeyModel) -> Result<Self, Self::Error> {
+        Ok(ApiKey {
+            id: Id::parse(key.key_id.into()).map_err(|_| CertificateParseError)?,
+            name: key.name,
+            account_id: key.account_id,
+            scopes: key
+                .scopes
+                .iter()
+                .map(|scope| Scope::from_scope_str(scope).map_err(|_| CertificateParseError))
+                .collect::<Result<_, _>>()?,
+            created_date: key.created_date.into(),
+            rotated_date: key.rotated_date.map(|dt| dt.into()),
+            secret_hash: key.secret_hash,
+        })
+    }
+}
+
+impl Responder for ApiKey {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
+}
+
+pub struct ApiKeys(pub Vec<ApiKey>);
+
+impl Responder for ApiKeys {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self.0)
+    }
+}
+
+/// A freshly minted or rotated key, the only response ever carrying its secret
+#[derive(Serialize)]
+pub struct IssuedApiKey {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    pub secret: String,
+}
+
+impl Responder for IssuedApiKey {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
+}
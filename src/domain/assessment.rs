@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use super::base::{AssessmentResult, Score};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Assessment {
     pub score: Option<Score>,
     pub progress: f32,
@@ -0,0 +1,290 @@
+use actix_web::{body::BoxBody, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{helpers::respond_with_json, model::AuditEntryModel};
+
+use super::{
+    base::Id,
+    certificate::Certificate,
+    error::{AuditActionParseError, CertificateParseError},
+};
+
+/// Hash the first entry of every certificate history is chained to
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// A change of a certificate recorded in the audit log
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Issue,
+    Update,
+    Accredit,
+    ChangeAccreditationStatus,
+    Revoke,
+    Renew,
+    Expire,
+}
+
+impl AuditAction {
+    /// Parses an action from its name, e.g. `revoke`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::domain::audit::AuditAction;
+    ///
+    /// assert_eq!(AuditAction::from_action_str("revoke").unwrap(), AuditAction::Revoke);
+    /// assert!(AuditAction::from_action_str("delete").is_err());
+    /// ```
+    pub fn from_action_str(action: &str) -> Result<AuditAction, AuditActionParseError> {
+        match action {
+            "issue" => Ok(AuditAction::Issue),
+            "update" => Ok(AuditAction::Update),
+            "accredit" => Ok(AuditAction::Accredit),
+            "change_accreditation_status" => Ok(AuditAction::ChangeAccreditationStatus),
+            "revoke" => Ok(AuditAction::Revoke),
+            "renew" => Ok(AuditAction::Renew),
+            "expire" => Ok(AuditAction::Expire),
+            _ => Err(AuditActionParseError),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Issue => "issue",
+            AuditAction::Update => "update",
+            AuditAction::Accredit => "accredit",
+            AuditAction::ChangeAccreditationStatus => "change_accreditation_status",
+            AuditAction::Revoke => "revoke",
+            AuditAction::Renew => "renew",
+            AuditAction::Expire => "expire",
+        }
+    }
+}
+
+impl std::fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// State of a certificate as recorded in its history, the way the API serves it
+pub fn snapshot(certificate: &Certificate) -> Value {
+    serde_json::to_value(certificate).expect("certificate is always serializable")
+}
+
+/// Copy of a JSON value with the keys of every object sorted, so hashes do not
+/// depend on the order the keys were stored in
+fn sorted(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), sorted(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+        other => other.clone(),
+    }
+}
+
+/// An entry of the history of a certificate.
+///
+/// Entries are chained, each one holding the hash of the entry before it, so
+/// changing or removing an entry breaks the chain of every later one.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEntry {
+    pub certificate_id: Id,
+    pub account_id: u32,
+    /// Position of the entry in the history, starting at 1
+    pub sequence: u64,
+    pub action: AuditAction,
+    /// Who made the change, `key:<key id>` for API keys, `user:<user id>` for users
+    /// and `job:<name>` for background jobs
+    pub actor: String,
+    pub recorded_at: DateTime<Utc>,
+    /// The certificate before the change, `None` when it was issued
+    pub before: Option<Value>,
+    /// The certificate after the change
+    pub after: Value,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Records a change of the certificate, chained to the last entry of its history
+    pub fn chain(
+        previous: Option<&AuditEntry>,
+        action: AuditAction,
+        actor: &str,
+        before: Option<Value>,
+        after: &Certificate,
+    ) -> AuditEntry {
+        let mut entry = AuditEntry {
+            certificate_id: after.id.clone(),
+            account_id: after.account_id,
+            sequence: previous.map_or(1, |previous| previous.sequence + 1),
+            action,
+            actor: actor.to_string(),
+            recorded_at: Utc::now().trunc_subsecs(3),
+            before,
+            after: snapshot(after),
+            previous_hash: previous
+                .map_or(GENESIS_HASH.to_string(), |previous| previous.hash.clone()),
+            hash: String::new(),
+        };
+        entry.hash = entry.digest();
+        entry
+    }
+
+    /// SHA-256 hex digest of everything the entry records, including the hash of the
+    /// entry before it
+    pub fn digest(&self) -> String {
+        let content = sorted(&json!({
+            "certificate_id": self.certificate_id.as_uuid(),
+            "account_id": self.account_id,
+            "sequence": self.sequence,
+            "action": self.action,
+            "actor": self.actor,
+            "recorded_at": self.recorded_at.timestamp_millis(),
+            "before": self.before,
+            "after": self.after,
+            "previous_hash": self.previous_hash,
+        }));
+        Sha256::digest(content.to_string().as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+/// Indicates if the entries, oldest first, form an unbroken chain whose entries all
+/// match their hash
+///
+/// # Examples
+///
+/// ```
+/// use crs::domain::audit::is_intact;
+///
+/// assert!(is_intact(&[]));
+/// ```
+pub fn is_intact(entries: &[AuditEntry]) -> bool {
+    let mut previous_hash = GENESIS_HASH;
+    for (index, entry) in entries.iter().enumerate() {
+        if entry.sequence != index as u64 + 1
+            || entry.previous_hash != previous_hash
+            || entry.hash != entry.digest()
+        {
+            return false;
+        }
+        previous_hash = &entry.hash;
+    }
+    true
+}
+
+impl TryFrom<AuditEntryModel> for AuditEntry {
+    type Error = CertificateParseError;
+
+    fn try_from(entry: AuditEntryModel) -> Result<Self, Self::Error> {
+        Ok(AuditEntry {
+            certificate_id: Id::parse(entry.certificate_id.into())
+                .map_err(|_| CertificateParseError)?,
+            account_id: entry.account_id,
+            sequence: entry.sequence,
+            action: AuditAction::from_action_str(&entry.action)
+                .map_err(|_| CertificateParseError)?,
+            actor: entry.actor,
+            recorded_at: entry.recorded_at.into(),
+            before: entry.before,
+            after: entry.after,
+            previous_hash: entry.previous_hash,
+            hash: entry.hash,
+        })
+    }
+}
+
+/// The history of a certificate, oldest change first
+#[derive(Serialize)]
+pub struct AuditTrail {
+    pub certificate_id: Uuid,
+    /// Whether the entries still form an unbroken chain, `false` means the log was
+    /// tampered with
+    pub intact: bool,
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditTrail {
+    pub fn new(certificate_id: Uuid, entries: Vec<AuditEntry>) -> Self {
+        AuditTrail {
+            certificate_id,
+            intact: is_intact(&entries),
+            entries,
+        }
+    }
+}
+
+impl Responder for AuditTrail {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        domain::revocation::{Revocation, RevocationReason},
+        test_helpers::certificate_for,
+    };
+
+    use super::{is_intact, snapshot, AuditAction, AuditEntry};
+
+    #[test]
+    fn tampering_should_break_the_chain() {
+        let mut certificate = certificate_for(Uuid::new_v4());
+        let issued = AuditEntry::chain(None, AuditAction::Issue, "key:a", None, &certificate);
+        let before = snapshot(&certificate);
+        certificate
+            .revoke(Revocation {
+                reason: RevocationReason::IssuedInError,
+                revoked_at: Utc::now(),
+                comment: None,
+            })
+            .unwrap();
+        let revoked = AuditEntry::chain(
+            Some(&issued),
+            AuditAction::Revoke,
+            "user:b",
+            Some(before),
+            &certificate,
+        );
+        assert_eq!(revoked.sequence, 2);
+        assert_eq!(revoked.previous_hash, issued.hash);
+        assert!(is_intact(&[issued.clone(), revoked.clone()]));
+
+        let mut edited = issued.clone();
+        edited.actor = "key:c".to_string();
+        assert!(!is_intact(&[edited.clone(), revoked.clone()]));
+        edited.hash = edited.digest();
+        assert!(!is_intact(&[edited, revoked.clone()]));
+
+        let mut rewritten = revoked.clone();
+        rewritten.after["status"] = json!("Active");
+        assert!(!is_intact(&[issued.clone(), rewritten]));
+
+        assert!(!is_intact(&[revoked]));
+    }
+}
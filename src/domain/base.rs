@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::error::{InvalidEmailError, InvalidIdError, InvalidPhoneError};
+use crate::model::AddressModel;
 
-#[derive(Serialize, Deserialize, Debug)]
+use super::error::{
+    AssessmentResultError, InvalidEmailError, InvalidIdError, InvalidPhoneError, InvalidScoreError,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Id(pub Uuid);
 
 impl Id {
@@ -20,7 +24,7 @@ impl Id {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Name {
     pub first_name: String,
     pub middle_name: Option<String>,
@@ -37,7 +41,7 @@ impl std::fmt::Display for Name {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Email(pub String);
 
 impl Email {
@@ -54,9 +58,24 @@ impl Email {
     pub fn as_string(&self) -> String {
         self.0.clone()
     }
+
+    /// Lowercase form without surrounding whitespace, used to tell whether two
+    /// addresses belong to the same person
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::domain::base::Email;
+    ///
+    /// let email = Email::parse("John.Doe@Email.com".to_string()).unwrap();
+    /// assert_eq!(email.normalized(), "john.doe@email.com");
+    /// ```
+    pub fn normalized(&self) -> String {
+        self.0.trim().to_lowercase()
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Phone(String);
 
 impl Phone {
@@ -75,7 +94,7 @@ impl Phone {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Address {
     pub street: String,
     pub city: String,
@@ -103,7 +122,19 @@ impl Default for Address {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl From<AddressModel> for Address {
+    fn from(address: AddressModel) -> Self {
+        Address {
+            street: address.street,
+            city: address.city,
+            building_number: address.building_number,
+            country: address.country,
+            postal_code: address.postal_code,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Score {
     pub value: u32,
     pub max: u32,
@@ -111,19 +142,84 @@ pub struct Score {
     pub passing_score: u32,
 }
 
+/// Upper bound of the scale used when a certificate carries no scale of its own
+pub const DEFAULT_MAX_SCORE: u32 = 100;
+
 impl Score {
-    pub fn new(value: u32, max: u32, min: u32, passing_score: u32) -> Self {
-        Score {
+    /// Builds a score, rejecting a scale whose maximum does not exceed its minimum, or a
+    /// value or passing score outside of the scale
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::domain::base::{AssessmentResult, Score};
+    ///
+    /// let score = Score::new(45, 60, 0, 50).unwrap();
+    /// assert_eq!(score.result(), AssessmentResult::Fail);
+    /// assert!(Score::new(70, 60, 0, 50).is_err());
+    /// assert!(Score::new(40, 60, 0, 80).is_err());
+    /// assert!(Score::new(50, 50, 50, 50).is_err());
+    /// ```
+    pub fn new(
+        value: u32,
+        max: u32,
+        min: u32,
+        passing_score: u32,
+    ) -> Result<Self, InvalidScoreError> {
+        if max <= min || !(min..=max).contains(&value) || !(min..=max).contains(&passing_score) {
+            return Err(InvalidScoreError);
+        }
+        Ok(Score {
             value,
             max,
             min,
             passing_score,
+        })
+    }
+
+    /// Score on the scale from 0 to 100 without a passing threshold, used when
+    /// no scale was provided
+    pub fn with_default_scale(value: u32) -> Result<Self, InvalidScoreError> {
+        Score::new(value, DEFAULT_MAX_SCORE, 0, 0)
+    }
+
+    /// Same scale with another achieved value
+    pub fn with_value(&self, value: u32) -> Result<Self, InvalidScoreError> {
+        Score::new(value, self.max, self.min, self.passing_score)
+    }
+
+    /// The assessment is passed once the value reaches the passing score
+    pub fn result(&self) -> AssessmentResult {
+        if self.value >= self.passing_score {
+            AssessmentResult::Pass
+        } else {
+            AssessmentResult::Fail
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum AssessmentResult {
     Fail,
     Pass,
 }
+
+impl AssessmentResult {
+    /// Converts a string literal to an AssessmentResult
+    pub fn from_result_str(result: &str) -> Result<AssessmentResult, AssessmentResultError> {
+        match result {
+            "Fail" | "fail" => Ok(AssessmentResult::Fail),
+            "Pass" | "pass" => Ok(AssessmentResult::Pass),
+            _ => Err(AssessmentResultError),
+        }
+    }
+}
+
+impl std::fmt::Display for AssessmentResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssessmentResult::Fail => write!(f, "Fail"),
+            AssessmentResult::Pass => write!(f, "Pass"),
+        }
+    }
+}
@@ -1,54 +1,373 @@
-use actix_web::{body::BoxBody, http::header::ContentType, HttpRequest, HttpResponse, Responder};
-use chrono::{DateTime, Utc};
+use actix_web::{
+    body::BoxBody,
+    http::header::{EntityTag, TryIntoHeaderValue, ETAG},
+    HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use chrono::{DateTime, SubsecRound, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{dto::certificate_dto::CertificateDto, model::CertificateModel};
+use crate::{
+    dto::{certificate_dto::CertificateDto, certificate_update_dto::CertificateUpdateDto},
+    error::CrsError,
+    export::{
+        open_badges::{
+            accepts_open_badge_assertion, accepts_open_badge_credential, assertion_for_request,
+            open_badge_credential_for_request,
+        },
+        verifiable_credential::{accepts_verifiable_credential, credential_for_request},
+    },
+    helpers::respond_with_json,
+    model::{CertificateModel, CERTIFICATE_SCHEMA_VERSION},
+};
 
 use super::{
+    accreditation::{Accreditation, AccreditationStatus},
     assessment::Assessment,
-    base::{AssessmentResult, Email, Id, Name, Score},
-    error::CertificateParseError,
+    base::{AssessmentResult, Email, Id, Name, Phone, Score},
+    error::{
+        AccreditationChangeError, AccreditationExistsError, CertificateDetailsMissingError,
+        CertificateParseError, CertificateRenewalError, CertificateRenewedError,
+        CertificateRevokedError, CertificateUpdateError, MissingAccreditationError,
+    },
     organization::Organization,
-    person::Person,
-    validity::Validity,
+    person::{CertificateRecipient, Person},
+    product::Product,
+    revocation::{CertificateStatus, Revocation},
+    signature::CertificateSignature,
+    validity::{ValidUntil, Validity},
 };
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Certificate {
     pub id: Id,
     // The person who received the certificate
-    pub recipient: Person,
+    pub recipient: CertificateRecipient,
     pub account_id: u32,
     pub product_id: u32,
     pub name: String,
     pub description: String,
-    // An organization that issued the certificate
-    pub authority: Organization,
+    // An organization that issued the certificate, absent from version 1 documents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authority: Option<Organization>,
     pub validity: Option<Validity>,
     pub assessment: Assessment,
+    pub accreditation: Option<Accreditation>,
+    pub status: CertificateStatus,
+    pub revocation: Option<Revocation>,
+    // When the expiry job marked the certificate expired
+    pub expired_at: Option<DateTime<Utc>>,
+    // The certificate this one renews, and the certificate that renewed this one
+    pub renewal_of: Option<Id>,
+    pub renewed_by: Option<Id>,
+    pub signature: Option<CertificateSignature>,
+    // Set once the certificate was signed, a missing signature then means it was stripped
+    pub signature_required: bool,
+    // Incremented on every change, used for optimistic concurrency
+    pub version: u32,
     pub created_date: DateTime<Utc>,
     pub updated_date: Option<DateTime<Utc>>,
 }
 
-fn respond_with_json<T: Serialize>(obj: T) -> HttpResponse<BoxBody> {
-    match serde_json::to_string(&obj) {
-        Ok(body) => HttpResponse::Ok()
-            .content_type(ContentType::json())
-            .body(body),
-        Err(_) => HttpResponse::BadRequest().body("Unable to serialize the response"),
+impl Certificate {
+    /// Revokes the certificate, keeping the revocation record alongside it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the certificate has already been revoked
+    pub fn revoke(&mut self, revocation: Revocation) -> Result<(), CertificateRevokedError> {
+        if self.status == CertificateStatus::Revoked {
+            return Err(CertificateRevokedError);
+        }
+        self.status = CertificateStatus::Revoked;
+        self.revocation = Some(revocation);
+        self.touch();
+        Ok(())
+    }
+
+    /// Applies a partial update, leaving the creation date and absent fields unchanged.
+    ///
+    /// A new score is checked against the scale of the certificate and decides the
+    /// assessment result, nothing is changed when it lies outside of the scale
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the certificate is revoked or the score lies outside of its scale
+    pub fn apply(&mut self, update: CertificateUpdateDto) -> Result<(), CertificateUpdateError> {
+        if self.status == CertificateStatus::Revoked {
+            return Err(CertificateUpdateError::Revoked(CertificateRevokedError));
+        }
+        if let Some(value) = update.score {
+            let score = match self.assessment.score.as_ref() {
+                Some(score) => score.with_value(value)?,
+                None => Score::with_default_scale(value)?,
+            };
+            self.assessment.result = score.result();
+            self.assessment.score = Some(score);
+        }
+        if let Some(progress) = update.progress {
+            self.assessment.progress = progress;
+        }
+        if let Some(validity) = update.validity {
+            // MongoDB stores dates with millisecond precision
+            let valid_from = validity.valid_from.trunc_subsecs(3);
+            let first_valid_from = self
+                .validity
+                .as_ref()
+                .map_or(valid_from, |current| current.first_valid_from);
+            self.validity = Some(Validity {
+                first_valid_from,
+                valid_from,
+                valid_until: validity
+                    .valid_until
+                    .map_or(ValidUntil::EndOfTime, |valid_until| {
+                        ValidUntil::Expiry(valid_until.trunc_subsecs(3))
+                    }),
+            });
+            // an extended validity brings an expired certificate back
+            if self.status == CertificateStatus::Expired
+                && self
+                    .validity
+                    .as_ref()
+                    .is_some_and(|validity| !validity.is_expired_at(Utc::now()))
+            {
+                self.status = CertificateStatus::Active;
+                self.expired_at = None;
+            }
+        }
+        if let Some(description) = update.description {
+            self.description = description;
+        }
+        self.touch();
+        Ok(())
+    }
+
+    /// Replaces the recipient details with the registered ones after the recipient
+    /// was updated in the registry
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the certificate is revoked, it keeps the details it was
+    /// revoked with
+    pub fn refresh_recipient(&mut self, recipient: Person) -> Result<(), CertificateRevokedError> {
+        if self.status == CertificateStatus::Revoked {
+            return Err(CertificateRevokedError);
+        }
+        self.recipient = recipient.into();
+        self.touch();
+        Ok(())
+    }
+
+    /// Attaches an accreditation granted after issuance
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the certificate already has an accreditation
+    pub fn accredit(
+        &mut self,
+        accreditation: Accreditation,
+    ) -> Result<(), AccreditationExistsError> {
+        if self.accreditation.is_some() {
+            return Err(AccreditationExistsError);
+        }
+        self.accreditation = Some(accreditation);
+        self.touch();
+        Ok(())
+    }
+
+    /// Moves the accreditation of the certificate to the `next` status
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the certificate has no accreditation or the lifecycle
+    /// does not allow the change
+    pub fn change_accreditation_status(
+        &mut self,
+        next: AccreditationStatus,
+        comment: Option<String>,
+    ) -> Result<(), AccreditationChangeError> {
+        self.accreditation
+            .as_mut()
+            .ok_or(AccreditationChangeError::Missing(MissingAccreditationError))?
+            .transition(next, comment)
+            .map_err(AccreditationChangeError::Transition)?;
+        self.touch();
+        Ok(())
+    }
+
+    /// Marks the certificate and its accreditation expired when their validity ended
+    /// at the given point in time, reporting what changed.
+    ///
+    /// Revoked certificates stay revoked, their accreditation still expires.
+    pub fn expire_at(&mut self, at: DateTime<Utc>) -> Expiration {
+        let mut expiration = Expiration::default();
+        if self.status == CertificateStatus::Active
+            && self
+                .validity
+                .as_ref()
+                .is_some_and(|validity| validity.is_expired_at(at))
+        {
+            self.status = CertificateStatus::Expired;
+            self.expired_at = Some(at);
+            expiration.certificate = true;
+        }
+        if let Some(accreditation) = self.accreditation.as_mut() {
+            if accreditation.status == AccreditationStatus::Active
+                && accreditation
+                    .end_date
+                    .is_some_and(|end_date| end_date <= at)
+            {
+                accreditation.expire_at(at);
+                expiration.accreditation = true;
+            }
+        }
+        if expiration.certificate || expiration.accreditation {
+            self.touch();
+        }
+        expiration
+    }
+
+    /// Issues the successor of the certificate for a new validity window and links
+    /// both certificates, the successor keeps the first validity start of the chain.
+    ///
+    /// Without `valid_from` the window starts now, without `valid_until` it is as
+    /// long as the current window. Accreditations are not carried over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the certificate is revoked or was already renewed
+    pub fn renew(
+        &mut self,
+        valid_from: Option<DateTime<Utc>>,
+        valid_until: Option<DateTime<Utc>>,
+    ) -> Result<Certificate, CertificateRenewalError> {
+        if self.status == CertificateStatus::Revoked {
+            return Err(CertificateRenewalError::Revoked(CertificateRevokedError));
+        }
+        if self.renewed_by.is_some() {
+            return Err(CertificateRenewalError::Renewed(CertificateRenewedError));
+        }
+
+        let now = Utc::now().trunc_subsecs(3);
+        let valid_from = valid_from.map_or(now, |valid_from| valid_from.trunc_subsecs(3));
+        let valid_until = match (valid_until, &self.validity) {
+            (Some(valid_until), _) => ValidUntil::Expiry(valid_until.trunc_subsecs(3)),
+            (None, Some(validity)) => match validity.valid_until {
+                ValidUntil::Expiry(expiry) => {
+                    ValidUntil::Expiry(valid_from + (expiry - validity.valid_from))
+                }
+                ValidUntil::EndOfTime => ValidUntil::EndOfTime,
+            },
+            (None, None) => ValidUntil::EndOfTime,
+        };
+        let first_valid_from = self
+            .validity
+            .as_ref()
+            .map_or(self.created_date, |validity| validity.first_valid_from);
+
+        let successor = Certificate {
+            id: Id::parse(Uuid::new_v4()).unwrap(),
+            recipient: self.recipient.clone(),
+            account_id: self.account_id,
+            product_id: self.product_id,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            authority: self.authority.clone(),
+            validity: Some(Validity {
+                first_valid_from,
+                valid_from,
+                valid_until,
+            }),
+            assessment: self.assessment.clone(),
+            accreditation: None,
+            status: CertificateStatus::Active,
+            revocation: None,
+            expired_at: None,
+            renewal_of: Some(self.id.clone()),
+            renewed_by: None,
+            signature: None,
+            signature_required: false,
+            version: 1,
+            created_date: now,
+            updated_date: None,
+        };
+        self.renewed_by = Some(successor.id.clone());
+        self.touch();
+        Ok(successor)
+    }
+
+    /// Id of the issuing organization, unknown for version 1 documents
+    pub fn authority_id(&self) -> Option<Uuid> {
+        self.authority
+            .as_ref()
+            .map(|authority| authority.id.as_uuid())
+    }
+
+    /// Recipient name and email and the issuing authority, which the exports need
+    pub fn issued_details(
+        &self,
+    ) -> Result<(&Name, &Email, &Organization), CertificateDetailsMissingError> {
+        match (&self.recipient.name, &self.recipient.email, &self.authority) {
+            (Some(name), Some(email), Some(authority)) => Ok((name, email, authority)),
+            _ => Err(CertificateDetailsMissingError),
+        }
+    }
+
+    /// Records a change by bumping the version and the update date
+    fn touch(&mut self) {
+        self.version += 1;
+        self.updated_date = Some(Utc::now().trunc_subsecs(3));
+    }
+
+    /// Entity tag of the current version
+    pub fn etag(&self) -> EntityTag {
+        EntityTag::new_strong(self.version.to_string())
     }
 }
 
 /// Implement the responder for the Certificate
+///
+/// Requests accepting `application/vc+ld+json` receive the certificate as a verifiable credential
+/// (an Open Badges 3.0 credential when the Open Badges profile is requested) and requests
+/// accepting `application/ld+json` receive an Open Badges 2.0 assertion.
 impl Responder for Certificate {
     type Body = BoxBody;
 
-    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
-        respond_with_json(self)
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        if accepts_open_badge_credential(req) {
+            return match open_badge_credential_for_request(&self, req) {
+                Ok(credential) => credential.respond_to(req),
+                Err(err) => CrsError::from(err).error_response(),
+            };
+        }
+        if accepts_open_badge_assertion(req) {
+            return match assertion_for_request(&self, req) {
+                Ok(assertion) => assertion.respond_to(req),
+                Err(err) => CrsError::from(err).error_response(),
+            };
+        }
+        if accepts_verifiable_credential(req) {
+            return match credential_for_request(&self, req) {
+                Ok(credential) => credential.respond_to(req),
+                Err(err) => CrsError::from(err).error_response(),
+            };
+        }
+        let etag = self.etag();
+        let mut response = respond_with_json(self);
+        if let Ok(etag) = etag.try_into_value() {
+            response.headers_mut().insert(ETAG, etag);
+        }
+        response
     }
 }
 
+/// What expired when a certificate was checked for expiry
+#[derive(Debug, Default, PartialEq)]
+pub struct Expiration {
+    pub certificate: bool,
+    pub accreditation: bool,
+}
+
 pub struct Certificates(pub Vec<Certificate>);
 
 impl Responder for Certificates {
@@ -63,62 +382,171 @@ impl TryFrom<CertificateModel> for Certificate {
     type Error = CertificateParseError;
 
     fn try_from(certificate: CertificateModel) -> Result<Self, Self::Error> {
-        Ok(Certificate {
-            id: match Id::parse(certificate.certificate_id.into()) {
-                Ok(id) => id,
-                Err(invalid_id_error) => panic!("{}", invalid_id_error),
+        let recipient_id =
+            Id::parse(certificate.user_id.into()).map_err(|_| CertificateParseError)?;
+        // version 1 documents only hold the recipient id, their details and the issuing
+        // authority stay absent, also once such a certificate was changed and written back
+        let recipient = match certificate.recipient {
+            Some(recipient) => CertificateRecipient {
+                id: recipient_id,
+                name: Some(Name {
+                    first_name: recipient.first_name,
+                    middle_name: recipient.middle_name,
+                    last_name: recipient.last_name,
+                }),
+                email: Some(Email(recipient.email)),
+                phone: recipient
+                    .phone
+                    .map(Phone::parse)
+                    .transpose()
+                    .map_err(|_| CertificateParseError)?,
+            },
+            None => CertificateRecipient {
+                id: recipient_id,
+                name: None,
+                email: None,
+                phone: None,
             },
-            recipient: Person::default(),
+        };
+        let authority = certificate
+            .authority
+            .map(Organization::try_from)
+            .transpose()?;
+        let score = certificate
+            .metadata
+            .score_scale
+            .map(|scale| {
+                Score::new(
+                    certificate.metadata.score,
+                    scale.max,
+                    scale.min,
+                    scale.passing_score,
+                )
+            })
+            .transpose()
+            .map_err(|_| CertificateParseError)?;
+
+        Ok(Certificate {
+            id: Id::parse(certificate.certificate_id.into()).map_err(|_| CertificateParseError)?,
+            recipient,
             account_id: certificate.account_id,
             product_id: certificate.product_id,
-            name: "".to_string(),
-            description: "".to_string(),
-            authority: Organization::default(),
-            validity: None,
+            name: certificate.name,
+            description: certificate.description,
+            authority,
+            validity: certificate.validity.map(Validity::from),
             assessment: Assessment {
-                score: None,
+                score,
                 progress: certificate.metadata.progress,
-                result: AssessmentResult::Pass,
+                result: certificate
+                    .metadata
+                    .result
+                    .unwrap_or(AssessmentResult::Pass),
             },
+            accreditation: certificate
+                .accreditation
+                .map(Accreditation::try_from)
+                .transpose()
+                .map_err(|_| CertificateParseError)?
+                .map(|mut accreditation| {
+                    // accreditations of older documents were given at issuance and never changed
+                    if certificate.schema_version < CERTIFICATE_SCHEMA_VERSION {
+                        accreditation.status_at_issuance = Some(accreditation.status.clone());
+                    }
+                    accreditation
+                }),
+            status: certificate.status,
+            revocation: certificate
+                .revocation
+                .map(Revocation::try_from)
+                .transpose()
+                .map_err(|_| CertificateParseError)?,
+            expired_at: certificate.expired_at.map(|dt| dt.into()),
+            renewal_of: certificate
+                .renewal_of
+                .map(|id| Id::parse(id.into()))
+                .transpose()
+                .map_err(|_| CertificateParseError)?,
+            renewed_by: certificate
+                .renewed_by
+                .map(|id| Id::parse(id.into()))
+                .transpose()
+                .map_err(|_| CertificateParseError)?,
+            // signatures written before the flag was stored require it all the same
+            signature_required: certificate.signature_required || certificate.signature.is_some(),
+            signature: certificate.signature.map(CertificateSignature::from),
+            version: certificate.version,
             created_date: certificate.created_date.into(),
             updated_date: certificate.updated_date.map(|dt| dt.into()),
         })
     }
 }
 
-impl TryFrom<CertificateDto> for Certificate {
-    type Error = CertificateParseError;
-
-    fn try_from(certificate: CertificateDto) -> Result<Self, Self::Error> {
+impl Certificate {
+    /// Issues a new certificate to the registered recipient on behalf of the resolved
+    /// issuing organization, the recipient details of the dto are not used.
+    ///
+    /// A registered product names and describes the certificate and decides how long
+    /// it stays valid from the date it was acquired.
+    pub fn issue(
+        certificate: CertificateDto,
+        recipient: Person,
+        authority: Organization,
+        product: Option<&Product>,
+    ) -> Result<Certificate, CertificateParseError> {
+        let score = match &certificate.metadata.score_scale {
+            Some(scale) => Score::new(
+                certificate.metadata.score,
+                scale.max,
+                scale.min,
+                scale.passing_score,
+            ),
+            None => Score::with_default_scale(certificate.metadata.score),
+        }
+        .map_err(|_| CertificateParseError)?;
+        // MongoDB stores dates with millisecond precision
+        let created_date = Utc::now().trunc_subsecs(3);
+        let acquired_date = certificate
+            .metadata
+            .acquired_date
+            .map_or(created_date, |acquired_date| acquired_date.trunc_subsecs(3));
         Ok(Certificate {
             id: Id::parse(Uuid::new_v4()).unwrap(),
-            recipient: Person {
-                id: Id(certificate.recipient.id),
-                name: Name {
-                    first_name: certificate.recipient.first_name,
-                    middle_name: None,
-                    last_name: certificate.recipient.last_name,
-                },
-                email: Email(certificate.recipient.email),
-                phone: None,
-            },
+            recipient: recipient.into(),
             account_id: certificate.account_id,
             product_id: certificate.product_id,
-            name: "".to_string(),
-            description: "".to_string(),
-            authority: Organization::default(),
-            validity: None,
+            name: product
+                .map(|product| product.name.clone())
+                .unwrap_or_default(),
+            description: product
+                .map(|product| product.description.clone())
+                .unwrap_or_default(),
+            authority: Some(authority),
+            validity: product.map(|product| product.validity_from(acquired_date)),
             assessment: Assessment {
-                score: Some(Score::new(
-                    certificate.metadata.score,
-                    certificate.metadata.score,
-                    0,
-                    certificate.metadata.score,
-                )),
+                result: score.result(),
+                score: Some(score),
                 progress: certificate.metadata.progress,
-                result: AssessmentResult::Pass,
             },
-            created_date: Utc::now(),
+            accreditation: certificate
+                .metadata
+                .accreditation
+                .map(Accreditation::try_from)
+                .transpose()
+                .map_err(|_| CertificateParseError)?
+                .map(|mut accreditation| {
+                    accreditation.status_at_issuance = Some(accreditation.status.clone());
+                    accreditation
+                }),
+            status: CertificateStatus::Active,
+            revocation: None,
+            expired_at: None,
+            renewal_of: None,
+            renewed_by: None,
+            signature: None,
+            signature_required: false,
+            version: 1,
+            created_date,
             updated_date: None,
         })
     }
@@ -126,18 +554,29 @@ impl TryFrom<CertificateDto> for Certificate {
 
 #[cfg(test)]
 mod tests {
-    use chrono::Utc;
-    use mongodb::bson::{DateTime, Uuid as BsonUuid};
+    use chrono::{Duration, SubsecRound, Utc};
+    use mongodb::bson::{self, doc, Bson, DateTime, Uuid as BsonUuid};
     use pretty_assertions::assert_eq;
     use uuid::Uuid;
 
     use crate::{
-        domain::certificate::Certificate,
+        domain::{
+            base::{AssessmentResult, Score},
+            certificate::Certificate,
+            validity::{ValidUntil, Validity},
+        },
         dto::{
-            certificate_dto::CertificateDto, certificate_metadata_dto::CertificateMetadataDto,
+            certificate_dto::CertificateDto,
+            certificate_metadata_dto::{AccreditationDto, CertificateMetadataDto},
+            certificate_update_dto::{CertificateUpdateDto, ValidityDto},
             recipient_dto::RecipientDto,
         },
-        model::{CertificateMetadataModel, CertificateModel},
+        helpers::SaveType,
+        model::{
+            AccreditationModel, CertificateMetadataModel, CertificateModel, OrganizationModel,
+            PersonModel, CERTIFICATE_SCHEMA_VERSION,
+        },
+        test_helpers::{certificate_for, organization, recipient},
     };
 
     #[test]
@@ -145,6 +584,7 @@ mod tests {
         let certificate_dto = CertificateDto {
             account_id: 1,
             product_id: 1,
+            organization_id: Uuid::new_v4(),
             recipient: RecipientDto {
                 id: Uuid::new_v4(),
                 first_name: "John".to_string(),
@@ -157,10 +597,12 @@ mod tests {
                 progress: 0.5,
                 acquired_date: None,
                 accreditation: None,
+                score_scale: None,
             },
         };
         let user_id = certificate_dto.recipient.id;
-        let certificate = Certificate::try_from(certificate_dto).unwrap();
+        let certificate =
+            Certificate::issue(certificate_dto, recipient(user_id), organization(), None).unwrap();
 
         assert_eq!(certificate.recipient.id.as_uuid(), user_id);
         assert!(certificate.created_date.timestamp() > 0);
@@ -168,15 +610,33 @@ mod tests {
 
     #[test]
     fn parse_certificate_model_should_succeed() {
+        let user_id = Uuid::new_v4();
         let certificate_model = CertificateModel {
-            certificate_id: BsonUuid::default(),
-            user_id: BsonUuid::default(),
+            schema_version: CERTIFICATE_SCHEMA_VERSION,
+            certificate_id: BsonUuid::new(),
+            user_id: BsonUuid::from_uuid_1(user_id),
             account_id: 1,
             product_id: 1,
+            recipient: Some(PersonModel::from_domain(&recipient(user_id))),
+            name: "".to_string(),
+            description: "".to_string(),
+            authority: Some(OrganizationModel::from_domain(&organization())),
+            validity: None,
             metadata: CertificateMetadataModel {
                 score: 0,
                 progress: 0.5,
+                score_scale: None,
+                result: None,
             },
+            accreditation: None,
+            status: Default::default(),
+            revocation: None,
+            expired_at: None,
+            renewal_of: None,
+            renewed_by: None,
+            signature: None,
+            signature_required: false,
+            version: 0,
             created_date: DateTime::from_chrono(Utc::now()),
             updated_date: None,
         };
@@ -188,4 +648,157 @@ mod tests {
             certificate_id
         );
     }
+
+    #[test]
+    fn certificate_model_should_round_trip_every_field() {
+        let user_id = Uuid::new_v4();
+        let mut certificate = Certificate::issue(
+            CertificateDto {
+                account_id: 1,
+                product_id: 1,
+                organization_id: Uuid::new_v4(),
+                recipient: RecipientDto {
+                    id: user_id,
+                    first_name: "John".to_string(),
+                    last_name: "Doe".to_string(),
+                    email: "john.doe@email.com".to_string(),
+                    phone: "+45 12345678".to_string(),
+                },
+                metadata: CertificateMetadataDto {
+                    score: 80,
+                    progress: 0.75,
+                    acquired_date: None,
+                    score_scale: None,
+                    accreditation: Some(AccreditationDto {
+                        name: "ISO 9001".to_string(),
+                        institution: "ISO".to_string(),
+                        start_date: Utc::now().trunc_subsecs(3),
+                        end_date: None,
+                        status: "active".to_string(),
+                    }),
+                },
+            },
+            recipient(user_id),
+            organization(),
+            None,
+        )
+        .unwrap();
+        certificate.name = "Rust fundamentals".to_string();
+        certificate.description = "Completed the Rust fundamentals course".to_string();
+        certificate.validity = Some(Validity {
+            first_valid_from: certificate.created_date,
+            valid_from: certificate.created_date,
+            valid_until: ValidUntil::Expiry(certificate.created_date + Duration::days(365)),
+        });
+
+        let model = CertificateModel::from_domain(&certificate, SaveType::Insert);
+        let document = bson::to_document(&model).unwrap();
+        let model: CertificateModel = bson::from_document(document).unwrap();
+        let read_back = Certificate::try_from(model).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&read_back).unwrap(),
+            serde_json::to_value(&certificate).unwrap()
+        );
+        assert!(read_back.accreditation.unwrap().accredited_at_issuance());
+    }
+
+    #[test]
+    fn accreditation_of_version_2_document_should_count_as_given_at_issuance() {
+        let mut model =
+            CertificateModel::from_domain(&certificate_for(Uuid::new_v4()), SaveType::Insert);
+        model.schema_version = 2;
+        model.accreditation = Some(AccreditationModel {
+            name: "ISO 9001".to_string(),
+            institution: "ISO".to_string(),
+            start_date: DateTime::now(),
+            end_date: None,
+            status: "Active".to_string(),
+            status_at_issuance: None,
+            history: Vec::new(),
+        });
+
+        let certificate = Certificate::try_from(model).unwrap();
+
+        assert!(certificate.accreditation.unwrap().accredited_at_issuance());
+    }
+
+    #[test]
+    fn apply_score_should_recompute_result_on_the_certificate_scale() {
+        let mut certificate = certificate_for(Uuid::new_v4());
+        certificate.assessment.score = Some(Score::new(55, 60, 0, 50).unwrap());
+
+        certificate
+            .apply(CertificateUpdateDto {
+                score: Some(45),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(certificate.assessment.result, AssessmentResult::Fail);
+
+        let version = certificate.version;
+        let out_of_scale = certificate.apply(CertificateUpdateDto {
+            score: Some(70),
+            ..Default::default()
+        });
+        assert!(out_of_scale.is_err());
+        assert_eq!(certificate.assessment.score.as_ref().unwrap().value, 45);
+        assert_eq!(certificate.version, version);
+    }
+
+    #[test]
+    fn apply_validity_should_keep_millisecond_precision() {
+        let mut certificate = certificate_for(Uuid::new_v4());
+        let valid_from = Utc::now();
+        let valid_until = valid_from + Duration::days(30);
+
+        certificate
+            .apply(CertificateUpdateDto {
+                validity: Some(ValidityDto {
+                    valid_from,
+                    valid_until: Some(valid_until),
+                }),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let validity = certificate.validity.unwrap();
+        assert_eq!(validity.valid_from, valid_from.trunc_subsecs(3));
+        assert!(matches!(
+            validity.valid_until,
+            ValidUntil::Expiry(expiry) if expiry == valid_until.trunc_subsecs(3)
+        ));
+    }
+
+    #[test]
+    fn parse_version_1_document_should_leave_missing_details_absent() {
+        let user_id = Uuid::new_v4();
+        let document = doc! {
+            "certificate_id": BsonUuid::new(),
+            "user_id": BsonUuid::from_uuid_1(user_id),
+            "account_id": 20,
+            "product_id": 15,
+            "metadata": { "score": 0, "progress": 0.5 },
+            "created_date": DateTime::now(),
+            "updated_date": Bson::Null,
+        };
+
+        let model: CertificateModel = bson::from_document(document).unwrap();
+        assert_eq!(model.schema_version, 1);
+
+        let certificate = Certificate::try_from(model).unwrap();
+        assert_eq!(certificate.recipient.id.as_uuid(), user_id);
+        assert_eq!(certificate.recipient.name, None);
+        assert_eq!(certificate.recipient.email, None);
+        assert!(certificate.authority.is_none());
+        assert!(certificate.issued_details().is_err());
+
+        let json = serde_json::to_value(&certificate).unwrap();
+        assert_eq!(json["recipient"], serde_json::json!({ "id": user_id }));
+        assert!(json.get("authority").is_none());
+
+        let written = CertificateModel::from_domain(&certificate, SaveType::Update);
+        assert!(written.recipient.is_none());
+        assert!(written.authority.is_none());
+    }
 }
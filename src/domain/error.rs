@@ -2,6 +2,8 @@ use std::error::Error;
 
 use serde::{Deserialize, Serialize};
 
+use super::accreditation::AccreditationStatus;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AccreditationStatusError;
 
@@ -76,3 +78,310 @@ impl std::fmt::Display for CertificateParseError {
         "unable to parse into a valid certificate".fmt(f)
     }
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CertificateDetailsMissingError;
+
+impl Error for CertificateDetailsMissingError {
+    fn description(&self) -> &str {
+        "certificate details missing"
+    }
+}
+
+impl std::fmt::Display for CertificateDetailsMissingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "certificate was stored without its recipient details and issuing authority, it must be reissued".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RevocationReasonError;
+
+impl Error for RevocationReasonError {
+    fn description(&self) -> &str {
+        "failed to parse revocation reason"
+    }
+}
+
+impl std::fmt::Display for RevocationReasonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "provided string was invalid, allowed values are `issued_in_error`, `superseded`, `fraud`, `withdrawn`, or `other`".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CertificateRevokedError;
+
+impl Error for CertificateRevokedError {
+    fn description(&self) -> &str {
+        "certificate is revoked"
+    }
+}
+
+impl std::fmt::Display for CertificateRevokedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "certificate has already been revoked".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CertificateStatusError;
+
+impl Error for CertificateStatusError {
+    fn description(&self) -> &str {
+        "failed to parse certificate status"
+    }
+}
+
+impl std::fmt::Display for CertificateStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "provided string was invalid, allowed values are `active`, `expired` or `revoked`".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AssessmentResultError;
+
+impl Error for AssessmentResultError {
+    fn description(&self) -> &str {
+        "failed to parse assessment result"
+    }
+}
+
+impl std::fmt::Display for AssessmentResultError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "provided string was invalid, allowed values are `pass` or `fail`".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InvalidCursorError;
+
+impl Error for InvalidCursorError {
+    fn description(&self) -> &str {
+        "failed to parse cursor"
+    }
+}
+
+impl std::fmt::Display for InvalidCursorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "provided cursor is not valid".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CertificateQueryError;
+
+impl Error for CertificateQueryError {
+    fn description(&self) -> &str {
+        "failed to parse certificate query"
+    }
+}
+
+impl std::fmt::Display for CertificateQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "provided query is not valid, check the status, result, cursor, limit and created date range".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrganizationParseError;
+
+impl Error for OrganizationParseError {
+    fn description(&self) -> &str {
+        "failed to parse organization"
+    }
+}
+
+impl std::fmt::Display for OrganizationParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "unable to parse into a valid organization".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecipientParseError;
+
+impl Error for RecipientParseError {
+    fn description(&self) -> &str {
+        "failed to parse recipient"
+    }
+}
+
+impl std::fmt::Display for RecipientParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "unable to parse into a valid recipient".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AccreditationTransitionError {
+    pub from: AccreditationStatus,
+    pub to: AccreditationStatus,
+}
+
+impl Error for AccreditationTransitionError {
+    fn description(&self) -> &str {
+        "illegal accreditation status change"
+    }
+}
+
+impl std::fmt::Display for AccreditationTransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "accreditation status cannot change from `{}` to `{}`",
+            self.from, self.to
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MissingAccreditationError;
+
+impl Error for MissingAccreditationError {
+    fn description(&self) -> &str {
+        "certificate is not accredited"
+    }
+}
+
+impl std::fmt::Display for MissingAccreditationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "certificate has no accreditation".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AccreditationExistsError;
+
+impl Error for AccreditationExistsError {
+    fn description(&self) -> &str {
+        "certificate is already accredited"
+    }
+}
+
+impl std::fmt::Display for AccreditationExistsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "certificate already has an accreditation, change its status instead".fmt(f)
+    }
+}
+
+/// Reasons an accreditation status cannot be changed
+#[derive(Debug)]
+pub enum AccreditationChangeError {
+    Missing(MissingAccreditationError),
+    Transition(AccreditationTransitionError),
+}
+
+impl Error for AccreditationChangeError {}
+
+impl std::fmt::Display for AccreditationChangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccreditationChangeError::Missing(err) => err.fmt(f),
+            AccreditationChangeError::Transition(err) => err.fmt(f),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CertificateRenewedError;
+
+impl Error for CertificateRenewedError {
+    fn description(&self) -> &str {
+        "certificate was already renewed"
+    }
+}
+
+impl std::fmt::Display for CertificateRenewedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "certificate was already renewed, renew the latest certificate of its renewal chain".fmt(f)
+    }
+}
+
+/// Reasons a certificate cannot be renewed
+#[derive(Debug)]
+pub enum CertificateRenewalError {
+    Revoked(CertificateRevokedError),
+    Renewed(CertificateRenewedError),
+}
+
+impl Error for CertificateRenewalError {}
+
+impl std::fmt::Display for CertificateRenewalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CertificateRenewalError::Revoked(err) => err.fmt(f),
+            CertificateRenewalError::Renewed(err) => err.fmt(f),
+        }
+    }
+}
+
+/// Reasons a certificate cannot be updated
+#[derive(Debug)]
+pub enum CertificateUpdateError {
+    Revoked(CertificateRevokedError),
+    Score(InvalidScoreError),
+}
+
+impl Error for CertificateUpdateError {}
+
+impl std::fmt::Display for CertificateUpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CertificateUpdateError::Revoked(err) => err.fmt(f),
+            CertificateUpdateError::Score(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<InvalidScoreError> for CertificateUpdateError {
+    fn from(err: InvalidScoreError) -> Self {
+        CertificateUpdateError::Score(err)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InvalidScoreError;
+
+impl Error for InvalidScoreError {
+    fn description(&self) -> &str {
+        "failed to parse score"
+    }
+}
+
+impl std::fmt::Display for InvalidScoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "the maximum must be greater than the minimum, and the score and passing score must lie between them".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScopeParseError;
+
+impl Error for ScopeParseError {
+    fn description(&self) -> &str {
+        "failed to parse scope"
+    }
+}
+
+impl std::fmt::Display for ScopeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "unknown scope".fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuditActionParseError;
+
+impl Error for AuditActionParseError {
+    fn description(&self) -> &str {
+        "failed to parse audit action"
+    }
+}
+
+impl std::fmt::Display for AuditActionParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "unknown audit action".fmt(f)
+    }
+}
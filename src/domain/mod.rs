@@ -1,8 +1,14 @@
 pub mod accreditation;
+pub mod api_key;
 pub mod assessment;
+pub mod audit;
 pub mod base;
 pub mod certificate;
 pub mod error;
 pub mod organization;
 pub mod person;
+pub mod product;
+pub mod revocation;
+pub mod signature;
 pub mod validity;
+pub mod verification;
@@ -1,9 +1,19 @@
+use actix_web::{body::BoxBody, HttpRequest, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::base::{Address, Email, Id, Phone};
+use crate::{
+    dto::organization_dto::{AddressDto, OrganizationDto},
+    helpers::respond_with_json,
+    model::{OrganizationModel, RegisteredOrganizationModel},
+};
 
-#[derive(Serialize, Deserialize, Debug)]
+use super::{
+    base::{Address, Email, Id, Phone},
+    error::{CertificateParseError, OrganizationParseError},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Organization {
     pub id: Id,
     pub name: String,
@@ -25,8 +35,74 @@ impl Organization {
     }
 }
 
+impl Organization {
+    /// Builds an organization with the given id out of its dto
+    pub fn from_dto(id: Id, organization: OrganizationDto) -> Result<Self, OrganizationParseError> {
+        Ok(Organization {
+            id,
+            name: organization.name,
+            email: Email::parse(organization.email).map_err(|_| OrganizationParseError)?,
+            phone: Phone::parse(organization.phone).map_err(|_| OrganizationParseError)?,
+            address: Address::from(organization.address),
+        })
+    }
+}
+
 impl Default for Organization {
     fn default() -> Self {
         Organization::new()
     }
 }
+
+impl TryFrom<OrganizationModel> for Organization {
+    type Error = CertificateParseError;
+
+    fn try_from(organization: OrganizationModel) -> Result<Self, Self::Error> {
+        Ok(Organization {
+            id: Id::parse(organization.organization_id.into())
+                .map_err(|_| CertificateParseError)?,
+            name: organization.name,
+            email: Email::parse(organization.email).map_err(|_| CertificateParseError)?,
+            phone: Phone::parse(organization.phone).map_err(|_| CertificateParseError)?,
+            address: Address::from(organization.address),
+        })
+    }
+}
+
+impl TryFrom<RegisteredOrganizationModel> for Organization {
+    type Error = CertificateParseError;
+
+    fn try_from(organization: RegisteredOrganizationModel) -> Result<Self, Self::Error> {
+        Organization::try_from(organization.organization)
+    }
+}
+
+impl From<AddressDto> for Address {
+    fn from(address: AddressDto) -> Self {
+        Address {
+            street: address.street,
+            city: address.city,
+            building_number: address.building_number,
+            country: address.country,
+            postal_code: address.postal_code,
+        }
+    }
+}
+
+impl Responder for Organization {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
+}
+
+pub struct Organizations(pub Vec<Organization>);
+
+impl Responder for Organizations {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self.0)
+    }
+}
@@ -1,9 +1,19 @@
+use actix_web::{body::BoxBody, HttpRequest, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::base::{Email, Id, Name, Phone};
+use crate::{
+    dto::recipient_dto::{RecipientDto, RecipientProfileDto},
+    helpers::respond_with_json,
+    model::RecipientModel,
+};
 
-#[derive(Serialize, Deserialize, Debug)]
+use super::{
+    base::{Email, Id, Name, Phone},
+    error::{CertificateParseError, RecipientParseError},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Person {
     pub id: Id,
     pub name: Name,
@@ -32,3 +42,103 @@ impl Default for Person {
         Person::new()
     }
 }
+
+impl Person {
+    /// Builds the recipient named in an issuance payload
+    pub fn from_dto(recipient: &RecipientDto) -> Result<Self, RecipientParseError> {
+        Ok(Person {
+            id: Id::parse(recipient.id).map_err(|_| RecipientParseError)?,
+            name: Name {
+                first_name: recipient.first_name.clone(),
+                middle_name: None,
+                last_name: recipient.last_name.clone(),
+            },
+            email: Email::parse(recipient.email.clone()).map_err(|_| RecipientParseError)?,
+            phone: Phone::parse(recipient.phone.clone()).ok(),
+        })
+    }
+
+    /// Builds a registered recipient with the given id out of its profile
+    pub fn from_profile(id: Id, profile: RecipientProfileDto) -> Result<Self, RecipientParseError> {
+        Ok(Person {
+            id,
+            name: Name {
+                first_name: profile.first_name,
+                middle_name: profile.middle_name.filter(|name| !name.trim().is_empty()),
+                last_name: profile.last_name,
+            },
+            email: Email::parse(profile.email).map_err(|_| RecipientParseError)?,
+            phone: if profile.phone.is_empty() {
+                None
+            } else {
+                Some(Phone::parse(profile.phone).map_err(|_| RecipientParseError)?)
+            },
+        })
+    }
+}
+
+/// The recipient of a certificate as kept with the certificate.
+///
+/// Version 1 documents only hold the recipient id, so their name, email and phone
+/// are absent rather than made up.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CertificateRecipient {
+    pub id: Id,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<Name>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<Email>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<Phone>,
+}
+
+impl From<Person> for CertificateRecipient {
+    fn from(person: Person) -> Self {
+        CertificateRecipient {
+            id: person.id,
+            name: Some(person.name),
+            email: Some(person.email),
+            phone: person.phone,
+        }
+    }
+}
+
+impl TryFrom<RecipientModel> for Person {
+    type Error = CertificateParseError;
+
+    fn try_from(recipient: RecipientModel) -> Result<Self, Self::Error> {
+        let person = recipient.person;
+        Ok(Person {
+            id: Id::parse(recipient.recipient_id.into()).map_err(|_| CertificateParseError)?,
+            name: Name {
+                first_name: person.first_name,
+                middle_name: person.middle_name,
+                last_name: person.last_name,
+            },
+            email: Email::parse(person.email).map_err(|_| CertificateParseError)?,
+            phone: person
+                .phone
+                .map(Phone::parse)
+                .transpose()
+                .map_err(|_| CertificateParseError)?,
+        })
+    }
+}
+
+impl Responder for Person {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
+}
+
+pub struct Recipients(pub Vec<Person>);
+
+impl Responder for Recipients {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self.0)
+    }
+}
@@ -0,0 +1,158 @@
+use actix_web::{body::BoxBody, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dto::{certificate_metadata_dto::ScoreScaleDto, product_dto::ProductDto},
+    helpers::respond_with_json,
+    model::ProductModel,
+};
+
+use super::{
+    base::Id,
+    error::{CertificateParseError, InvalidIdError},
+    validity::{ValidUntil, Validity},
+};
+
+/// A product certificates are issued for, acting as the template and rule set of
+/// its certificates
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Product {
+    pub id: u32,
+    pub name: String,
+    pub description: String,
+    /// The only organization issuing certificates for the product
+    pub organization_id: Id,
+    /// Days a certificate stays valid once acquired, certificates never expire without one
+    pub validity_days: Option<u32>,
+    pub score_scale: Option<ScoreScale>,
+    pub required_accreditation: Option<RequiredAccreditation>,
+}
+
+/// Bounds of the score of an assessment and the score needed to pass it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScoreScale {
+    pub max: u32,
+    pub min: u32,
+    pub passing_score: u32,
+}
+
+/// Accreditation every certificate of a product must be issued with
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RequiredAccreditation {
+    pub name: String,
+    pub institution: String,
+}
+
+impl Product {
+    /// Builds a product with the given id out of its dto
+    pub fn from_dto(id: u32, product: ProductDto) -> Result<Self, InvalidIdError> {
+        Ok(Product {
+            id,
+            name: product.name,
+            description: product.description,
+            organization_id: Id::parse(product.organization_id)?,
+            validity_days: product.validity_days,
+            score_scale: product.score_scale.map(|scale| ScoreScale {
+                max: scale.max,
+                min: scale.min,
+                passing_score: scale.passing_score,
+            }),
+            required_accreditation: product.required_accreditation.map(|accreditation| {
+                RequiredAccreditation {
+                    name: accreditation.name,
+                    institution: accreditation.institution,
+                }
+            }),
+        })
+    }
+
+    /// Validity of a certificate of the product acquired at the given point in time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use crs::domain::{base::Id, product::Product, validity::ValidUntil};
+    /// use uuid::Uuid;
+    ///
+    /// let product = Product {
+    ///     id: 15,
+    ///     name: "Rust fundamentals".to_string(),
+    ///     description: "".to_string(),
+    ///     organization_id: Id::parse(Uuid::new_v4()).unwrap(),
+    ///     validity_days: Some(365),
+    ///     score_scale: None,
+    ///     required_accreditation: None,
+    /// };
+    /// let acquired = Utc::now();
+    /// let validity = product.validity_from(acquired);
+    /// assert!(matches!(
+    ///     validity.valid_until,
+    ///     ValidUntil::Expiry(expiry) if expiry == acquired + Duration::days(365)
+    /// ));
+    /// ```
+    pub fn validity_from(&self, acquired: DateTime<Utc>) -> Validity {
+        Validity {
+            first_valid_from: acquired,
+            valid_from: acquired,
+            valid_until: self.validity_days.map_or(ValidUntil::EndOfTime, |days| {
+                ValidUntil::Expiry(acquired + Duration::days(days.into()))
+            }),
+        }
+    }
+}
+
+impl From<&ScoreScale> for ScoreScaleDto {
+    fn from(scale: &ScoreScale) -> Self {
+        ScoreScaleDto {
+            max: scale.max,
+            min: scale.min,
+            passing_score: scale.passing_score,
+        }
+    }
+}
+
+impl TryFrom<ProductModel> for Product {
+    type Error = CertificateParseError;
+
+    fn try_from(product: ProductModel) -> Result<Self, Self::Error> {
+        Ok(Product {
+            id: product.product_id,
+            name: product.name,
+            description: product.description,
+            organization_id: Id::parse(product.organization_id.into())
+                .map_err(|_| CertificateParseError)?,
+            validity_days: product.validity_days,
+            score_scale: product.score_scale.map(|scale| ScoreScale {
+                max: scale.max,
+                min: scale.min,
+                passing_score: scale.passing_score,
+            }),
+            required_accreditation: product.required_accreditation.map(|accreditation| {
+                RequiredAccreditation {
+                    name: accreditation.name,
+                    institution: accreditation.institution,
+                }
+            }),
+        })
+    }
+}
+
+impl Responder for Product {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
+}
+
+pub struct Products(pub Vec<Product>);
+
+impl Responder for Products {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self.0)
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{dto::revocation_dto::RevocationDto, model::RevocationModel};
+
+use super::error::{CertificateStatusError, RevocationReasonError};
+
+/// Record of a certificate being invalidated, kept alongside the certificate
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Revocation {
+    pub reason: RevocationReason,
+    pub revoked_at: DateTime<Utc>,
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RevocationReason {
+    IssuedInError,
+    Superseded,
+    Fraud,
+    Withdrawn,
+    Other,
+}
+
+/// Current state of a certificate
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub enum CertificateStatus {
+    #[default]
+    Active,
+    /// The validity window ended, set by the expiry job
+    Expired,
+    Revoked,
+}
+
+impl RevocationReason {
+    /// Converts a reason code to a RevocationReason
+    pub fn from_reason_str(reason: &str) -> Result<RevocationReason, RevocationReasonError> {
+        match reason {
+            "IssuedInError" | "issued_in_error" => Ok(RevocationReason::IssuedInError),
+            "Superseded" | "superseded" => Ok(RevocationReason::Superseded),
+            "Fraud" | "fraud" => Ok(RevocationReason::Fraud),
+            "Withdrawn" | "withdrawn" => Ok(RevocationReason::Withdrawn),
+            "Other" | "other" => Ok(RevocationReason::Other),
+            _ => Err(RevocationReasonError),
+        }
+    }
+}
+
+impl std::fmt::Display for RevocationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RevocationReason::IssuedInError => write!(f, "IssuedInError"),
+            RevocationReason::Superseded => write!(f, "Superseded"),
+            RevocationReason::Fraud => write!(f, "Fraud"),
+            RevocationReason::Withdrawn => write!(f, "Withdrawn"),
+            RevocationReason::Other => write!(f, "Other"),
+        }
+    }
+}
+
+impl CertificateStatus {
+    /// Converts a string literal to a CertificateStatus
+    pub fn from_status_str(status: &str) -> Result<CertificateStatus, CertificateStatusError> {
+        match status {
+            "Active" | "active" => Ok(CertificateStatus::Active),
+            "Expired" | "expired" => Ok(CertificateStatus::Expired),
+            "Revoked" | "revoked" => Ok(CertificateStatus::Revoked),
+            _ => Err(CertificateStatusError),
+        }
+    }
+}
+
+impl std::fmt::Display for CertificateStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CertificateStatus::Active => write!(f, "Active"),
+            CertificateStatus::Expired => write!(f, "Expired"),
+            CertificateStatus::Revoked => write!(f, "Revoked"),
+        }
+    }
+}
+
+impl TryFrom<RevocationDto> for Revocation {
+    type Error = RevocationReasonError;
+
+    fn try_from(revocation: RevocationDto) -> Result<Self, Self::Error> {
+        Ok(Revocation {
+            reason: RevocationReason::from_reason_str(&revocation.reason)?,
+            revoked_at: revocation
+                .revoked_at
+                .unwrap_or_else(Utc::now)
+                .trunc_subsecs(3),
+            comment: revocation.comment,
+        })
+    }
+}
+
+impl TryFrom<RevocationModel> for Revocation {
+    type Error = RevocationReasonError;
+
+    fn try_from(revocation: RevocationModel) -> Result<Self, Self::Error> {
+        Ok(Revocation {
+            reason: RevocationReason::from_reason_str(&revocation.reason)?,
+            revoked_at: revocation.revoked_at.into(),
+            comment: revocation.comment,
+        })
+    }
+}
@@ -0,0 +1,172 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::model::SignatureModel;
+
+use super::{
+    accreditation::AccreditationStatus,
+    base::{AssessmentResult, Name},
+    certificate::Certificate,
+    validity::ValidUntil,
+};
+
+pub const SIGNATURE_ALGORITHM: &str = "Ed25519";
+
+/// Detached signature over the canonical form of a certificate
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CertificateSignature {
+    pub algorithm: String,
+    /// Base64 encoded signature bytes
+    pub value: String,
+}
+
+/// Outcome of checking a certificate signature
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SignatureStatus {
+    Valid,
+    /// The signature does not match, or is missing although the certificate was signed
+    Invalid,
+    /// The certificate was never signed
+    Unsigned,
+    /// No issuer key is available to check the signature against
+    Unverifiable,
+}
+
+/// The issued facts of a certificate covered by its signature.
+///
+/// Mutable state (status, revocation, update date) is deliberately left out, dates
+/// are kept as milliseconds since they are stored with that precision. Who received
+/// and who issued the certificate and its accreditation are covered, so every change
+/// of those details has to sign the certificate again.
+#[derive(Serialize)]
+struct SignedContent<'a> {
+    certificate_id: Uuid,
+    recipient_id: Uuid,
+    recipient_name: Option<&'a Name>,
+    recipient_email: Option<&'a str>,
+    account_id: u32,
+    product_id: u32,
+    name: &'a str,
+    description: &'a str,
+    authority_id: Option<Uuid>,
+    authority_name: Option<&'a str>,
+    first_valid_from: Option<i64>,
+    valid_from: Option<i64>,
+    valid_until: Option<i64>,
+    score: Option<[u32; 4]>,
+    progress: f32,
+    result: &'a AssessmentResult,
+    created_date: i64,
+    renewal_of: Option<Uuid>,
+    accreditation: Option<SignedAccreditation<'a>>,
+}
+
+/// The accreditation of a certificate covered by its signature, along with its
+/// status so a revoked or expired accreditation cannot be passed off as active
+#[derive(Serialize)]
+struct SignedAccreditation<'a> {
+    name: &'a str,
+    institution: &'a str,
+    start_date: i64,
+    end_date: Option<i64>,
+    status: &'a AccreditationStatus,
+}
+
+impl Certificate {
+    /// Canonical byte representation of the certificate used for signing
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let validity = self.validity.as_ref();
+        let content = SignedContent {
+            certificate_id: self.id.as_uuid(),
+            recipient_id: self.recipient.id.as_uuid(),
+            recipient_name: self.recipient.name.as_ref(),
+            recipient_email: self.recipient.email.as_ref().map(|email| email.0.as_str()),
+            account_id: self.account_id,
+            product_id: self.product_id,
+            name: &self.name,
+            description: &self.description,
+            authority_id: self.authority_id(),
+            authority_name: self
+                .authority
+                .as_ref()
+                .map(|authority| authority.name.as_str()),
+            first_valid_from: validity.map(|validity| validity.first_valid_from.timestamp_millis()),
+            valid_from: validity.map(|validity| validity.valid_from.timestamp_millis()),
+            valid_until: validity.and_then(|validity| match validity.valid_until {
+                ValidUntil::EndOfTime => None,
+                ValidUntil::Expiry(expiry) => Some(expiry.timestamp_millis()),
+            }),
+            score: self
+                .assessment
+                .score
+                .as_ref()
+                .map(|score| [score.value, score.max, score.min, score.passing_score]),
+            progress: self.assessment.progress,
+            result: &self.assessment.result,
+            created_date: self.created_date.timestamp_millis(),
+            renewal_of: self.renewal_of.as_ref().map(|id| id.as_uuid()),
+            accreditation: self
+                .accreditation
+                .as_ref()
+                .map(|accreditation| SignedAccreditation {
+                    name: &accreditation.name,
+                    institution: &accreditation.institution,
+                    start_date: accreditation.start_date.timestamp_millis(),
+                    end_date: accreditation
+                        .end_date
+                        .map(|end_date| end_date.timestamp_millis()),
+                    status: &accreditation.status,
+                }),
+        };
+        serde_json::to_vec(&content).expect("signed content is always serializable")
+    }
+
+    /// Signs the certificate with the issuer key, replacing any previous signature
+    pub fn sign(&mut self, key: &SigningKey) {
+        let signature = key.sign(&self.canonical_bytes());
+        self.signature = Some(CertificateSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            value: STANDARD.encode(signature.to_bytes()),
+        });
+        self.signature_required = true;
+    }
+
+    /// Checks the certificate signature against the issuer key.
+    ///
+    /// Certificates issued before signing was enabled for their issuer stay unsigned.
+    /// Once a certificate was signed a missing signature makes it invalid, so stripping
+    /// the signature of a tampered certificate does not make it pass.
+    pub fn signature_status(&self, key: Option<&VerifyingKey>) -> SignatureStatus {
+        let (signature, key) = match (&self.signature, key) {
+            (Some(signature), Some(key)) => (signature, key),
+            (None, Some(_)) if self.signature_required => return SignatureStatus::Invalid,
+            (None, _) => return SignatureStatus::Unsigned,
+            (Some(_), None) => return SignatureStatus::Unverifiable,
+        };
+        if signature.algorithm != SIGNATURE_ALGORITHM {
+            return SignatureStatus::Invalid;
+        }
+
+        let signature = STANDARD
+            .decode(&signature.value)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok());
+        match signature {
+            Some(signature) if key.verify(&self.canonical_bytes(), &signature).is_ok() => {
+                SignatureStatus::Valid
+            }
+            _ => SignatureStatus::Invalid,
+        }
+    }
+}
+
+impl From<SignatureModel> for CertificateSignature {
+    fn from(signature: SignatureModel) -> Self {
+        CertificateSignature {
+            algorithm: signature.algorithm,
+            value: signature.value,
+        }
+    }
+}
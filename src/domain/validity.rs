@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::model::ValidityModel;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Validity {
     pub first_valid_from: DateTime<Utc>,
@@ -13,3 +15,26 @@ pub enum ValidUntil {
     EndOfTime,
     Expiry(DateTime<Utc>),
 }
+
+impl Validity {
+    /// Indicates if the validity window has ended at the given point in time
+    pub fn is_expired_at(&self, at: DateTime<Utc>) -> bool {
+        match self.valid_until {
+            ValidUntil::EndOfTime => false,
+            ValidUntil::Expiry(expiry) => expiry <= at,
+        }
+    }
+}
+
+impl From<ValidityModel> for Validity {
+    fn from(validity: ValidityModel) -> Self {
+        Validity {
+            first_valid_from: validity.first_valid_from.into(),
+            valid_from: validity.valid_from.into(),
+            valid_until: match validity.valid_until {
+                Some(expiry) => ValidUntil::Expiry(expiry.into()),
+                None => ValidUntil::EndOfTime,
+            },
+        }
+    }
+}
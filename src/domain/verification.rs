@@ -0,0 +1,111 @@
+use actix_web::{body::BoxBody, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::helpers::respond_with_json;
+
+use super::{
+    accreditation::AccreditationStatus, certificate::Certificate, revocation::CertificateStatus,
+    signature::SignatureStatus, validity::ValidUntil,
+};
+
+/// Outcome of verifying a certificate
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Valid,
+    Expired,
+    Revoked,
+    /// The certificate signature does not match its content
+    Invalid,
+    Unknown,
+}
+
+/// Public verification result for a certificate.
+///
+/// Only exposes what a third party needs to trust the certificate, the recipient's
+/// contact details are intentionally left out.
+#[derive(Serialize, Debug)]
+pub struct Verification {
+    pub certificate_id: Uuid,
+    pub verdict: Verdict,
+    pub signature: SignatureStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<DateTime<Utc>>,
+    /// Current status of the accreditation, omitted for certificates without one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accreditation: Option<AccreditationStatus>,
+}
+
+impl Verification {
+    /// Verification result for a certificate that could not be found
+    pub fn unknown(certificate_id: Uuid) -> Self {
+        Verification {
+            certificate_id,
+            verdict: Verdict::Unknown,
+            signature: SignatureStatus::Unsigned,
+            issuer: None,
+            name: None,
+            valid_from: None,
+            valid_until: None,
+            accreditation: None,
+        }
+    }
+
+    /// Verifies the certificate at the given point in time, given the outcome of its signature check
+    pub fn of(certificate: &Certificate, signature: SignatureStatus, at: DateTime<Utc>) -> Self {
+        let verdict = if signature == SignatureStatus::Invalid {
+            Verdict::Invalid
+        } else if certificate.status == CertificateStatus::Revoked {
+            Verdict::Revoked
+        } else if certificate.status == CertificateStatus::Expired
+            || certificate
+                .validity
+                .as_ref()
+                .is_some_and(|validity| validity.is_expired_at(at))
+        {
+            Verdict::Expired
+        } else {
+            Verdict::Valid
+        };
+
+        Verification {
+            certificate_id: certificate.id.as_uuid(),
+            verdict,
+            signature,
+            issuer: certificate
+                .authority
+                .as_ref()
+                .map(|authority| authority.name.clone()),
+            name: Some(certificate.name.clone()),
+            valid_from: certificate
+                .validity
+                .as_ref()
+                .map(|validity| validity.valid_from),
+            valid_until: certificate.validity.as_ref().and_then(|validity| {
+                match validity.valid_until {
+                    ValidUntil::EndOfTime => None,
+                    ValidUntil::Expiry(expiry) => Some(expiry),
+                }
+            }),
+            accreditation: certificate
+                .accreditation
+                .as_ref()
+                .map(|accreditation| accreditation.status.clone()),
+        }
+    }
+}
+
+impl Responder for Verification {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
+}
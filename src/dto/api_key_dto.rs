@@ -0,0 +1,82 @@
+use serde::Deserialize;
+
+use crate::{domain::api_key::Scope, error::CrsError};
+
+use super::validation::Violations;
+
+/// API key data transfer object, used to mint keys
+#[derive(Deserialize)]
+pub struct ApiKeyDto {
+    pub name: String,
+    /// Account the key is bound to, required and always the account of the minting key
+    #[serde(default)]
+    pub account_id: Option<u32>,
+    pub scopes: Vec<String>,
+}
+
+impl ApiKeyDto {
+    /// Validates a key minted by a key of the given account, collecting every violation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::dto::api_key_dto::ApiKeyDto;
+    ///
+    /// let key = ApiKeyDto {
+    ///     name: "LMS integration".to_string(),
+    ///     account_id: Some(20),
+    ///     scopes: vec!["certificates:issue".to_string()],
+    /// };
+    /// assert!(key.validate(20).is_ok());
+    /// assert!(key.validate(99).is_err());
+    ///
+    /// let key = ApiKeyDto {
+    ///     account_id: None,
+    ///     ..key
+    /// };
+    /// assert!(key.validate(20).is_err());
+    /// ```
+    pub fn validate(&self, account_id: u32) -> Result<(), CrsError> {
+        let mut violations = Violations::default();
+        violations.check(
+            !self.name.trim().is_empty(),
+            "name",
+            "required",
+            &self.name,
+            "must not be empty",
+        );
+        violations.check(
+            self.account_id.is_some(),
+            "account_id",
+            "required",
+            self.account_id,
+            "keys must be bound to an account",
+        );
+        violations.check(
+            self.account_id.is_none_or(|own| own == account_id),
+            "account_id",
+            "tenant",
+            self.account_id,
+            &format!("must be the account of the request, {account_id}"),
+        );
+        violations.check(
+            !self.scopes.is_empty(),
+            "scopes",
+            "required",
+            &self.scopes,
+            "must grant at least one scope",
+        );
+        for (index, scope) in self.scopes.iter().enumerate() {
+            if let Err(err) = Scope::from_scope_str(scope) {
+                violations.check(
+                    false,
+                    &format!("scopes[{index}]"),
+                    "scope",
+                    scope,
+                    &err.to_string(),
+                );
+            }
+        }
+        violations.into_result("invalid API key")
+    }
+}
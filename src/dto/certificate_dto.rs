@@ -1,21 +1,26 @@
 use serde::Deserialize;
+use uuid::Uuid;
 
-use super::{certificate_metadata_dto::CertificateMetadataDto, recipient_dto::RecipientDto};
+use crate::{domain::product::Product, error::CrsError};
+
+use super::{
+    certificate_metadata_dto::CertificateMetadataDto, recipient_dto::RecipientDto,
+    validation::Violations,
+};
 
 /// Certificate data transfer object
 #[derive(Deserialize)]
 pub struct CertificateDto {
     pub account_id: u32,
     pub product_id: u32,
+    /// The issuing organization, resolved at issuance
+    pub organization_id: Uuid,
     pub recipient: RecipientDto,
     pub metadata: CertificateMetadataDto,
 }
 
 impl CertificateDto {
-    /// Validates the certificate dto and indicates if it is valid or not
-    ///
-    /// # Returns
-    /// **true** if the certificate dto is valid, otherwise **false**
+    /// Validates the certificate dto, collecting every violation instead of stopping at the first
     ///
     /// # Examples
     ///
@@ -28,6 +33,7 @@ impl CertificateDto {
     /// let certificate = CertificateDto {
     ///     account_id: 1,
     ///     product_id: 1,
+    ///     organization_id: Uuid::new_v4(),
     ///     recipient: RecipientDto {
     ///         id: Uuid::new_v4(),
     ///         first_name: "firstName".to_string(),
@@ -40,10 +46,11 @@ impl CertificateDto {
     ///         progress: 0.5,
     ///         acquired_date: None,
     ///         accreditation: None,
+    ///         score_scale: None,
     ///     },
     /// };
     ///
-    /// assert_eq!(certificate.is_valid(), true);
+    /// assert_eq!(certificate.violations().is_empty(), true);
     ///
     /// ```
     ///
@@ -56,8 +63,9 @@ impl CertificateDto {
     /// use uuid::Uuid;
     ///
     /// let certificate = CertificateDto {
-    ///     account_id: 1,
+    ///     account_id: 0,
     ///     product_id: 1,
+    ///     organization_id: Uuid::new_v4(),
     ///     recipient: RecipientDto {
     ///         id: Uuid::nil(),
     ///         first_name: "firstName".to_string(),
@@ -70,16 +78,85 @@ impl CertificateDto {
     ///         progress: 0.5,
     ///         acquired_date: None,
     ///         accreditation: None,
+    ///         score_scale: None,
     ///     },
     /// };
     ///
-    /// assert_eq!(certificate.is_valid(), false);
+    /// let fields: Vec<String> = certificate.violations().into_inner().into_iter().map(|err| err.field).collect();
+    /// assert_eq!(fields, vec!["account_id", "recipient.id"]);
     ///
     /// ```
-    pub fn is_valid(&self) -> bool {
-        !self.account_id != 0
-            && self.product_id != 0
-            && self.recipient.is_valid()
-            && self.metadata.is_valid()
+    pub fn violations(&self) -> Violations {
+        let mut violations = Violations::default();
+        violations.check(
+            self.account_id != 0,
+            "account_id",
+            "required",
+            self.account_id,
+            "must not be 0",
+        );
+        violations.check(
+            self.product_id != 0,
+            "product_id",
+            "required",
+            self.product_id,
+            "must not be 0",
+        );
+        violations.check(
+            !self.organization_id.is_nil(),
+            "organization_id",
+            "required",
+            self.organization_id,
+            "must reference an organization",
+        );
+        self.recipient.validate("recipient", &mut violations);
+        self.metadata.validate("metadata", &mut violations);
+        violations
+    }
+
+    /// Validates the certificate dto, reporting every violation as a validation error
+    pub fn validate(&self) -> Result<(), CrsError> {
+        self.violations().into_result("invalid certificate")
+    }
+
+    /// Applies the rules of the registered product the certificate is issued for and
+    /// validates the score against the resulting scale.
+    ///
+    /// The product must be issued by the organization of the dto, its score scale
+    /// replaces the scale of the dto and its required accreditation must be present.
+    pub fn apply_product(&mut self, product: Option<&Product>) -> Violations {
+        let mut violations = Violations::default();
+        if let Some(product) = product {
+            violations.check(
+                self.organization_id == product.organization_id.as_uuid(),
+                "organization_id",
+                "product",
+                self.organization_id,
+                &format!("must be the issuing organization of product {}", product.id),
+            );
+            if let Some(scale) = &product.score_scale {
+                self.metadata.score_scale = Some(scale.into());
+            }
+            if let Some(required) = &product.required_accreditation {
+                violations.check(
+                    self.metadata
+                        .accreditation
+                        .as_ref()
+                        .is_some_and(|accreditation| {
+                            accreditation.name == required.name
+                                && accreditation.institution == required.institution
+                        }),
+                    "metadata.accreditation",
+                    "product",
+                    (),
+                    &format!(
+                        "product {} requires the {} accreditation by {}",
+                        product.id, required.name, required.institution
+                    ),
+                );
+            }
+        }
+        self.metadata.validate_score("metadata", &mut violations);
+        violations
     }
 }
@@ -1,6 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
+use crate::domain::{accreditation::AccreditationStatus, base::DEFAULT_MAX_SCORE};
+
+use super::validation::{field_path, Violations};
+
 /// Certificate metadata data transfer object
 #[derive(Deserialize)]
 pub struct CertificateMetadataDto {
@@ -8,48 +12,154 @@ pub struct CertificateMetadataDto {
     pub progress: f32,
     pub acquired_date: Option<DateTime<Utc>>,
     pub accreditation: Option<AccreditationDto>,
+    /// Scale the score was achieved on, scores from 0 to 100 always pass without one
+    #[serde(default)]
+    pub score_scale: Option<ScoreScaleDto>,
+}
+
+/// Bounds of the score and the score needed to pass the assessment
+#[derive(Deserialize, Debug)]
+pub struct ScoreScaleDto {
+    pub max: u32,
+    #[serde(default)]
+    pub min: u32,
+    pub passing_score: u32,
+}
+
+impl ScoreScaleDto {
+    /// Validates that the bounds are ordered and the passing score lies between them
+    pub fn validate(&self, path: &str, violations: &mut Violations) {
+        violations.check(
+            self.min < self.max,
+            &field_path(path, "max"),
+            "score_scale",
+            self.max,
+            "must be greater than min",
+        );
+        violations.check(
+            (self.min..=self.max).contains(&self.passing_score),
+            &field_path(path, "passing_score"),
+            "score_scale",
+            self.passing_score,
+            "must be between min and max",
+        );
+    }
+
+    fn is_consistent(&self) -> bool {
+        self.min < self.max && (self.min..=self.max).contains(&self.passing_score)
+    }
 }
 
 impl CertificateMetadataDto {
-    /// Validates the certificate metadata
-    /// # Returns
-    /// **true** if the certificate metadata is valid, otherwise **false**
+    /// Validates the certificate metadata, recording every violation under the `path` prefix.
+    ///
+    /// The score itself is left to [`CertificateMetadataDto::validate_score`], as the
+    /// product of the certificate may define the scale it is checked against.
     ///
     /// # Examples
     ///
     /// ```
     /// use pretty_assertions::assert_eq;
-    /// use crs::dto::certificate_metadata_dto::CertificateMetadataDto;
+    /// use crs::dto::{certificate_metadata_dto::CertificateMetadataDto, validation::Violations};
     ///
     /// let metadata = CertificateMetadataDto {
     ///     score: 0,
     ///     progress: 0.5,
     ///     acquired_date: None,
     ///     accreditation: None,
+    ///     score_scale: None,
     /// };
-    /// assert_eq!(metadata.is_valid(), true);
+    /// let mut violations = Violations::default();
+    /// metadata.validate("metadata", &mut violations);
+    /// assert_eq!(violations.is_empty(), true);
     /// ```
     /// ---
     ///
     /// ```
     ///
     /// use pretty_assertions::assert_eq;
-    /// use crs::dto::certificate_metadata_dto::CertificateMetadataDto;
+    /// use crs::dto::{certificate_metadata_dto::CertificateMetadataDto, validation::Violations};
     ///
     /// let metadata = CertificateMetadataDto {
     ///     score: 0,
     ///     progress: 1.5,
     ///     acquired_date: None,
     ///     accreditation: None,
+    ///     score_scale: None,
     /// };
-    /// assert_eq!(metadata.is_valid(), false);
+    /// let mut violations = Violations::default();
+    /// metadata.validate("metadata", &mut violations);
+    /// assert_eq!(violations.into_inner()[0].field, "metadata.progress");
     /// ```
-    pub fn is_valid(&self) -> bool {
-        self.score <= 100
-            && self.progress >= 0.0
-            && self.progress <= 1.0
-            && AccreditationDto::is_valid_option(&self.accreditation)
+    pub fn validate(&self, path: &str, violations: &mut Violations) {
+        if let Some(scale) = &self.score_scale {
+            scale.validate(&field_path(path, "score_scale"), violations);
+        }
+        validate_progress(self.progress, &field_path(path, "progress"), violations);
+        if let Some(accreditation) = &self.accreditation {
+            accreditation.validate(&field_path(path, "accreditation"), violations);
+        }
     }
+
+    /// Validates that the score lies on its scale and reaches the passing score,
+    /// scores without a scale run from 0 to 100 and always pass
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pretty_assertions::assert_eq;
+    /// use crs::dto::{
+    ///     certificate_metadata_dto::{CertificateMetadataDto, ScoreScaleDto},
+    ///     validation::Violations,
+    /// };
+    ///
+    /// let metadata = CertificateMetadataDto {
+    ///     score: 45,
+    ///     progress: 1.0,
+    ///     acquired_date: None,
+    ///     accreditation: None,
+    ///     score_scale: Some(ScoreScaleDto { max: 60, min: 0, passing_score: 50 }),
+    /// };
+    /// let mut violations = Violations::default();
+    /// metadata.validate_score("metadata", &mut violations);
+    /// assert_eq!(violations.into_inner()[0].rule, "passing_score");
+    /// ```
+    pub fn validate_score(&self, path: &str, violations: &mut Violations) {
+        let field = field_path(path, "score");
+        let (min, max, passing_score) = match &self.score_scale {
+            // an inconsistent scale is reported on its own
+            Some(scale) if !scale.is_consistent() => return,
+            Some(scale) => (scale.min, scale.max, scale.passing_score),
+            None => (0, DEFAULT_MAX_SCORE, 0),
+        };
+        let in_range = (min..=max).contains(&self.score);
+        violations.check(
+            in_range,
+            &field,
+            "range",
+            self.score,
+            &format!("must be between {min} and {max}"),
+        );
+        // certificates are only issued for passed assessments
+        violations.check(
+            !in_range || self.score >= passing_score,
+            &field,
+            "passing_score",
+            self.score,
+            &format!("must reach the passing score of {passing_score}"),
+        );
+    }
+}
+
+/// Validates that the progress is a fraction between 0 and 1
+pub fn validate_progress(progress: f32, field: &str, violations: &mut Violations) {
+    violations.check(
+        (0.0..=1.0).contains(&progress),
+        field,
+        "range",
+        progress,
+        "must be between 0 and 1",
+    );
 }
 
 #[derive(Deserialize, Debug)]
@@ -62,13 +172,52 @@ pub struct AccreditationDto {
 }
 
 impl AccreditationDto {
-    /// Validates the option type of accreditation dto
-    pub fn is_valid_option(acc_dto: &Option<AccreditationDto>) -> bool {
-        match acc_dto {
-            Some(acc) => {
-                !acc.name.is_empty() && !acc.institution.is_empty() && !acc.status.is_empty()
-            }
-            None => true,
+    /// Validates the accreditation, recording every violation under the `path` prefix
+    pub fn validate(&self, path: &str, violations: &mut Violations) {
+        violations.check(
+            !self.name.trim().is_empty(),
+            &field_path(path, "name"),
+            "required",
+            &self.name,
+            "must not be empty",
+        );
+        violations.check(
+            !self.institution.trim().is_empty(),
+            &field_path(path, "institution"),
+            "required",
+            &self.institution,
+            "must not be empty",
+        );
+        match AccreditationStatus::from_status_str(&self.status) {
+            Ok(status) => violations.check(
+                status.is_initial(),
+                &field_path(path, "status"),
+                "accreditation_status",
+                &self.status,
+                "a new accreditation must be `pending` or `active`",
+            ),
+            Err(err) => violations.check(
+                false,
+                &field_path(path, "status"),
+                "accreditation_status",
+                &self.status,
+                &err.to_string(),
+            ),
         }
+        violations.check(
+            self.end_date
+                .is_none_or(|end_date| end_date > self.start_date),
+            &field_path(path, "end_date"),
+            "date_order",
+            self.end_date,
+            "must be after the start date",
+        );
     }
 }
+
+/// Requested status change of an accreditation
+#[derive(Deserialize, Debug)]
+pub struct AccreditationStatusDto {
+    pub status: String,
+    pub comment: Option<String>,
+}
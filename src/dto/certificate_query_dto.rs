@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    domain::{base::AssessmentResult, error::CertificateQueryError, revocation::CertificateStatus},
+    repository::query::{CertificateQuery, PageCursor, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+};
+
+/// Certificate listing query string
+#[derive(Deserialize, Default)]
+pub struct CertificateQueryDto {
+    pub account_id: Option<u32>,
+    pub product_id: Option<u32>,
+    pub user_id: Option<Uuid>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub status: Option<String>,
+    pub result: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl TryFrom<CertificateQueryDto> for CertificateQuery {
+    type Error = CertificateQueryError;
+
+    /// Parses the listing query
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::{dto::certificate_query_dto::CertificateQueryDto, repository::query::CertificateQuery};
+    ///
+    /// let query = CertificateQueryDto {
+    ///     status: Some("revoked".to_string()),
+    ///     limit: Some(50),
+    ///     ..Default::default()
+    /// };
+    /// assert!(CertificateQuery::try_from(query).is_ok());
+    ///
+    /// let query = CertificateQueryDto {
+    ///     limit: Some(1000),
+    ///     ..Default::default()
+    /// };
+    /// assert!(CertificateQuery::try_from(query).is_err());
+    /// ```
+    fn try_from(query: CertificateQueryDto) -> Result<Self, Self::Error> {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(CertificateQueryError);
+        }
+        if let (Some(created_from), Some(created_to)) = (query.created_from, query.created_to) {
+            if created_from > created_to {
+                return Err(CertificateQueryError);
+            }
+        }
+
+        Ok(CertificateQuery {
+            product_id: query.product_id,
+            user_id: query.user_id,
+            created_from: query.created_from,
+            created_to: query.created_to,
+            status: query
+                .status
+                .map(|status| CertificateStatus::from_status_str(&status))
+                .transpose()
+                .map_err(|_| CertificateQueryError)?,
+            result: query
+                .result
+                .map(|result| AssessmentResult::from_result_str(&result))
+                .transpose()
+                .map_err(|_| CertificateQueryError)?,
+            after: query
+                .cursor
+                .map(|cursor| PageCursor::decode(&cursor))
+                .transpose()
+                .map_err(|_| CertificateQueryError)?,
+            limit,
+        })
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::error::CrsError;
+
+use super::{certificate_metadata_dto::validate_progress, validation::Violations};
+
+/// Partial certificate update data transfer object, absent fields are left unchanged
+#[derive(Deserialize, Default)]
+pub struct CertificateUpdateDto {
+    pub progress: Option<f32>,
+    pub score: Option<u32>,
+    pub validity: Option<ValidityDto>,
+    pub description: Option<String>,
+    /// Version the update is based on, used when no `If-Match` header is sent
+    pub version: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct ValidityDto {
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+impl CertificateUpdateDto {
+    /// Validates the update with the same rules as issuance, a new score is checked
+    /// against the scale of the certificate when it is applied
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::dto::certificate_update_dto::CertificateUpdateDto;
+    ///
+    /// let update = CertificateUpdateDto {
+    ///     progress: Some(1.0),
+    ///     description: Some("Completed".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// assert!(update.validate().is_ok());
+    /// ```
+    /// ---
+    ///
+    /// ```
+    ///
+    /// use crs::dto::certificate_update_dto::CertificateUpdateDto;
+    ///
+    /// let update = CertificateUpdateDto {
+    ///     progress: Some(1.5),
+    ///     ..Default::default()
+    /// };
+    /// assert!(update.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), CrsError> {
+        let mut violations = Violations::default();
+        if let Some(progress) = self.progress {
+            validate_progress(progress, "progress", &mut violations);
+        }
+        if let Some(validity) = &self.validity {
+            violations.check(
+                validity
+                    .valid_until
+                    .is_none_or(|valid_until| valid_until > validity.valid_from),
+                "validity.valid_until",
+                "date_order",
+                validity.valid_until,
+                "must be after valid_from",
+            );
+        }
+        violations.into_result("invalid certificate update")
+    }
+}
@@ -1,3 +1,11 @@
+pub mod api_key_dto;
 pub mod certificate_dto;
 pub mod certificate_metadata_dto;
+pub mod certificate_query_dto;
+pub mod certificate_update_dto;
+pub mod organization_dto;
+pub mod product_dto;
 pub mod recipient_dto;
+pub mod renewal_dto;
+pub mod revocation_dto;
+pub mod validation;
@@ -0,0 +1,77 @@
+use serde::Deserialize;
+
+use crate::{
+    domain::base::{Email, Phone},
+    error::CrsError,
+};
+
+use super::validation::Violations;
+
+/// Issuing organization data transfer object, used to create and replace organizations
+#[derive(Deserialize)]
+pub struct OrganizationDto {
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+    #[serde(default)]
+    pub address: AddressDto,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct AddressDto {
+    pub street: String,
+    pub city: String,
+    pub building_number: String,
+    pub country: String,
+    pub postal_code: String,
+}
+
+impl OrganizationDto {
+    /// Validates the organization, collecting every violation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::dto::organization_dto::{AddressDto, OrganizationDto};
+    ///
+    /// let organization = OrganizationDto {
+    ///     name: "Acme Academy".to_string(),
+    ///     email: "certificates@acme.com".to_string(),
+    ///     phone: "+45 87654321".to_string(),
+    ///     address: AddressDto::default(),
+    /// };
+    /// assert!(organization.validate().is_ok());
+    ///
+    /// let organization = OrganizationDto {
+    ///     name: "".to_string(),
+    ///     ..organization
+    /// };
+    /// assert!(organization.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), CrsError> {
+        let mut violations = Violations::default();
+        violations.check(
+            !self.name.trim().is_empty(),
+            "name",
+            "required",
+            &self.name,
+            "must not be empty",
+        );
+        violations.check(
+            Email::parse(self.email.clone()).is_ok(),
+            "email",
+            "email",
+            &self.email,
+            "must be a valid email address",
+        );
+        violations.check(
+            Phone::parse(self.phone.clone()).is_ok(),
+            "phone",
+            "phone",
+            &self.phone,
+            "must be a valid phone number",
+        );
+        violations.into_result("invalid organization")
+    }
+}
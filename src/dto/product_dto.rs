@@ -0,0 +1,126 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::error::CrsError;
+
+use super::{
+    certificate_metadata_dto::ScoreScaleDto,
+    validation::{field_path, Violations},
+};
+
+/// Product data transfer object, used to replace products
+#[derive(Deserialize)]
+pub struct ProductDto {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub organization_id: Uuid,
+    pub validity_days: Option<u32>,
+    pub score_scale: Option<ScoreScaleDto>,
+    pub required_accreditation: Option<RequiredAccreditationDto>,
+}
+
+/// New product data transfer object, products are identified by the id the
+/// certificate payloads refer to
+#[derive(Deserialize)]
+pub struct NewProductDto {
+    pub product_id: u32,
+    #[serde(flatten)]
+    pub product: ProductDto,
+}
+
+#[derive(Deserialize)]
+pub struct RequiredAccreditationDto {
+    pub name: String,
+    pub institution: String,
+}
+
+impl ProductDto {
+    /// Validates the product, collecting every violation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::dto::product_dto::ProductDto;
+    /// use uuid::Uuid;
+    ///
+    /// let product = ProductDto {
+    ///     name: "Rust fundamentals".to_string(),
+    ///     description: "".to_string(),
+    ///     organization_id: Uuid::new_v4(),
+    ///     validity_days: Some(365),
+    ///     score_scale: None,
+    ///     required_accreditation: None,
+    /// };
+    /// assert!(product.validate().is_ok());
+    ///
+    /// let product = ProductDto {
+    ///     validity_days: Some(0),
+    ///     ..product
+    /// };
+    /// assert!(product.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), CrsError> {
+        self.violations().into_result("invalid product")
+    }
+
+    fn violations(&self) -> Violations {
+        let mut violations = Violations::default();
+        violations.check(
+            !self.name.trim().is_empty(),
+            "name",
+            "required",
+            &self.name,
+            "must not be empty",
+        );
+        violations.check(
+            !self.organization_id.is_nil(),
+            "organization_id",
+            "required",
+            self.organization_id,
+            "must reference an organization",
+        );
+        violations.check(
+            self.validity_days != Some(0),
+            "validity_days",
+            "range",
+            self.validity_days,
+            "must be at least 1",
+        );
+        if let Some(scale) = &self.score_scale {
+            scale.validate("score_scale", &mut violations);
+        }
+        if let Some(accreditation) = &self.required_accreditation {
+            violations.check(
+                !accreditation.name.trim().is_empty(),
+                &field_path("required_accreditation", "name"),
+                "required",
+                &accreditation.name,
+                "must not be empty",
+            );
+            violations.check(
+                !accreditation.institution.trim().is_empty(),
+                &field_path("required_accreditation", "institution"),
+                "required",
+                &accreditation.institution,
+                "must not be empty",
+            );
+        }
+        violations
+    }
+}
+
+impl NewProductDto {
+    /// Validates the new product, collecting every violation
+    pub fn validate(&self) -> Result<(), CrsError> {
+        let mut violations = self.product.violations();
+        violations.check(
+            self.product_id != 0,
+            "product_id",
+            "required",
+            self.product_id,
+            "must not be 0",
+        );
+        violations.into_result("invalid product")
+    }
+}
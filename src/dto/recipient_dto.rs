@@ -1,6 +1,13 @@
 use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::{
+    domain::base::{Email, Phone},
+    error::CrsError,
+};
+
+use super::validation::{field_path, Violations};
+
 #[derive(Deserialize)]
 pub struct RecipientDto {
     pub id: Uuid,
@@ -10,16 +17,72 @@ pub struct RecipientDto {
     pub phone: String,
 }
 
+/// Profile of a registered recipient, the registry assigns the id
+#[derive(Deserialize)]
+pub struct RecipientProfileDto {
+    pub first_name: String,
+    #[serde(default)]
+    pub middle_name: Option<String>,
+    pub last_name: String,
+    pub email: String,
+    #[serde(default)]
+    pub phone: String,
+}
+
+/// Filters of the recipient listing
+#[derive(Deserialize)]
+pub struct RecipientQueryDto {
+    pub email: Option<String>,
+}
+
+/// Checks the contact details shared by issuance payloads and registered profiles
+fn validate_details(
+    path: &str,
+    first_name: &str,
+    last_name: &str,
+    email: &str,
+    phone: &str,
+    violations: &mut Violations,
+) {
+    violations.check(
+        !first_name.trim().is_empty(),
+        &field_path(path, "first_name"),
+        "required",
+        first_name,
+        "must not be empty",
+    );
+    violations.check(
+        !last_name.trim().is_empty(),
+        &field_path(path, "last_name"),
+        "required",
+        last_name,
+        "must not be empty",
+    );
+    violations.check(
+        Email::parse(email.to_string()).is_ok(),
+        &field_path(path, "email"),
+        "email",
+        email,
+        "must be a valid email address",
+    );
+    // the phone number is optional, but must be valid when given
+    violations.check(
+        phone.is_empty() || Phone::parse(phone.to_string()).is_ok(),
+        &field_path(path, "phone"),
+        "phone",
+        phone,
+        "must be a valid phone number",
+    );
+}
+
 impl RecipientDto {
-    /// Validates the recipient
-    /// # Returns
-    /// **true** if the recipient is valid, otherwise **false**
+    /// Validates the recipient, recording every violation under the `path` prefix
     ///
     /// # Examples
     ///
     /// ```
     /// use pretty_assertions::assert_eq;
-    /// use crs::dto::recipient_dto::RecipientDto;
+    /// use crs::dto::{recipient_dto::RecipientDto, validation::Violations};
     /// use uuid::Uuid;
     ///
     /// let recipient = RecipientDto {
@@ -29,29 +92,82 @@ impl RecipientDto {
     ///     email: "test@email.com".to_string(),
     ///     phone: "12345678".to_string()
     /// };
-    /// assert_eq!(recipient.is_valid(), true);
+    /// let mut violations = Violations::default();
+    /// recipient.validate("recipient", &mut violations);
+    /// assert_eq!(violations.is_empty(), true);
     /// ```
     /// ---
     ///
     /// ```
     ///
     /// use pretty_assertions::assert_eq;
-    /// use crs::dto::recipient_dto::RecipientDto;
+    /// use crs::dto::{recipient_dto::RecipientDto, validation::Violations};
     /// use uuid::Uuid;
     ///
     /// let recipient = RecipientDto {
     ///     id: Uuid::nil(),
     ///     first_name: "firstName".to_string(),
     ///     last_name: "lastName".to_string(),
-    ///     email: "test@email.com".to_string(),
+    ///     email: "not an email".to_string(),
     ///     phone: "12345678".to_string()
     /// };
-    /// assert_eq!(recipient.is_valid(), false);
+    /// let mut violations = Violations::default();
+    /// recipient.validate("recipient", &mut violations);
+    /// let fields: Vec<String> = violations.into_inner().into_iter().map(|err| err.field).collect();
+    /// assert_eq!(fields, vec!["recipient.id", "recipient.email"]);
+    /// ```
+    pub fn validate(&self, path: &str, violations: &mut Violations) {
+        violations.check(
+            !self.id.is_nil() && !self.id.is_max(),
+            &field_path(path, "id"),
+            "id",
+            self.id,
+            "must be a valid, non nil UUID",
+        );
+        validate_details(
+            path,
+            &self.first_name,
+            &self.last_name,
+            &self.email,
+            &self.phone,
+            violations,
+        );
+    }
+}
+
+impl RecipientProfileDto {
+    /// Validates the profile, collecting every violation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crs::dto::recipient_dto::RecipientProfileDto;
+    ///
+    /// let profile = RecipientProfileDto {
+    ///     first_name: "John".to_string(),
+    ///     middle_name: None,
+    ///     last_name: "Doe".to_string(),
+    ///     email: "john.doe@email.com".to_string(),
+    ///     phone: "".to_string(),
+    /// };
+    /// assert!(profile.validate().is_ok());
+    ///
+    /// let profile = RecipientProfileDto {
+    ///     email: "john.doe".to_string(),
+    ///     ..profile
+    /// };
+    /// assert!(profile.validate().is_err());
     /// ```
-    pub fn is_valid(&self) -> bool {
-        self.id != Uuid::nil()
-            && !self.first_name.is_empty()
-            && !self.last_name.is_empty()
-            && !self.email.is_empty()
+    pub fn validate(&self) -> Result<(), CrsError> {
+        let mut violations = Violations::default();
+        validate_details(
+            "",
+            &self.first_name,
+            &self.last_name,
+            &self.email,
+            &self.phone,
+            &mut violations,
+        );
+        violations.into_result("invalid recipient")
     }
 }
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::error::CrsError;
+
+use super::validation::Violations;
+
+/// Renewal request data transfer object, an empty body renews from now on for
+/// as long as the renewed certificate was valid
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct RenewalDto {
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+impl RenewalDto {
+    /// Validates the requested validity window
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use crs::dto::renewal_dto::RenewalDto;
+    ///
+    /// let now = Utc::now();
+    /// let renewal = RenewalDto {
+    ///     valid_from: Some(now),
+    ///     valid_until: Some(now + Duration::days(365)),
+    /// };
+    /// assert!(renewal.validate().is_ok());
+    ///
+    /// let renewal = RenewalDto {
+    ///     valid_until: Some(now - Duration::days(1)),
+    ///     ..renewal
+    /// };
+    /// assert!(renewal.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), CrsError> {
+        let mut violations = Violations::default();
+        let valid_from = self.valid_from.unwrap_or_else(Utc::now);
+        violations.check(
+            self.valid_until
+                .is_none_or(|valid_until| valid_until > valid_from),
+            "valid_until",
+            "date_order",
+            self.valid_until,
+            "must be after valid_from",
+        );
+        violations.into_result("invalid renewal")
+    }
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::domain::revocation::RevocationReason;
+
+/// Revocation request data transfer object
+#[derive(Deserialize)]
+pub struct RevocationDto {
+    pub reason: String,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub comment: Option<String>,
+}
+
+impl RevocationDto {
+    /// Validates the revocation request
+    /// # Returns
+    /// **true** if the reason is a known reason code and the timestamp is not in the future, otherwise **false**
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pretty_assertions::assert_eq;
+    /// use crs::dto::revocation_dto::RevocationDto;
+    ///
+    /// let revocation = RevocationDto {
+    ///     reason: "issued_in_error".to_string(),
+    ///     revoked_at: None,
+    ///     comment: None,
+    /// };
+    /// assert_eq!(revocation.is_valid(), true);
+    /// ```
+    /// ---
+    ///
+    /// ```
+    ///
+    /// use pretty_assertions::assert_eq;
+    /// use crs::dto::revocation_dto::RevocationDto;
+    ///
+    /// let revocation = RevocationDto {
+    ///     reason: "bored".to_string(),
+    ///     revoked_at: None,
+    ///     comment: None,
+    /// };
+    /// assert_eq!(revocation.is_valid(), false);
+    /// ```
+    pub fn is_valid(&self) -> bool {
+        RevocationReason::from_reason_str(&self.reason).is_ok()
+            && self
+                .revoked_at
+                .is_none_or(|revoked_at| revoked_at <= Utc::now())
+    }
+}
@@ -0,0 +1,52 @@
+use serde::Serialize;
+
+use crate::error::{CrsError, FieldError};
+
+/// Collects every rule violation of an incoming payload instead of stopping at the first one
+#[derive(Default, Debug)]
+pub struct Violations(Vec<FieldError>);
+
+impl Violations {
+    /// Records a violation of `rule` by `field` unless `valid` holds
+    pub fn check(
+        &mut self,
+        valid: bool,
+        field: &str,
+        rule: &'static str,
+        value: impl Serialize,
+        message: &str,
+    ) {
+        if !valid {
+            self.0.push(FieldError::new(field, rule, value, message));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_inner(self) -> Vec<FieldError> {
+        self.0
+    }
+
+    /// Turns the collected violations into a validation error, if there are any
+    pub fn into_result(self, message: &str) -> Result<(), CrsError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(CrsError::Validation {
+                message: message.to_string(),
+                errors: self.0,
+            })
+        }
+    }
+}
+
+/// Path of a nested field, e.g. `field_path("recipient", "email")` is `recipient.email`
+pub fn field_path(prefix: &str, field: &str) -> String {
+    if prefix.is_empty() {
+        field.to_string()
+    } else {
+        format!("{prefix}.{field}")
+    }
+}
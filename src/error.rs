@@ -0,0 +1,391 @@
+use std::error::Error;
+
+use actix_web::{
+    error::{JsonPayloadError, QueryPayloadError},
+    http::{header::ContentType, StatusCode},
+    HttpRequest, HttpResponse, ResponseError,
+};
+use log::error;
+use serde::Serialize;
+
+use crate::{
+    domain::error::{
+        AccreditationChangeError, AccreditationExistsError, AccreditationStatusError,
+        CertificateDetailsMissingError, CertificateParseError, CertificateQueryError,
+        CertificateRenewalError, CertificateRevokedError, CertificateUpdateError, InvalidIdError,
+        InvalidScoreError, OrganizationParseError, RecipientParseError, RevocationReasonError,
+        ScopeParseError,
+    },
+    export::pdf::PdfError,
+    repository::RepositoryError,
+};
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Violation of a validation rule by a single request field
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    /// Path of the offending field, e.g. `recipient.email`
+    pub field: String,
+    /// Name of the violated rule, e.g. `email` or `range`
+    pub rule: &'static str,
+    /// The offending value, omitted when it is unknown
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    pub value: serde_json::Value,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(
+        field: impl Into<String>,
+        rule: &'static str,
+        value: impl Serialize,
+        message: impl Into<String>,
+    ) -> Self {
+        FieldError {
+            field: field.into(),
+            rule,
+            value: serde_json::to_value(value).unwrap_or_default(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Every error the service reports to its clients
+#[derive(Debug)]
+pub enum CrsError {
+    /// The request body or query failed validation
+    Validation {
+        message: String,
+        errors: Vec<FieldError>,
+    },
+    /// The request could not be understood at all
+    BadRequest(String),
+    /// The request carries no valid credentials
+    Unauthorized(String),
+    /// The credentials of the request do not grant the operation
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    PreconditionFailed(String),
+    PreconditionRequired(String),
+    /// The request exceeds a configured size limit
+    PayloadTooLarge(String),
+    /// Storage failures, details are logged but never sent to clients
+    Storage(RepositoryError),
+    /// Any other server side failure, details are logged but never sent to clients
+    Internal(String),
+}
+
+/// RFC 7807 problem details
+#[derive(Serialize)]
+struct Problem<'a> {
+    #[serde(rename = "type")]
+    problem_type: &'a str,
+    title: &'a str,
+    status: u16,
+    detail: String,
+    code: &'a str,
+    #[serde(skip_serializing_if = "<[FieldError]>::is_empty")]
+    errors: &'a [FieldError],
+}
+
+impl CrsError {
+    /// Shorthand for a validation error without field details
+    pub fn invalid(message: impl Into<String>) -> Self {
+        CrsError::Validation {
+            message: message.into(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Stable machine readable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            CrsError::Validation { .. } => "validation_failed",
+            CrsError::BadRequest(_) => "bad_request",
+            CrsError::Unauthorized(_) => "unauthorized",
+            CrsError::Forbidden(_) => "forbidden",
+            CrsError::NotFound(_) => "not_found",
+            CrsError::Conflict(_) => "conflict",
+            CrsError::PreconditionFailed(_) => "precondition_failed",
+            CrsError::PreconditionRequired(_) => "precondition_required",
+            CrsError::PayloadTooLarge(_) => "payload_too_large",
+            CrsError::Storage(_) => "storage_error",
+            CrsError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Human readable message that is safe to show to clients
+    fn detail(&self) -> String {
+        match self {
+            CrsError::Validation { message, .. }
+            | CrsError::BadRequest(message)
+            | CrsError::Unauthorized(message)
+            | CrsError::Forbidden(message)
+            | CrsError::NotFound(message)
+            | CrsError::Conflict(message)
+            | CrsError::PreconditionFailed(message)
+            | CrsError::PreconditionRequired(message)
+            | CrsError::PayloadTooLarge(message) => message.clone(),
+            CrsError::Storage(_) => "certificate storage failed, try again later".to_string(),
+            CrsError::Internal(_) => "an unexpected error occurred".to_string(),
+        }
+    }
+
+    fn field_errors(&self) -> &[FieldError] {
+        match self {
+            CrsError::Validation { errors, .. } => errors,
+            _ => &[],
+        }
+    }
+}
+
+impl Error for CrsError {}
+
+impl std::fmt::Display for CrsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrsError::Storage(err) => err.fmt(f),
+            CrsError::Internal(message) => message.fmt(f),
+            _ => self.detail().fmt(f),
+        }
+    }
+}
+
+impl ResponseError for CrsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CrsError::Validation { .. } | CrsError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            CrsError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            CrsError::Forbidden(_) => StatusCode::FORBIDDEN,
+            CrsError::NotFound(_) => StatusCode::NOT_FOUND,
+            CrsError::Conflict(_) => StatusCode::CONFLICT,
+            CrsError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            CrsError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            CrsError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            CrsError::Storage(_) | CrsError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("{}", self);
+        }
+        let problem = Problem {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or_default(),
+            status: status.as_u16(),
+            detail: self.detail(),
+            code: self.code(),
+            errors: self.field_errors(),
+        };
+        HttpResponse::build(status)
+            .content_type(ContentType(PROBLEM_CONTENT_TYPE.parse().unwrap()))
+            .json(problem)
+    }
+}
+
+impl From<RepositoryError> for CrsError {
+    fn from(err: RepositoryError) -> Self {
+        CrsError::Storage(err)
+    }
+}
+
+impl From<PdfError> for CrsError {
+    fn from(err: PdfError) -> Self {
+        CrsError::Internal(err.to_string())
+    }
+}
+
+impl From<InvalidIdError> for CrsError {
+    fn from(err: InvalidIdError) -> Self {
+        CrsError::BadRequest(err.to_string())
+    }
+}
+
+impl From<CertificateQueryError> for CrsError {
+    fn from(err: CertificateQueryError) -> Self {
+        CrsError::invalid(err.to_string())
+    }
+}
+
+impl From<CertificateParseError> for CrsError {
+    fn from(err: CertificateParseError) -> Self {
+        CrsError::invalid(err.to_string())
+    }
+}
+
+impl From<CertificateDetailsMissingError> for CrsError {
+    fn from(err: CertificateDetailsMissingError) -> Self {
+        CrsError::Conflict(err.to_string())
+    }
+}
+
+impl From<OrganizationParseError> for CrsError {
+    fn from(err: OrganizationParseError) -> Self {
+        CrsError::invalid(err.to_string())
+    }
+}
+
+impl From<RecipientParseError> for CrsError {
+    fn from(err: RecipientParseError) -> Self {
+        CrsError::invalid(err.to_string())
+    }
+}
+
+impl From<AccreditationExistsError> for CrsError {
+    fn from(err: AccreditationExistsError) -> Self {
+        CrsError::Conflict(err.to_string())
+    }
+}
+
+impl From<AccreditationStatusError> for CrsError {
+    fn from(err: AccreditationStatusError) -> Self {
+        CrsError::Validation {
+            message: "invalid accreditation".to_string(),
+            errors: vec![FieldError::new(
+                "status",
+                "accreditation_status",
+                (),
+                err.to_string(),
+            )],
+        }
+    }
+}
+
+impl From<InvalidScoreError> for CrsError {
+    fn from(err: InvalidScoreError) -> Self {
+        CrsError::Validation {
+            message: "invalid score".to_string(),
+            errors: vec![FieldError::new("score", "range", (), err.to_string())],
+        }
+    }
+}
+
+impl From<AccreditationChangeError> for CrsError {
+    fn from(err: AccreditationChangeError) -> Self {
+        match err {
+            AccreditationChangeError::Missing(err) => CrsError::NotFound(err.to_string()),
+            AccreditationChangeError::Transition(err) => CrsError::Conflict(err.to_string()),
+        }
+    }
+}
+
+impl From<RevocationReasonError> for CrsError {
+    fn from(err: RevocationReasonError) -> Self {
+        CrsError::Validation {
+            message: "invalid revocation".to_string(),
+            errors: vec![FieldError::new(
+                "reason",
+                "revocation_reason",
+                (),
+                err.to_string(),
+            )],
+        }
+    }
+}
+
+impl From<CertificateRevokedError> for CrsError {
+    fn from(err: CertificateRevokedError) -> Self {
+        CrsError::Conflict(err.to_string())
+    }
+}
+
+impl From<CertificateUpdateError> for CrsError {
+    fn from(err: CertificateUpdateError) -> Self {
+        match err {
+            CertificateUpdateError::Revoked(err) => err.into(),
+            CertificateUpdateError::Score(err) => err.into(),
+        }
+    }
+}
+
+impl From<CertificateRenewalError> for CrsError {
+    fn from(err: CertificateRenewalError) -> Self {
+        CrsError::Conflict(err.to_string())
+    }
+}
+
+impl From<ScopeParseError> for CrsError {
+    fn from(err: ScopeParseError) -> Self {
+        CrsError::Validation {
+            message: "invalid API key".to_string(),
+            errors: vec![FieldError::new("scopes", "scope", (), err.to_string())],
+        }
+    }
+}
+
+/// Reports malformed JSON bodies as problem details
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    CrsError::BadRequest(err.to_string()).into()
+}
+
+/// Reports malformed query strings as problem details
+pub fn query_error_handler(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    CrsError::BadRequest(err.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body::to_bytes, http::header, ResponseError};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::{CrsError, FieldError, PROBLEM_CONTENT_TYPE};
+    use crate::repository::RepositoryError;
+
+    #[actix_web::test]
+    async fn validation_error_should_render_problem_details() {
+        let err = CrsError::Validation {
+            message: "invalid certificate".to_string(),
+            errors: vec![FieldError::new(
+                "metadata.score",
+                "range",
+                120,
+                "must be at most 100",
+            )],
+        };
+        let resp = err.error_response();
+
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_CONTENT_TYPE
+        );
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "type": "about:blank",
+                "title": "Bad Request",
+                "status": 400,
+                "detail": "invalid certificate",
+                "code": "validation_failed",
+                "errors": [{
+                    "field": "metadata.score",
+                    "rule": "range",
+                    "value": 120,
+                    "message": "must be at most 100"
+                }]
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn storage_error_should_not_leak_details() {
+        let err = CrsError::from(RepositoryError("connection refused".to_string()));
+        let resp = err.error_response();
+
+        assert_eq!(resp.status().as_u16(), 500);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body["code"], "storage_error");
+        assert!(!body["detail"]
+            .as_str()
+            .unwrap()
+            .contains("connection refused"));
+    }
+}
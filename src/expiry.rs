@@ -0,0 +1,256 @@
+use std::{error::Error, sync::Arc, time::Duration};
+
+use actix_web::{body::BoxBody, rt, web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, SubsecRound, Utc};
+use log::{error, info, warn};
+use serde::Serialize;
+
+use crate::{
+    audit::Auditor,
+    domain::audit::{snapshot, AuditAction},
+    helpers::respond_with_json,
+    repository::{AuditRepository, CertificateRepository, RepositoryError},
+    signing::{sign_changed, Keyring},
+};
+
+/// Actor of the changes made by the background expiry job
+const EXPIRY_JOB_ACTOR: &str = "job:expiry";
+
+/// Seconds between expiry runs unless `CRS_EXPIRY_INTERVAL_SECS` says otherwise
+pub const DEFAULT_EXPIRY_INTERVAL_SECS: u64 = 3600;
+
+/// Number of due certificates loaded at once
+const EXPIRY_PAGE_SIZE: usize = 100;
+
+/// Schedule of the background expiry job
+pub struct ExpiryConfig {
+    pub interval: Duration,
+}
+
+#[derive(Debug)]
+pub struct ExpiryConfigError(pub String);
+
+impl Error for ExpiryConfigError {}
+
+impl std::fmt::Display for ExpiryConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid expiry configuration: {}", self.0)
+    }
+}
+
+impl ExpiryConfig {
+    /// Reads the interval from `CRS_EXPIRY_INTERVAL_SECS`, falling back to the default.
+    ///
+    /// Returns `None` when the interval is `0`, which disables the background job.
+    pub fn from_env() -> Result<Option<ExpiryConfig>, ExpiryConfigError> {
+        let secs = match dotenvy::var("CRS_EXPIRY_INTERVAL_SECS") {
+            Ok(value) => value.parse::<u64>().map_err(|_| {
+                ExpiryConfigError(format!(
+                    "CRS_EXPIRY_INTERVAL_SECS must be a number of seconds, got `{}`",
+                    value
+                ))
+            })?,
+            Err(_) => DEFAULT_EXPIRY_INTERVAL_SECS,
+        };
+        if secs == 0 {
+            info!("Automatic certificate expiry is disabled");
+            return Ok(None);
+        }
+        Ok(Some(ExpiryConfig {
+            interval: Duration::from_secs(secs),
+        }))
+    }
+}
+
+/// Outcome of an expiry run
+#[derive(Serialize, Debug, Default)]
+pub struct ExpiryReport {
+    /// Certificates marked expired
+    pub certificates: usize,
+    /// Accreditations marked expired
+    pub accreditations: usize,
+    /// Certificates changed concurrently, left for the next run
+    pub skipped: usize,
+}
+
+impl Responder for ExpiryReport {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(self)
+    }
+}
+
+/// Marks every certificate and accreditation whose validity ended at the given
+/// point in time expired, recording each change in the audit log. Certificates
+/// whose accreditation expired are signed again, since the signature covers it.
+pub async fn expire_due(
+    repository: &dyn CertificateRepository,
+    auditor: &Auditor,
+    keyring: Option<&Keyring>,
+    at: DateTime<Utc>,
+) -> Result<ExpiryReport, RepositoryError> {
+    let mut report = ExpiryReport::default();
+    loop {
+        let due = repository.find_expiring(at, EXPIRY_PAGE_SIZE).await?;
+        let count = due.len();
+        let mut progressed = false;
+        for mut certificate in due {
+            let expected_version = certificate.version;
+            let before = snapshot(&certificate);
+            let expiration = certificate.expire_at(at);
+            if expiration.accreditation {
+                sign_changed(keyring, &mut certificate);
+            }
+            if !repository.update(&certificate, expected_version).await? {
+                report.skipped += 1;
+                continue;
+            }
+            auditor
+                .record(AuditAction::Expire, Some(before), &certificate)
+                .await;
+            progressed = true;
+            if expiration.certificate {
+                report.certificates += 1;
+                info!("Certificate expired: {}", certificate.id.as_uuid());
+            }
+            if expiration.accreditation {
+                report.accreditations += 1;
+                info!(
+                    "Accreditation of certificate expired: {}",
+                    certificate.id.as_uuid()
+                );
+            }
+        }
+        // a page of concurrently changed certificates would be loaded again forever
+        if count < EXPIRY_PAGE_SIZE || !progressed {
+            break;
+        }
+    }
+    Ok(report)
+}
+
+/// Runs [`expire_due`] on the configured interval for as long as the server runs
+pub fn spawn_expiry_job(
+    repository: Arc<dyn CertificateRepository>,
+    audit: Arc<dyn AuditRepository>,
+    keyring: Option<web::Data<Keyring>>,
+    config: &ExpiryConfig,
+) {
+    let auditor = Auditor::new(web::Data::from(audit), EXPIRY_JOB_ACTOR);
+    let interval = config.interval;
+    info!("Expiring certificates every {} seconds", interval.as_secs());
+    rt::spawn(async move {
+        let mut ticks = rt::time::interval(interval);
+        loop {
+            ticks.tick().await;
+            let keyring = keyring.as_ref().map(|keyring| keyring.get_ref());
+            match expire_due(&*repository, &auditor, keyring, Utc::now().trunc_subsecs(3)).await {
+                Ok(report) if report.skipped > 0 => warn!(
+                    "Expiry run skipped {} concurrently changed certificates",
+                    report.skipped
+                ),
+                Ok(_) => {}
+                Err(err) => error!("Expiry run failed. {}", err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::web;
+    use chrono::{Duration, SubsecRound, Utc};
+    use ed25519_dalek::SigningKey;
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use super::{expire_due, EXPIRY_JOB_ACTOR};
+    use crate::{
+        audit::Auditor,
+        domain::{
+            accreditation::{Accreditation, AccreditationStatus},
+            audit::AuditAction,
+            revocation::CertificateStatus,
+            signature::SignatureStatus,
+            validity::{ValidUntil, Validity},
+        },
+        repository::{
+            in_memory::{InMemoryAuditRepository, InMemoryCertificateRepository},
+            AuditRepository, CertificateRepository,
+        },
+        signing::Keyring,
+        test_helpers::certificate_for,
+    };
+
+    #[actix_web::test]
+    async fn expire_due_should_expire_certificates_and_accreditations_once() {
+        let repository = InMemoryCertificateRepository::default();
+        let audit: Arc<dyn AuditRepository> = Arc::new(InMemoryAuditRepository::default());
+        let auditor = Auditor::new(web::Data::from(audit.clone()), EXPIRY_JOB_ACTOR);
+        let keyring = Keyring::with_default_key(SigningKey::from_bytes(&[7u8; 32]));
+        let now = Utc::now().trunc_subsecs(3);
+        let validity = |valid_until| Validity {
+            first_valid_from: now - Duration::days(30),
+            valid_from: now - Duration::days(30),
+            valid_until,
+        };
+
+        let mut expired = certificate_for(Uuid::new_v4());
+        expired.validity = Some(validity(ValidUntil::Expiry(now - Duration::days(1))));
+        expired.accreditation = Some(Accreditation {
+            name: "ISO 9001".to_string(),
+            institution: "ISO".to_string(),
+            start_date: now - Duration::days(30),
+            end_date: Some(now - Duration::days(1)),
+            status: AccreditationStatus::Active,
+            status_at_issuance: Some(AccreditationStatus::Active),
+            history: Vec::new(),
+        });
+        keyring.sign(&mut expired);
+        let mut current = certificate_for(Uuid::new_v4());
+        current.validity = Some(validity(ValidUntil::Expiry(now + Duration::days(1))));
+        let mut unlimited = certificate_for(Uuid::new_v4());
+        unlimited.validity = Some(validity(ValidUntil::EndOfTime));
+        for certificate in [&expired, &current, &unlimited] {
+            repository.insert(certificate).await.unwrap();
+        }
+
+        let report = expire_due(&repository, &auditor, Some(&keyring), now)
+            .await
+            .unwrap();
+        assert_eq!((report.certificates, report.accreditations), (1, 1));
+        let history = audit
+            .find_by_certificate_id(expired.id.as_uuid())
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].action, AuditAction::Expire);
+        assert_eq!(history[0].actor, EXPIRY_JOB_ACTOR);
+
+        let stored = repository
+            .find_by_id(expired.account_id, expired.id.as_uuid())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, CertificateStatus::Expired);
+        assert_eq!(stored.expired_at, Some(now));
+        assert_eq!(keyring.verify(&stored), SignatureStatus::Valid);
+        let accreditation = stored.accreditation.unwrap();
+        assert_eq!(accreditation.status, AccreditationStatus::Expired);
+        assert_eq!(accreditation.history.len(), 1);
+        let stored = repository
+            .find_by_id(current.account_id, current.id.as_uuid())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, CertificateStatus::Active);
+
+        let report = expire_due(&repository, &auditor, Some(&keyring), now)
+            .await
+            .unwrap();
+        assert_eq!((report.certificates, report.accreditations), (0, 0));
+    }
+}
@@ -0,0 +1,259 @@
+use std::{error::Error, io::Read};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    domain::certificate::Certificate,
+    dto::{
+        certificate_dto::CertificateDto,
+        certificate_metadata_dto::{AccreditationDto, CertificateMetadataDto, ScoreScaleDto},
+        recipient_dto::RecipientDto,
+    },
+    error::FieldError,
+};
+
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// One certificate as a spreadsheet row.
+///
+/// Exports fill every column, imports only read the issuance columns and ignore
+/// `certificate_id`, `accreditation_status_at_issuance`, `result`, `status` and
+/// `created_date`, so an export can be edited and imported again.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct CertificateRow {
+    pub certificate_id: Option<Uuid>,
+    pub account_id: u32,
+    pub product_id: u32,
+    pub organization_id: Option<Uuid>,
+    pub recipient_id: Option<Uuid>,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: String,
+    pub score: u32,
+    pub max_score: Option<u32>,
+    pub min_score: Option<u32>,
+    pub passing_score: Option<u32>,
+    pub progress: f32,
+    pub acquired_date: Option<DateTime<Utc>>,
+    pub accreditation_name: String,
+    pub accreditation_institution: String,
+    pub accreditation_start_date: Option<DateTime<Utc>>,
+    pub accreditation_end_date: Option<DateTime<Utc>>,
+    pub accreditation_status: String,
+    pub accreditation_status_at_issuance: String,
+    pub result: String,
+    pub status: String,
+    pub created_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+pub struct CsvError(pub String);
+
+impl Error for CsvError {}
+
+impl std::fmt::Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to write csv: {}", self.0)
+    }
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(err: csv::Error) -> Self {
+        CsvError(err.to_string())
+    }
+}
+
+impl CertificateRow {
+    pub fn from_certificate(certificate: &Certificate) -> Self {
+        let accreditation = certificate.accreditation.as_ref();
+        let score = certificate.assessment.score.as_ref();
+        let name = certificate.recipient.name.as_ref();
+        CertificateRow {
+            certificate_id: Some(certificate.id.as_uuid()),
+            account_id: certificate.account_id,
+            product_id: certificate.product_id,
+            organization_id: certificate.authority_id(),
+            recipient_id: Some(certificate.recipient.id.as_uuid()),
+            first_name: name.map(|name| name.first_name.clone()).unwrap_or_default(),
+            last_name: name.map(|name| name.last_name.clone()).unwrap_or_default(),
+            email: certificate
+                .recipient
+                .email
+                .as_ref()
+                .map(|email| email.as_string())
+                .unwrap_or_default(),
+            phone: certificate
+                .recipient
+                .phone
+                .as_ref()
+                .map(|phone| phone.as_string())
+                .unwrap_or_default(),
+            score: score.map_or(0, |score| score.value),
+            max_score: score.map(|score| score.max),
+            min_score: score.map(|score| score.min),
+            passing_score: score.map(|score| score.passing_score),
+            progress: certificate.assessment.progress,
+            acquired_date: None,
+            accreditation_name: accreditation
+                .map(|acc| acc.name.clone())
+                .unwrap_or_default(),
+            accreditation_institution: accreditation
+                .map(|acc| acc.institution.clone())
+                .unwrap_or_default(),
+            accreditation_start_date: accreditation.map(|acc| acc.start_date),
+            accreditation_end_date: accreditation.and_then(|acc| acc.end_date),
+            accreditation_status: accreditation
+                .map(|acc| acc.status.to_string())
+                .unwrap_or_default(),
+            accreditation_status_at_issuance: accreditation
+                .and_then(|acc| acc.status_at_issuance.as_ref())
+                .map(|status| status.to_string())
+                .unwrap_or_default(),
+            result: certificate.assessment.result.to_string(),
+            status: certificate.status.to_string(),
+            created_date: Some(certificate.created_date),
+        }
+    }
+
+    /// Maps the issuance columns to a certificate dto, a row without an accreditation
+    /// name has no accreditation and a row without a `max_score` has no score scale
+    pub fn into_dto(self) -> CertificateDto {
+        let accreditation = (!self.accreditation_name.is_empty()).then(|| AccreditationDto {
+            name: self.accreditation_name,
+            institution: self.accreditation_institution,
+            start_date: self.accreditation_start_date.unwrap_or_default(),
+            end_date: self.accreditation_end_date,
+            status: self.accreditation_status,
+        });
+        let score_scale = self.max_score.map(|max| ScoreScaleDto {
+            max,
+            min: self.min_score.unwrap_or_default(),
+            passing_score: self.passing_score.unwrap_or_default(),
+        });
+        CertificateDto {
+            account_id: self.account_id,
+            product_id: self.product_id,
+            organization_id: self.organization_id.unwrap_or_default(),
+            recipient: RecipientDto {
+                id: self.recipient_id.unwrap_or_default(),
+                first_name: self.first_name,
+                last_name: self.last_name,
+                email: self.email,
+                phone: self.phone,
+            },
+            metadata: CertificateMetadataDto {
+                score: self.score,
+                progress: self.progress,
+                acquired_date: self.acquired_date,
+                accreditation,
+                score_scale,
+            },
+        }
+    }
+}
+
+/// A parsed import row, identified by its line in the uploaded file
+pub struct ImportedRow {
+    pub line: u64,
+    pub certificate: Result<CertificateDto, Vec<FieldError>>,
+}
+
+/// Reads certificates from CSV with a header row, validating each row on its own.
+///
+/// Rows that cannot be parsed or fail validation carry their violations, the
+/// remaining rows are unaffected.
+pub fn read_certificates<R: Read>(reader: R) -> Vec<ImportedRow> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let mut rows = Vec::new();
+    for (index, record) in reader.deserialize::<CertificateRow>().enumerate() {
+        // the header is line 1
+        let mut line = index as u64 + 2;
+        let certificate = match record {
+            Ok(row) => {
+                let certificate = row.into_dto();
+                let violations = certificate.violations();
+                if violations.is_empty() {
+                    Ok(certificate)
+                } else {
+                    Err(violations.into_inner())
+                }
+            }
+            Err(err) => {
+                if let Some(position) = err.position() {
+                    line = position.line();
+                }
+                Err(vec![FieldError::new("", "format", (), err.to_string())])
+            }
+        };
+        rows.push(ImportedRow { line, certificate });
+    }
+    rows
+}
+
+/// Writes certificates as CSV, with a header row when `with_header` is set
+pub fn write_certificates(
+    certificates: &[Certificate],
+    with_header: bool,
+) -> Result<Vec<u8>, CsvError> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(with_header)
+        .from_writer(Vec::new());
+    for certificate in certificates {
+        writer.serialize(CertificateRow::from_certificate(certificate))?;
+    }
+    writer.into_inner().map_err(|err| CsvError(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use super::{read_certificates, write_certificates};
+    use crate::test_helpers::certificate_for;
+
+    #[test]
+    fn exported_certificates_should_import_again() {
+        let user_id = Uuid::new_v4();
+        let csv = write_certificates(&[certificate_for(user_id)], true).unwrap();
+
+        let rows = read_certificates(csv.as_slice());
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].line, 2);
+        let certificate = rows[0].certificate.as_ref().unwrap();
+        assert_eq!(certificate.recipient.id, user_id);
+        assert_eq!(certificate.recipient.email, "john.doe@email.com");
+        assert_eq!(certificate.metadata.score, 100);
+        assert_eq!(certificate.account_id, 20);
+    }
+
+    #[test]
+    fn invalid_rows_should_report_their_line_and_violations() {
+        let csv = format!(
+            "account_id,product_id,organization_id,recipient_id,first_name,last_name,email,phone,score,progress\n\
+             20,15,{organization_id},{},Jane,Doe,jane@email.com,,90,1.0\n\
+             20,15,{organization_id},{},Jane,Doe,not-an-email,,90,1.0\n\
+             twenty,15,{organization_id},,Jane,Doe,jane@email.com,,90,1.0\n",
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            organization_id = Uuid::new_v4()
+        );
+
+        let rows = read_certificates(csv.as_bytes());
+
+        assert!(rows[0].certificate.is_ok());
+        let errors = rows[1].certificate.as_ref().err().unwrap();
+        assert_eq!(rows[1].line, 3);
+        assert_eq!(errors[0].field, "recipient.email");
+        let errors = rows[2].certificate.as_ref().err().unwrap();
+        assert_eq!(rows[2].line, 4);
+        assert_eq!(errors[0].rule, "format");
+    }
+}
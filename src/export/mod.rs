@@ -0,0 +1,56 @@
+pub mod certificate_csv;
+pub mod open_badges;
+pub mod pdf;
+pub mod verifiable_credential;
+
+use serde_json::Value;
+
+/// Serializes a JSON value in its JSON Canonicalization Scheme (RFC 8785) form.
+///
+/// Object members are sorted by their UTF-16 code units and integral numbers are
+/// written without a fraction, which is what signatures over exported documents
+/// are computed on.
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            let members: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    format!("{}:{}", Value::String(key.clone()), canonical_json(value))
+                })
+                .collect();
+            format!("{{{}}}", members.join(","))
+        }
+        Value::Array(values) => {
+            let values: Vec<String> = values.iter().map(canonical_json).collect();
+            format!("[{}]", values.join(","))
+        }
+        Value::Number(number) => match number.as_f64() {
+            Some(float) if number.is_f64() && float.fract() == 0.0 && float.abs() < 1e15 => {
+                format!("{}", float as i64)
+            }
+            _ => number.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::canonical_json;
+
+    #[test]
+    fn canonical_json_should_sort_members_and_trim_numbers() {
+        let value = json!({"b": [1.0, 0.5, "x"], "a": {"d": null, "c": true}});
+
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"a":{"c":true,"d":null},"b":[1,0.5,"x"]}"#
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use actix_web::{body::BoxBody, http::header::ACCEPT, web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    domain::{
+        base::Email, certificate::Certificate, error::CertificateDetailsMissingError,
+        revocation::CertificateStatus, validity::ValidUntil,
+    },
+    helpers::respond_with_json_as,
+    signing::Keyring,
+};
+
+use super::verifiable_credential::{
+    create_proof, did_key, verify_proof, DataIntegrityProof, VC_CONTENT_TYPE, VC_CONTEXT,
+};
+
+pub const OPEN_BADGES_V2_CONTENT_TYPE: &str = "application/ld+json";
+pub const OPEN_BADGES_V2_CONTEXT: &str = "https://w3id.org/openbadges/v2";
+pub const OPEN_BADGES_V3_CONTEXT: &str =
+    "https://purl.imsglobal.org/spec/ob/v3p0/context-3.0.3.json";
+/// Profile a client adds to the verifiable credential media type to ask for an Open Badges 3.0 credential
+pub const OPEN_BADGES_V3_PROFILE: &str = "https://purl.imsglobal.org/spec/ob/v3p0";
+
+/// Salted SHA-256 hash of a recipient email, as used by Open Badges identity objects
+pub fn hash_identity(email: &Email, salt: &str) -> String {
+    let identity = email.as_string().trim().to_lowercase();
+    format!(
+        "sha256${:x}",
+        Sha256::digest(format!("{}{}", identity, salt))
+    )
+}
+
+/// Open Badges 2.0 assertion with its badge class and issuer profile embedded
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Assertion {
+    #[serde(rename = "@context")]
+    pub context: String,
+    #[serde(rename = "type")]
+    pub assertion_type: String,
+    pub id: String,
+    pub recipient: IdentityObject,
+    pub badge: BadgeClass,
+    pub verification: VerificationObject,
+    pub issued_on: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub revoked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revocation_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IdentityObject {
+    #[serde(rename = "type")]
+    pub identity_type: String,
+    pub hashed: bool,
+    pub salt: String,
+    pub identity: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BadgeClass {
+    #[serde(rename = "type")]
+    pub badge_type: String,
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub criteria: Criteria,
+    pub issuer: Profile,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Criteria {
+    pub narrative: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Profile {
+    #[serde(rename = "type")]
+    pub profile_type: String,
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerificationObject {
+    #[serde(rename = "type")]
+    pub verification_type: String,
+}
+
+/// Id of the badge class awarded for a product
+fn achievement_id(product_id: u32) -> String {
+    format!("urn:crs:product:{}", product_id)
+}
+
+fn criteria_of(certificate: &Certificate) -> Criteria {
+    Criteria {
+        narrative: match certificate.assessment.score.as_ref() {
+            Some(score) => format!(
+                "Achieve a score of at least {} out of {}",
+                score.passing_score, score.max
+            ),
+            None => "Complete the assessment".to_string(),
+        },
+    }
+}
+
+impl Assertion {
+    /// Maps a certificate to a hosted Open Badges 2.0 assertion.
+    ///
+    /// `base_url` is the public address of the service, the assertion is hosted at
+    /// `{base_url}/api/certificates/{certificate_id}/badge`.
+    pub fn from_certificate(
+        certificate: &Certificate,
+        base_url: &str,
+    ) -> Result<Self, CertificateDetailsMissingError> {
+        let (_, email, authority) = certificate.issued_details()?;
+        let salt = certificate.id.as_uuid().simple().to_string();
+        Ok(Assertion {
+            context: OPEN_BADGES_V2_CONTEXT.to_string(),
+            assertion_type: "Assertion".to_string(),
+            id: format!(
+                "{}/api/certificates/{}/badge",
+                base_url.trim_end_matches('/'),
+                certificate.id.as_uuid()
+            ),
+            recipient: IdentityObject {
+                identity_type: "email".to_string(),
+                hashed: true,
+                identity: hash_identity(email, &salt),
+                salt,
+            },
+            badge: BadgeClass {
+                badge_type: "BadgeClass".to_string(),
+                id: achievement_id(certificate.product_id),
+                name: certificate.name.clone(),
+                description: certificate.description.clone(),
+                criteria: criteria_of(certificate),
+                issuer: Profile {
+                    profile_type: "Profile".to_string(),
+                    id: format!("urn:uuid:{}", authority.id.as_uuid()),
+                    name: authority.name.clone(),
+                    email: authority.email.as_string(),
+                },
+            },
+            verification: VerificationObject {
+                verification_type: "hosted".to_string(),
+            },
+            issued_on: certificate.created_date,
+            expires: certificate.validity.as_ref().and_then(|validity| {
+                match validity.valid_until {
+                    ValidUntil::EndOfTime => None,
+                    ValidUntil::Expiry(expiry) => Some(expiry),
+                }
+            }),
+            revoked: certificate.status == CertificateStatus::Revoked,
+            revocation_reason: certificate
+                .revocation
+                .as_ref()
+                .map(|revocation| revocation.reason.to_string()),
+        })
+    }
+}
+
+/// Open Badges 3.0 achievement credential
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenBadgeCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub credential_type: Vec<String>,
+    pub issuer: IssuerProfile,
+    pub name: String,
+    pub valid_from: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<DateTime<Utc>>,
+    pub credential_subject: AchievementSubject,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof: Option<DataIntegrityProof>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IssuerProfile {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub profile_type: Vec<String>,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AchievementSubject {
+    #[serde(rename = "type")]
+    pub subject_type: Vec<String>,
+    pub identifier: Vec<IdentifierEntry>,
+    pub achievement: Achievement,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentifierEntry {
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    pub identity_hash: String,
+    pub identity_type: String,
+    pub hashed: bool,
+    pub salt: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Achievement {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub achievement_type: Vec<String>,
+    pub name: String,
+    pub description: String,
+    pub criteria: Criteria,
+}
+
+impl OpenBadgeCredential {
+    /// Maps a certificate to an Open Badges 3.0 credential, with an `eddsa-jcs-2022`
+    /// proof when the issuer key is given
+    pub fn from_certificate(
+        certificate: &Certificate,
+        signing_key: Option<&SigningKey>,
+    ) -> Result<Self, CertificateDetailsMissingError> {
+        let (_, email, authority) = certificate.issued_details()?;
+        let salt = certificate.id.as_uuid().simple().to_string();
+        let validity = certificate.validity.as_ref();
+        let mut credential = OpenBadgeCredential {
+            context: vec![VC_CONTEXT.to_string(), OPEN_BADGES_V3_CONTEXT.to_string()],
+            id: format!("urn:uuid:{}", certificate.id.as_uuid()),
+            credential_type: vec![
+                "VerifiableCredential".to_string(),
+                "OpenBadgeCredential".to_string(),
+            ],
+            issuer: IssuerProfile {
+                id: match signing_key {
+                    Some(key) => did_key(&key.verifying_key()),
+                    None => format!("urn:uuid:{}", authority.id.as_uuid()),
+                },
+                profile_type: vec!["Profile".to_string()],
+                name: authority.name.clone(),
+            },
+            name: certificate.name.clone(),
+            valid_from: validity.map_or(certificate.created_date, |validity| validity.valid_from),
+            valid_until: validity.and_then(|validity| match validity.valid_until {
+                ValidUntil::EndOfTime => None,
+                ValidUntil::Expiry(expiry) => Some(expiry),
+            }),
+            credential_subject: AchievementSubject {
+                subject_type: vec!["AchievementSubject".to_string()],
+                identifier: vec![IdentifierEntry {
+                    entry_type: "IdentityObject".to_string(),
+                    identity_hash: hash_identity(email, &salt),
+                    identity_type: "emailAddress".to_string(),
+                    hashed: true,
+                    salt,
+                }],
+                achievement: Achievement {
+                    id: achievement_id(certificate.product_id),
+                    achievement_type: vec!["Achievement".to_string()],
+                    name: certificate.name.clone(),
+                    description: certificate.description.clone(),
+                    criteria: criteria_of(certificate),
+                },
+            },
+            proof: None,
+        };
+        if let Some(key) = signing_key {
+            let document =
+                serde_json::to_value(&credential).expect("credential is always serializable");
+            credential.proof = Some(create_proof(&document, key, Utc::now()));
+        }
+        Ok(credential)
+    }
+
+    /// Checks the Data Integrity proof against the issuer key
+    pub fn verify(&self, key: &VerifyingKey) -> bool {
+        let document = serde_json::to_value(self).expect("credential is always serializable");
+        verify_proof(&document, key)
+    }
+}
+
+fn accept_header(req: &HttpRequest) -> &str {
+    req.headers()
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .unwrap_or_default()
+}
+
+/// Indicates if the request asks for an Open Badges 2.0 assertion through its `Accept` header
+pub fn accepts_open_badge_assertion(req: &HttpRequest) -> bool {
+    accept_header(req).contains(OPEN_BADGES_V2_CONTENT_TYPE)
+}
+
+/// Indicates if the request asks for an Open Badges 3.0 credential through its `Accept` header
+pub fn accepts_open_badge_credential(req: &HttpRequest) -> bool {
+    let accept = accept_header(req);
+    accept.contains(VC_CONTENT_TYPE) && accept.contains(OPEN_BADGES_V3_PROFILE)
+}
+
+/// Builds the Open Badges 2.0 assertion of a certificate hosted by this service
+pub fn assertion_for_request(
+    certificate: &Certificate,
+    req: &HttpRequest,
+) -> Result<Assertion, CertificateDetailsMissingError> {
+    let connection_info = req.connection_info();
+    let base_url = format!("{}://{}", connection_info.scheme(), connection_info.host());
+    Assertion::from_certificate(certificate, &base_url)
+}
+
+/// Builds the Open Badges 3.0 credential of a certificate, signed when the app has issuer keys
+pub fn open_badge_credential_for_request(
+    certificate: &Certificate,
+    req: &HttpRequest,
+) -> Result<OpenBadgeCredential, CertificateDetailsMissingError> {
+    let keyring = req.app_data::<web::Data<Keyring>>();
+    let signing_key = keyring.and_then(|keyring| {
+        certificate
+            .authority_id()
+            .and_then(|authority_id| keyring.signing_key_for(authority_id))
+    });
+    OpenBadgeCredential::from_certificate(certificate, signing_key)
+}
+
+impl Responder for Assertion {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json_as(self, OPEN_BADGES_V2_CONTENT_TYPE)
+    }
+}
+
+impl Responder for OpenBadgeCredential {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json_as(self, VC_CONTENT_TYPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::SigningKey;
+    use pretty_assertions::assert_eq;
+    use sha2::{Digest, Sha256};
+    use uuid::Uuid;
+
+    use crate::test_helpers::certificate_for;
+
+    use super::{Assertion, OpenBadgeCredential};
+
+    #[test]
+    fn assertion_should_hash_recipient_email() {
+        let certificate = certificate_for(Uuid::new_v4());
+
+        let assertion =
+            Assertion::from_certificate(&certificate, "https://crs.example.com/").unwrap();
+        let salt = certificate.id.as_uuid().simple().to_string();
+        let expected = format!(
+            "sha256${:x}",
+            Sha256::digest(format!("john.doe@email.com{salt}"))
+        );
+
+        assert_eq!(assertion.recipient.identity, expected);
+        assert_eq!(
+            assertion.id,
+            format!(
+                "https://crs.example.com/api/certificates/{}/badge",
+                certificate.id.as_uuid()
+            )
+        );
+        let document = serde_json::to_string(&assertion).unwrap();
+        assert!(!document.contains("john.doe@email.com"));
+    }
+
+    #[test]
+    fn open_badge_credential_should_verify() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let certificate = certificate_for(Uuid::new_v4());
+
+        let credential = OpenBadgeCredential::from_certificate(&certificate, Some(&key)).unwrap();
+
+        assert_eq!(
+            credential.credential_type,
+            vec!["VerifiableCredential", "OpenBadgeCredential"]
+        );
+        assert!(credential.verify(&key.verifying_key()));
+    }
+}
@@ -0,0 +1,326 @@
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use actix_web::{
+    body::BoxBody,
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    HttpRequest, HttpResponse, Responder,
+};
+use log::info;
+use printpdf::{BuiltinFont, Mm, PdfDocument};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{certificate::Certificate, validity::ValidUntil};
+
+pub const PDF_CONTENT_TYPE: &str = "application/pdf";
+
+/// Millimeters per typographic point
+const MM_PER_PT: f32 = 0.3528;
+/// Average glyph width of the built-in Helvetica fonts, relative to the font size
+const AVERAGE_GLYPH_WIDTH: f32 = 0.5;
+
+/// Layout of a certificate PDF.
+///
+/// Texts can contain the placeholders `{recipient_name}`, `{certificate_name}`,
+/// `{description}`, `{issuer_name}`, `{score}`, `{max_score}`, `{issued_on}`,
+/// `{valid_from}`, `{valid_until}` and `{certificate_id}`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PdfTemplate {
+    pub page_width_mm: f32,
+    pub page_height_mm: f32,
+    pub elements: Vec<TextElement>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TextElement {
+    pub text: String,
+    pub x_mm: f32,
+    pub y_mm: f32,
+    pub font_size: f32,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub align: Align,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+}
+
+impl Default for PdfTemplate {
+    /// Landscape A4 layout used for products without a template of their own
+    fn default() -> Self {
+        let centered = |text: &str, y_mm: f32, font_size: f32, bold: bool| TextElement {
+            text: text.to_string(),
+            x_mm: 148.5,
+            y_mm,
+            font_size,
+            bold,
+            align: Align::Center,
+        };
+        PdfTemplate {
+            page_width_mm: 297.0,
+            page_height_mm: 210.0,
+            elements: vec![
+                centered("Certificate of Achievement", 165.0, 32.0, true),
+                centered("This certifies that", 140.0, 14.0, false),
+                centered("{recipient_name}", 120.0, 26.0, true),
+                centered("has successfully completed", 100.0, 14.0, false),
+                centered("{certificate_name}", 85.0, 20.0, true),
+                centered("Score: {score} / {max_score}", 68.0, 12.0, false),
+                centered(
+                    "Valid from {valid_from} until {valid_until}",
+                    58.0,
+                    12.0,
+                    false,
+                ),
+                centered("Issued by {issuer_name} on {issued_on}", 40.0, 12.0, false),
+                centered("Certificate id: {certificate_id}", 20.0, 8.0, false),
+            ],
+        }
+    }
+}
+
+/// Certificate PDF templates, looked up by product id
+#[derive(Default)]
+pub struct PdfTemplates {
+    default_template: PdfTemplate,
+    product_templates: HashMap<u32, PdfTemplate>,
+}
+
+#[derive(Debug)]
+pub struct PdfError(pub String);
+
+impl Error for PdfError {}
+
+impl std::fmt::Display for PdfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unable to render certificate pdf: {}", self.0)
+    }
+}
+
+impl PdfTemplates {
+    pub fn add_product_template(&mut self, product_id: u32, template: PdfTemplate) {
+        self.product_templates.insert(product_id, template);
+    }
+
+    /// Template of the given product, falling back to the default template
+    pub fn template_for(&self, product_id: u32) -> &PdfTemplate {
+        self.product_templates
+            .get(&product_id)
+            .unwrap_or(&self.default_template)
+    }
+
+    /// Loads templates from a directory holding one `<product id>.json` file per product.
+    ///
+    /// A `default.json` file replaces the built-in default template.
+    pub fn load_dir(dir: &Path) -> Result<PdfTemplates, PdfError> {
+        let mut templates = PdfTemplates::default();
+        let entries = fs::read_dir(dir).map_err(|err| PdfError(err.to_string()))?;
+        for entry in entries {
+            let path = entry.map_err(|err| PdfError(err.to_string()))?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let stem = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("");
+            let content = fs::read_to_string(&path).map_err(|err| PdfError(err.to_string()))?;
+            let template: PdfTemplate = serde_json::from_str(&content)
+                .map_err(|err| PdfError(format!("{}: {}", path.display(), err)))?;
+            if stem == "default" {
+                templates.default_template = template;
+            } else {
+                let product_id = stem
+                    .parse()
+                    .map_err(|_| PdfError(format!("{} is not a product id", stem)))?;
+                templates.add_product_template(product_id, template);
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Loads templates from the directory in `CRS_PDF_TEMPLATES_DIR`, if set
+    pub fn from_env() -> Result<Option<PdfTemplates>, PdfError> {
+        match dotenvy::var("CRS_PDF_TEMPLATES_DIR") {
+            Ok(dir) => {
+                info!("Loading certificate pdf templates from {}", dir);
+                PdfTemplates::load_dir(Path::new(&dir)).map(Some)
+            }
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Replaces the template placeholders with the certificate values
+pub fn fill_placeholders(text: &str, certificate: &Certificate) -> String {
+    let date = |date: chrono::DateTime<chrono::Utc>| date.format("%Y-%m-%d").to_string();
+    let score = certificate.assessment.score.as_ref();
+    let validity = certificate.validity.as_ref();
+    [
+        (
+            "{recipient_name}",
+            certificate
+                .recipient
+                .name
+                .as_ref()
+                .map_or("-".to_string(), |name| name.to_string()),
+        ),
+        ("{certificate_name}", certificate.name.clone()),
+        ("{description}", certificate.description.clone()),
+        (
+            "{issuer_name}",
+            certificate
+                .authority
+                .as_ref()
+                .map_or("-".to_string(), |authority| authority.name.clone()),
+        ),
+        (
+            "{score}",
+            score.map_or("-".to_string(), |score| score.value.to_string()),
+        ),
+        (
+            "{max_score}",
+            score.map_or("-".to_string(), |score| score.max.to_string()),
+        ),
+        ("{issued_on}", date(certificate.created_date)),
+        (
+            "{valid_from}",
+            date(validity.map_or(certificate.created_date, |validity| validity.valid_from)),
+        ),
+        (
+            "{valid_until}",
+            match validity.map(|validity| &validity.valid_until) {
+                Some(ValidUntil::Expiry(expiry)) => date(*expiry),
+                _ => "no expiry".to_string(),
+            },
+        ),
+        ("{certificate_id}", certificate.id.as_uuid().to_string()),
+    ]
+    .iter()
+    .fold(text.to_string(), |text, (placeholder, value)| {
+        text.replace(placeholder, value)
+    })
+}
+
+/// Rendered certificate PDF
+pub struct CertificatePdf {
+    pub file_name: String,
+    pub bytes: Vec<u8>,
+}
+
+impl CertificatePdf {
+    /// Renders the certificate into a PDF using the given template
+    pub fn render(certificate: &Certificate, template: &PdfTemplate) -> Result<Self, PdfError> {
+        let (doc, page, layer) = PdfDocument::new(
+            certificate.name.as_str(),
+            Mm(template.page_width_mm),
+            Mm(template.page_height_mm),
+            "Certificate",
+        );
+        let regular = doc
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|err| PdfError(err.to_string()))?;
+        let bold = doc
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|err| PdfError(err.to_string()))?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        for element in &template.elements {
+            let text = fill_placeholders(&element.text, certificate);
+            let x_mm = match element.align {
+                Align::Left => element.x_mm,
+                // Built-in fonts carry no metrics here, so centering uses the average glyph width
+                Align::Center => {
+                    let width_mm = text.chars().count() as f32
+                        * element.font_size
+                        * AVERAGE_GLYPH_WIDTH
+                        * MM_PER_PT;
+                    (element.x_mm - width_mm / 2.0).max(0.0)
+                }
+            };
+            let font = if element.bold { &bold } else { &regular };
+            layer.use_text(text, element.font_size, Mm(x_mm), Mm(element.y_mm), font);
+        }
+
+        Ok(CertificatePdf {
+            file_name: format!("certificate-{}.pdf", certificate.id.as_uuid()),
+            bytes: doc
+                .save_to_bytes()
+                .map_err(|err| PdfError(err.to_string()))?,
+        })
+    }
+}
+
+impl Responder for CertificatePdf {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        HttpResponse::Ok()
+            .content_type(PDF_CONTENT_TYPE)
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(self.file_name)],
+            })
+            .body(self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use crate::test_helpers::certificate_for;
+
+    use super::{fill_placeholders, CertificatePdf, PdfTemplate, PdfTemplates};
+
+    #[test]
+    fn fill_placeholders_should_use_certificate_values() {
+        let mut certificate = certificate_for(Uuid::new_v4());
+        certificate.name = "Rust fundamentals".to_string();
+
+        let text = fill_placeholders(
+            "{recipient_name} completed {certificate_name} with {score}/{max_score}",
+            &certificate,
+        );
+
+        assert_eq!(text, "John Doe completed Rust fundamentals with 100/100");
+    }
+
+    #[test]
+    fn render_should_produce_pdf() {
+        let certificate = certificate_for(Uuid::new_v4());
+
+        let pdf = CertificatePdf::render(&certificate, &PdfTemplate::default()).unwrap();
+
+        assert!(pdf.bytes.starts_with(b"%PDF"));
+        assert!(pdf.file_name.ends_with(".pdf"));
+    }
+
+    #[test]
+    fn load_dir_should_read_product_templates() {
+        let dir = std::env::temp_dir().join(format!("crs-templates-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let template = PdfTemplate {
+            page_width_mm: 210.0,
+            ..PdfTemplate::default()
+        };
+        fs::write(
+            dir.join("15.json"),
+            serde_json::to_string(&template).unwrap(),
+        )
+        .unwrap();
+
+        let templates = PdfTemplates::load_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(templates.template_for(15).page_width_mm, 210.0);
+        assert_eq!(templates.template_for(16).page_width_mm, 297.0);
+    }
+}
@@ -0,0 +1,295 @@
+use actix_web::{body::BoxBody, http::header::ACCEPT, web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, SecondsFormat, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    domain::{
+        base::AssessmentResult, certificate::Certificate, error::CertificateDetailsMissingError,
+        validity::ValidUntil,
+    },
+    helpers::respond_with_json_as,
+    signing::Keyring,
+};
+
+use super::canonical_json;
+
+pub const VC_CONTENT_TYPE: &str = "application/vc+ld+json";
+pub const VC_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+pub const PROOF_CRYPTOSUITE: &str = "eddsa-jcs-2022";
+
+/// Multicodec prefix of an Ed25519 public key
+const ED25519_PUB_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+/// Certificate expressed in the W3C Verifiable Credentials Data Model 2.0
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiableCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub credential_type: Vec<String>,
+    pub issuer: Issuer,
+    pub name: String,
+    pub description: String,
+    pub valid_from: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<DateTime<Utc>>,
+    pub credential_subject: CredentialSubject,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof: Option<DataIntegrityProof>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Issuer {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialSubject {
+    pub id: String,
+    pub name: String,
+    pub assessment: AssessmentClaim,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssessmentClaim {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_score: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passing_score: Option<u32>,
+    pub progress: f32,
+    pub result: AssessmentResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DataIntegrityProof {
+    #[serde(rename = "type")]
+    pub proof_type: String,
+    pub cryptosuite: String,
+    pub created: String,
+    pub verification_method: String,
+    pub proof_purpose: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_value: Option<String>,
+}
+
+/// `did:key` identifier of an Ed25519 public key
+pub fn did_key(key: &VerifyingKey) -> String {
+    let mut bytes = ED25519_PUB_MULTICODEC.to_vec();
+    bytes.extend_from_slice(key.as_bytes());
+    format!("did:key:z{}", bs58::encode(bytes).into_string())
+}
+
+impl VerifiableCredential {
+    /// Maps a certificate to a verifiable credential.
+    ///
+    /// When the issuer key is given the issuer is identified by its `did:key` and the
+    /// credential carries an `eddsa-jcs-2022` Data Integrity proof.
+    pub fn from_certificate(
+        certificate: &Certificate,
+        signing_key: Option<&SigningKey>,
+    ) -> Result<Self, CertificateDetailsMissingError> {
+        let (recipient_name, _, authority) = certificate.issued_details()?;
+        let validity = certificate.validity.as_ref();
+        let score = certificate.assessment.score.as_ref();
+        let mut credential = VerifiableCredential {
+            context: vec![VC_CONTEXT.to_string()],
+            id: format!("urn:uuid:{}", certificate.id.as_uuid()),
+            credential_type: vec![
+                "VerifiableCredential".to_string(),
+                "CertificateCredential".to_string(),
+            ],
+            issuer: Issuer {
+                id: match signing_key {
+                    Some(key) => did_key(&key.verifying_key()),
+                    None => format!("urn:uuid:{}", authority.id.as_uuid()),
+                },
+                name: authority.name.clone(),
+            },
+            name: certificate.name.clone(),
+            description: certificate.description.clone(),
+            valid_from: validity.map_or(certificate.created_date, |validity| validity.valid_from),
+            valid_until: validity.and_then(|validity| match validity.valid_until {
+                ValidUntil::EndOfTime => None,
+                ValidUntil::Expiry(expiry) => Some(expiry),
+            }),
+            credential_subject: CredentialSubject {
+                id: format!("urn:uuid:{}", certificate.recipient.id.as_uuid()),
+                name: recipient_name.to_string(),
+                assessment: AssessmentClaim {
+                    score: score.map(|score| score.value),
+                    max_score: score.map(|score| score.max),
+                    passing_score: score.map(|score| score.passing_score),
+                    progress: certificate.assessment.progress,
+                    result: certificate.assessment.result.clone(),
+                },
+            },
+            proof: None,
+        };
+        if let Some(key) = signing_key {
+            credential.sign(key, Utc::now());
+        }
+        Ok(credential)
+    }
+
+    /// Adds a Data Integrity proof created with the issuer key
+    pub fn sign(&mut self, key: &SigningKey, created: DateTime<Utc>) {
+        self.proof = None;
+        let document = serde_json::to_value(&*self).expect("credential is always serializable");
+        self.proof = Some(create_proof(&document, key, created));
+    }
+
+    /// Checks the Data Integrity proof against the issuer key
+    pub fn verify(&self, key: &VerifyingKey) -> bool {
+        let document = serde_json::to_value(self).expect("credential is always serializable");
+        verify_proof(&document, key)
+    }
+}
+
+/// Hash of the proof options and the unsecured document as defined by `eddsa-jcs-2022`
+fn hash_data(document: &Value, proof: &DataIntegrityProof) -> Vec<u8> {
+    let mut document = document.clone();
+    let mut context = Value::Null;
+    if let Value::Object(members) = &mut document {
+        members.remove("proof");
+        context = members.get("@context").cloned().unwrap_or(Value::Null);
+    }
+    let mut proof_config = serde_json::to_value(DataIntegrityProof {
+        proof_value: None,
+        ..proof.clone()
+    })
+    .expect("proof is always serializable");
+    if let Value::Object(members) = &mut proof_config {
+        members.insert("@context".to_string(), context);
+    }
+
+    let mut hash_data = Sha256::digest(canonical_json(&proof_config)).to_vec();
+    hash_data.extend(Sha256::digest(canonical_json(&document)));
+    hash_data
+}
+
+/// Creates an `eddsa-jcs-2022` Data Integrity proof over a JSON-LD document
+pub fn create_proof(
+    document: &Value,
+    key: &SigningKey,
+    created: DateTime<Utc>,
+) -> DataIntegrityProof {
+    let did = did_key(&key.verifying_key());
+    let mut proof = DataIntegrityProof {
+        proof_type: "DataIntegrityProof".to_string(),
+        cryptosuite: PROOF_CRYPTOSUITE.to_string(),
+        created: created.to_rfc3339_opts(SecondsFormat::Secs, true),
+        verification_method: format!("{}#{}", did, did.trim_start_matches("did:key:")),
+        proof_purpose: "assertionMethod".to_string(),
+        proof_value: None,
+    };
+    let signature = key.sign(&hash_data(document, &proof));
+    proof.proof_value = Some(format!(
+        "z{}",
+        bs58::encode(signature.to_bytes()).into_string()
+    ));
+    proof
+}
+
+/// Checks the `eddsa-jcs-2022` proof embedded in a JSON-LD document
+pub fn verify_proof(document: &Value, key: &VerifyingKey) -> bool {
+    let proof: DataIntegrityProof = match document
+        .get("proof")
+        .and_then(|proof| serde_json::from_value(proof.clone()).ok())
+    {
+        Some(proof) => proof,
+        None => return false,
+    };
+    let signature = proof
+        .proof_value
+        .as_deref()
+        .and_then(|value| value.strip_prefix('z'))
+        .and_then(|value| bs58::decode(value).into_vec().ok())
+        .and_then(|bytes| Signature::from_slice(&bytes).ok());
+    match signature {
+        Some(signature) => key.verify(&hash_data(document, &proof), &signature).is_ok(),
+        None => false,
+    }
+}
+
+/// Indicates if the request asks for a verifiable credential through its `Accept` header
+pub fn accepts_verifiable_credential(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains(VC_CONTENT_TYPE))
+}
+
+/// Builds the verifiable credential of a certificate, signed when the app has issuer keys
+pub fn credential_for_request(
+    certificate: &Certificate,
+    req: &HttpRequest,
+) -> Result<VerifiableCredential, CertificateDetailsMissingError> {
+    let keyring = req.app_data::<web::Data<Keyring>>();
+    let signing_key = keyring.and_then(|keyring| {
+        certificate
+            .authority_id()
+            .and_then(|authority_id| keyring.signing_key_for(authority_id))
+    });
+    VerifiableCredential::from_certificate(certificate, signing_key)
+}
+
+impl Responder for VerifiableCredential {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json_as(self, VC_CONTENT_TYPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::SigningKey;
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use crate::test_helpers::certificate_for;
+
+    use super::VerifiableCredential;
+
+    #[test]
+    fn credential_should_map_certificate() {
+        let user_id = Uuid::new_v4();
+        let certificate = certificate_for(user_id);
+
+        let credential = VerifiableCredential::from_certificate(&certificate, None).unwrap();
+        let document = serde_json::to_value(&credential).unwrap();
+
+        assert_eq!(
+            document["credentialSubject"]["id"],
+            format!("urn:uuid:{user_id}")
+        );
+        assert_eq!(document["credentialSubject"]["name"], "John Doe");
+        assert!(document.get("proof").is_none());
+        assert!(!document.to_string().contains("john.doe@email.com"));
+    }
+
+    #[test]
+    fn signed_credential_should_verify() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let certificate = certificate_for(Uuid::new_v4());
+
+        let mut credential =
+            VerifiableCredential::from_certificate(&certificate, Some(&key)).unwrap();
+        assert!(credential.issuer.id.starts_with("did:key:z6Mk"));
+        assert!(credential.verify(&key.verifying_key()));
+
+        credential.credential_subject.name = "Jane Doe".to_string();
+        assert!(!credential.verify(&key.verifying_key()));
+    }
+}
@@ -0,0 +1,227 @@
+use actix_web::web;
+use log::info;
+use uuid::Uuid;
+
+use crate::{
+    audit::Auditor,
+    domain::{
+        accreditation::{Accreditation, AccreditationStatus},
+        audit::{snapshot, AuditAction},
+        certificate::Certificate,
+        error::MissingAccreditationError,
+    },
+    dto::{
+        certificate_metadata_dto::{AccreditationDto, AccreditationStatusDto},
+        validation::Violations,
+    },
+    error::CrsError,
+    repository::CertificateRepository,
+    signing::{sign_changed, Keyring},
+    tenant::Tenant,
+};
+
+use super::get_certificate::find_certificate;
+
+/// Stores the changed certificate, unless it was changed concurrently
+async fn save(
+    repository: &web::Data<dyn CertificateRepository>,
+    certificate: &Certificate,
+    expected_version: u32,
+) -> Result<(), CrsError> {
+    if !repository.update(certificate, expected_version).await? {
+        return Err(CrsError::Conflict(
+            "certificate was modified concurrently, retry".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn by_id(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn CertificateRepository>,
+) -> Result<Accreditation, CrsError> {
+    let certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    certificate
+        .accreditation
+        .ok_or_else(|| CrsError::NotFound(MissingAccreditationError.to_string()))
+}
+
+/// Attaches an accreditation granted after the certificate was issued
+pub async fn create(
+    tenant: Tenant,
+    auditor: Auditor,
+    path: web::Path<(Uuid,)>,
+    accreditation: web::Json<AccreditationDto>,
+    repository: web::Data<dyn CertificateRepository>,
+    keyring: Option<web::Data<Keyring>>,
+) -> Result<Certificate, CrsError> {
+    let mut violations = Violations::default();
+    accreditation.validate("", &mut violations);
+    violations.into_result("invalid accreditation")?;
+    let accreditation = Accreditation::try_from(accreditation.into_inner())?;
+
+    let mut certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    let expected_version = certificate.version;
+    let before = snapshot(&certificate);
+    certificate.accredit(accreditation)?;
+    // the signature covers the accreditation, so it must follow the change
+    sign_changed(
+        keyring.as_ref().map(|keyring| keyring.get_ref()),
+        &mut certificate,
+    );
+    save(&repository, &certificate, expected_version).await?;
+    auditor
+        .record(AuditAction::Accredit, Some(before), &certificate)
+        .await;
+    info!("Accredited certificate: {}", certificate.id.as_uuid());
+    Ok(certificate)
+}
+
+/// Moves the accreditation of a certificate along its lifecycle
+pub async fn change_status(
+    tenant: Tenant,
+    auditor: Auditor,
+    path: web::Path<(Uuid,)>,
+    change: web::Json<AccreditationStatusDto>,
+    repository: web::Data<dyn CertificateRepository>,
+    keyring: Option<web::Data<Keyring>>,
+) -> Result<Certificate, CrsError> {
+    let change = change.into_inner();
+    let status = AccreditationStatus::from_status_str(&change.status)?;
+
+    let mut certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    let expected_version = certificate.version;
+    let before = snapshot(&certificate);
+    certificate.change_accreditation_status(status, change.comment)?;
+    sign_changed(
+        keyring.as_ref().map(|keyring| keyring.get_ref()),
+        &mut certificate,
+    );
+    save(&repository, &certificate, expected_version).await?;
+    auditor
+        .record(
+            AuditAction::ChangeAccreditationStatus,
+            Some(before),
+            &certificate,
+        )
+        .await;
+    info!(
+        "Changed accreditation status of certificate: {}",
+        certificate.id.as_uuid()
+    );
+    Ok(certificate)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, web, App};
+    use chrono::Utc;
+    use ed25519_dalek::SigningKey;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        repository::{in_memory::InMemoryCertificateRepository, CertificateRepository},
+        signing::Keyring,
+        test_helpers::{audited, authenticated, certificate_for, TEST_API_KEY},
+    };
+
+    #[actix_web::test]
+    async fn accreditation_should_follow_its_lifecycle() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let keyring = Keyring::with_default_key(SigningKey::from_bytes(&[7u8; 32]));
+        let mut certificate = certificate_for(Uuid::new_v4());
+        keyring.sign(&mut certificate);
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .app_data(web::Data::new(keyring))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+        let uri = format!("/api/certificates/{certificate_id}/accreditation");
+
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::post()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({
+                "name": "ISO 9001",
+                "institution": "ISO",
+                "start_date": Utc::now(),
+                "status": "pending"
+            }))
+            .to_request();
+        let accredited: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(accredited["accreditation"]["status"], "Pending");
+        assert_eq!(
+            accredited["accreditation"]["status_at_issuance"],
+            serde_json::Value::Null
+        );
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{uri}/status"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"status": "expired"}))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        for status in ["active", "revoked"] {
+            let req = test::TestRequest::post()
+                .uri(&format!("{uri}/status"))
+                .insert_header((API_KEY_HEADER, TEST_API_KEY))
+                .set_json(json!({"status": status, "comment": "audit"}))
+                .to_request();
+            assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        }
+
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let accreditation: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(accreditation["status"], "Revoked");
+        let history: Vec<(&str, &str)> = accreditation["history"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|change| {
+                (
+                    change["from"].as_str().unwrap(),
+                    change["to"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(history, vec![("Pending", "Active"), ("Active", "Revoked")]);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/verify/{certificate_id}"))
+            .to_request();
+        let verification: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(verification["accreditation"], "Revoked");
+        assert_eq!(verification["signature"], "Valid");
+    }
+}
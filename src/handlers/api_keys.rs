@@ -0,0 +1,228 @@
+use actix_web::{web, HttpResponse};
+use log::info;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        api_key::{ApiKey, ApiKeys, IssuedApiKey},
+        base::Id,
+    },
+    dto::api_key_dto::ApiKeyDto,
+    error::CrsError,
+    repository::ApiKeyRepository,
+    tenant::Tenant,
+};
+
+fn not_found() -> CrsError {
+    CrsError::NotFound("API key not found".to_string())
+}
+
+/// Mints a key of the tenant's account, its secret is only part of this response
+pub async fn create(
+    tenant: Tenant,
+    key: web::Json<ApiKeyDto>,
+    repository: web::Data<dyn ApiKeyRepository>,
+) -> Result<IssuedApiKey, CrsError> {
+    key.validate(tenant.account_id())?;
+    let (key, secret) = ApiKey::mint(Id::parse(Uuid::new_v4())?, key.into_inner())?;
+    repository.insert(&key).await?;
+    info!("Minted API key: {}", key.id.as_uuid());
+    Ok(IssuedApiKey { key, secret })
+}
+
+pub async fn index(
+    tenant: Tenant,
+    repository: web::Data<dyn ApiKeyRepository>,
+) -> Result<ApiKeys, CrsError> {
+    Ok(ApiKeys(repository.find_all(tenant.account_id()).await?))
+}
+
+/// Replaces the secret of a key, the previous secret stops working at once
+pub async fn rotate(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn ApiKeyRepository>,
+) -> Result<IssuedApiKey, CrsError> {
+    let key_id = Id::parse(path.into_inner().0)?;
+    let mut key = repository
+        .find_by_id(tenant.account_id(), key_id.as_uuid())
+        .await?
+        .ok_or_else(not_found)?;
+    let secret = key.rotate();
+    if !repository.update(&key).await? {
+        return Err(not_found());
+    }
+    info!("Rotated API key: {}", key.id.as_uuid());
+    Ok(IssuedApiKey { key, secret })
+}
+
+pub async fn delete(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn ApiKeyRepository>,
+) -> Result<HttpResponse, CrsError> {
+    let key_id = Id::parse(path.into_inner().0)?;
+    if !repository
+        .delete(tenant.account_id(), key_id.as_uuid())
+        .await?
+    {
+        return Err(not_found());
+    }
+    info!("Deleted API key: {}", key_id.as_uuid());
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, App};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        repository::Repositories,
+        test_helpers::{authenticated, OTHER_ACCOUNT_API_KEY, TEST_API_KEY},
+    };
+
+    #[actix_web::test]
+    async fn api_keys_should_only_grant_their_scopes() {
+        let repositories = Repositories::in_memory();
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/certificates")
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        let req = test::TestRequest::post()
+            .uri("/api/keys")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"name": "support", "scopes": ["certificates:read"]}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"][0]["field"], "account_id");
+
+        let req = test::TestRequest::post()
+            .uri("/api/keys")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"name": "support", "account_id": 20, "scopes": ["certificates:read"]}))
+            .to_request();
+        let key: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let secret = key["secret"].as_str().unwrap().to_string();
+        assert_eq!(key["scopes"], json!(["certificates:read"]));
+        assert_eq!(key.get("secret_hash"), None);
+
+        let req = test::TestRequest::get()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, secret.as_str()))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri("/api/certificates/00000000-0000-0000-0000-000000000001/revoke")
+            .insert_header((API_KEY_HEADER, secret.as_str()))
+            .set_json(json!({"reason": "fraud"}))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::FORBIDDEN
+        );
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/keys/{}/rotate", key["id"].as_str().unwrap()))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let rotated: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let req = test::TestRequest::get()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, secret.as_str()))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+        let req = test::TestRequest::get()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, rotated["secret"].as_str().unwrap()))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn api_keys_of_other_accounts_should_not_be_managed() {
+        let repositories = Repositories::in_memory();
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/keys")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"name": "support", "account_id": 20, "scopes": ["certificates:read"]}))
+            .to_request();
+        let key: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let key_id = key["id"].as_str().unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/keys")
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .set_json(
+                json!({"name": "intruder", "account_id": 20, "scopes": ["certificates:read"]}),
+            )
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"][0]["field"], "account_id");
+        assert_eq!(problem["errors"][0]["rule"], "tenant");
+
+        let req = test::TestRequest::get()
+            .uri("/api/keys")
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        let keys: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert!(keys
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|key| key["account_id"] == 99));
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/keys/{key_id}/rotate"))
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/keys/{key_id}"))
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::get()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, key["secret"].as_str().unwrap()))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+}
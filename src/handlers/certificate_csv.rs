@@ -0,0 +1,222 @@
+use actix_multipart::Multipart;
+use actix_web::{
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    web, HttpResponse,
+};
+use futures::{stream, TryStreamExt};
+use log::error;
+
+use crate::{
+    audit::Auditor,
+    batch::{BatchConfig, BatchReport, BATCH_PAYLOAD_LIMIT},
+    dto::certificate_query_dto::CertificateQueryDto,
+    error::CrsError,
+    export::certificate_csv::{read_certificates, write_certificates, CSV_CONTENT_TYPE},
+    repository::{
+        query::{CertificateQuery, MAX_PAGE_SIZE},
+        CertificateRepository, OrganizationRepository, ProductRepository, RecipientRepository,
+    },
+    signing::Keyring,
+    tenant::Tenant,
+};
+
+use super::store_certificate::Issuer;
+
+/// Reads the first file of a multipart upload
+async fn read_upload(mut payload: Multipart) -> Result<Vec<u8>, CrsError> {
+    let malformed = |err: actix_multipart::MultipartError| CrsError::BadRequest(err.to_string());
+    let mut field = payload
+        .try_next()
+        .await
+        .map_err(malformed)?
+        .ok_or_else(|| CrsError::BadRequest("no csv file was uploaded".to_string()))?;
+
+    let mut content = Vec::new();
+    while let Some(chunk) = field.try_next().await.map_err(malformed)? {
+        if content.len() + chunk.len() > BATCH_PAYLOAD_LIMIT {
+            return Err(CrsError::PayloadTooLarge(format!(
+                "csv uploads are limited to {} bytes",
+                BATCH_PAYLOAD_LIMIT
+            )));
+        }
+        content.extend_from_slice(&chunk);
+    }
+    Ok(content)
+}
+
+/// Issues a certificate for every valid row of an uploaded CSV file
+#[allow(clippy::too_many_arguments)]
+pub async fn import(
+    tenant: Tenant,
+    auditor: Auditor,
+    payload: Multipart,
+    certificates: web::Data<dyn CertificateRepository>,
+    organizations: web::Data<dyn OrganizationRepository>,
+    recipients: web::Data<dyn RecipientRepository>,
+    products: web::Data<dyn ProductRepository>,
+    keyring: Option<web::Data<Keyring>>,
+    config: Option<web::Data<BatchConfig>>,
+) -> Result<BatchReport, CrsError> {
+    let content = read_upload(payload).await?;
+    let rows = read_certificates(content.as_slice());
+    BatchConfig::or_default(config.as_ref().map(|config| config.get_ref()))
+        .ensure_fits(rows.len())?;
+
+    let rows = rows
+        .into_iter()
+        .map(|row| (row.line as usize, row.certificate));
+    Issuer::new(
+        tenant,
+        &auditor,
+        &certificates,
+        &organizations,
+        &recipients,
+        &products,
+        keyring.as_ref(),
+    )
+    .issue_all(rows)
+    .await
+}
+
+/// Streams every certificate matching the listing filters as CSV, one page at a time
+pub async fn export(
+    tenant: Tenant,
+    query: web::Query<CertificateQueryDto>,
+    repository: web::Data<dyn CertificateRepository>,
+) -> Result<HttpResponse, CrsError> {
+    if let Some(account_id) = query.account_id {
+        tenant.ensure_account(account_id)?;
+    }
+    let mut query = CertificateQuery::try_from(query.into_inner())?;
+    tenant.restrict(&mut query)?;
+    query.limit = MAX_PAGE_SIZE;
+
+    // the state is the query of the next page, and whether it is the first one
+    let pages = stream::try_unfold((Some(query), true), move |(query, first)| {
+        let repository = repository.clone();
+        async move {
+            let Some(mut query) = query else {
+                return Ok(None);
+            };
+            let page = repository
+                .find_page(tenant.account_id(), &query)
+                .await
+                .map_err(|err| {
+                    error!("Certificate export aborted: {}", err);
+                    actix_web::error::ErrorInternalServerError("certificate export failed")
+                })?;
+            let csv = write_certificates(&page.certificates, first)
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+            let next = page.next.map(|cursor| {
+                query.after = Some(cursor);
+                query
+            });
+            Ok::<_, actix_web::Error>(Some((web::Bytes::from(csv), (next, false))))
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type(CSV_CONTENT_TYPE)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename("certificates.csv".to_string())],
+        })
+        .streaming(pages))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        repository::{in_memory::InMemoryCertificateRepository, CertificateRepository},
+        test_helpers::{
+            authenticated, certificate_for, repositories_with_organization, TEST_API_KEY,
+        },
+    };
+
+    #[actix_web::test]
+    async fn export_should_stream_every_page() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificates: Vec<_> = (0..150).map(|_| certificate_for(Uuid::new_v4())).collect();
+        repository.insert_many(&certificates).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/certificates/csv?account_id=20")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = test::read_body(resp).await;
+        let csv = std::str::from_utf8(&body).unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 151);
+        assert!(lines[0].starts_with("certificate_id,account_id"));
+    }
+
+    #[actix_web::test]
+    async fn import_should_issue_valid_rows_and_report_invalid_ones() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let user_id = Uuid::new_v4();
+        let csv = format!(
+            "account_id,product_id,organization_id,recipient_id,first_name,last_name,email,phone,score,progress\n\
+             20,15,{organization_id},{user_id},Jane,Doe,jane@email.com,,90,1.0\n\
+             20,15,{organization_id},{user_id},Jane,Doe,jane@email.com,,190,1.0\n"
+        );
+        let body = format!(
+            "--boundary\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"certificates.csv\"\r\n\
+             Content-Type: text/csv\r\n\r\n\
+             {csv}\r\n\
+             --boundary--\r\n"
+        );
+        let req = test::TestRequest::post()
+            .uri("/api/certificates/csv")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((
+                header::CONTENT_TYPE,
+                "multipart/form-data; boundary=boundary",
+            ))
+            .set_payload(body)
+            .to_request();
+        let report: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(report["created"], 1);
+        assert_eq!(report["rejected"], 1);
+        assert_eq!(report["items"][1]["index"], 3);
+        assert_eq!(report["items"][1]["errors"][0]["field"], "metadata.score");
+        let stored = repositories
+            .certificates
+            .find_by_user_id(20, user_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+    }
+}
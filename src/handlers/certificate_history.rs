@@ -0,0 +1,134 @@
+use actix_web::web;
+use uuid::Uuid;
+
+use crate::{
+    domain::audit::AuditTrail,
+    error::CrsError,
+    repository::{AuditRepository, CertificateRepository},
+    tenant::Tenant,
+};
+
+use super::get_certificate::find_certificate;
+
+/// Serves the recorded changes of a certificate of the tenant, and whether their
+/// hash chain is intact
+pub async fn index(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    certificates: web::Data<dyn CertificateRepository>,
+    audit: web::Data<dyn AuditRepository>,
+) -> Result<AuditTrail, CrsError> {
+    let certificate = find_certificate(&certificates, tenant, path.into_inner().0).await?;
+    let certificate_id = certificate.id.as_uuid();
+    let entries = audit.find_by_certificate_id(certificate_id).await?;
+    Ok(AuditTrail::new(certificate_id, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::{header, StatusCode},
+        test, App,
+    };
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        domain::audit::{AuditAction, AuditEntry},
+        test_helpers::{
+            authenticated, repositories_with_organization, OTHER_ACCOUNT_API_KEY, TEST_API_KEY,
+        },
+    };
+
+    #[actix_web::test]
+    async fn history_should_chain_every_change() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({
+                "account_id": 20,
+                "product_id": 15,
+                "organization_id": organization_id,
+                "recipient": {"id": Uuid::new_v4(), "first_name": "John", "last_name": "Doe", "email": "john.doe@email.com", "phone": "12345678"},
+                "metadata": {"score": 100, "progress": 1.0}
+            }))
+            .to_request();
+        let issued: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let certificate_id = issued["id"].as_str().unwrap().to_string();
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((header::IF_MATCH, "\"1\""))
+            .set_json(json!({"description": "Revised"}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{certificate_id}/revoke"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"reason": "issued_in_error"}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let uri = format!("/api/certificates/{certificate_id}/history");
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let history: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(history["intact"], true);
+        let entries = history["entries"].as_array().unwrap();
+        let actions: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry["action"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, ["issue", "update", "revoke"]);
+        assert!(entries[0]["actor"].as_str().unwrap().starts_with("key:"));
+        assert_eq!(entries[0]["before"], serde_json::Value::Null);
+        assert_eq!(entries[1]["after"]["description"], "Revised");
+        assert_eq!(entries[2]["before"]["status"], "Active");
+        assert_eq!(entries[2]["after"]["status"], "Revoked");
+        assert_eq!(entries[2]["previous_hash"], entries[1]["hash"]);
+
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        // an entry forged without knowing the chain does not link to the last one
+        let certificate = repositories
+            .certificates
+            .find_by_id(20, certificate_id.parse().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        let forged = AuditEntry::chain(None, AuditAction::Update, "key:forged", None, &certificate);
+        let forged = AuditEntry {
+            sequence: 4,
+            ..forged
+        };
+        assert!(repositories.audit.append(&forged).await.unwrap());
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let history: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(history["intact"], false);
+    }
+}
@@ -0,0 +1,31 @@
+use actix_web::web;
+use chrono::{SubsecRound, Utc};
+use log::info;
+
+use crate::{
+    audit::Auditor,
+    error::CrsError,
+    expiry::{expire_due, ExpiryReport},
+    repository::CertificateRepository,
+    signing::Keyring,
+};
+
+/// Runs the expiry job right away, for operators who cannot wait for the next scheduled run
+pub async fn run(
+    auditor: Auditor,
+    repository: web::Data<dyn CertificateRepository>,
+    keyring: Option<web::Data<Keyring>>,
+) -> Result<ExpiryReport, CrsError> {
+    let report = expire_due(
+        &**repository,
+        &auditor,
+        keyring.as_ref().map(|keyring| keyring.get_ref()),
+        Utc::now().trunc_subsecs(3),
+    )
+    .await?;
+    info!(
+        "Expiry run expired {} certificates and {} accreditations",
+        report.certificates, report.accreditations
+    );
+    Ok(report)
+}
@@ -1,143 +1,157 @@
-use actix_web::{web, Either, HttpResponse, Responder};
-use log::error;
-use mongodb::Database;
+use actix_web::{web, HttpRequest, Responder};
 use uuid::Uuid;
 
 use crate::{
-    db::{find_certificate_by_id, find_certificates_by_user_id},
     domain::{
         base::Id,
         certificate::{Certificate, Certificates},
     },
+    error::CrsError,
+    export::{
+        open_badges::assertion_for_request,
+        pdf::{CertificatePdf, PdfTemplates},
+        verifiable_credential::credential_for_request,
+    },
+    repository::CertificateRepository,
+    tenant::Tenant,
 };
 
-pub async fn by_id(path: web::Path<(Uuid,)>, data: web::Data<Option<Database>>) -> impl Responder {
-    let certificate_id_result = Id::parse(path.into_inner().0);
+/// Loads a certificate of the tenant, reporting a missing one or one the tenant
+/// cannot reach as not found
+pub(crate) async fn find_certificate(
+    repository: &web::Data<dyn CertificateRepository>,
+    tenant: Tenant,
+    certificate_id: Uuid,
+) -> Result<Certificate, CrsError> {
+    let certificate_id = Id::parse(certificate_id)?;
+    repository
+        .find_by_id(tenant.account_id(), certificate_id.as_uuid())
+        .await?
+        .filter(|certificate| tenant.can_access(certificate))
+        .ok_or_else(|| CrsError::NotFound("certificate not found".to_string()))
+}
 
-    if certificate_id_result.is_err() {
-        Either::Right(
-            HttpResponse::BadRequest().body(certificate_id_result.err().unwrap().to_string()),
-        )
-    } else {
-        match data.into() {
-            Some(db) => {
-                if let Some(database) = db.as_ref() {
-                    if let Ok(certificate_id) = certificate_id_result {
-                        if let Some(certificate_model) =
-                            find_certificate_by_id(database, certificate_id.as_uuid()).await
-                        {
-                            return match Certificate::try_from(certificate_model) {
-                                Ok(certificate) => Either::Left(certificate),
-                                Err(err) => Either::Right(
-                                    HttpResponse::InternalServerError().body(err.to_string()),
-                                ),
-                            };
-                        }
-                    }
-                }
-
-                Either::Right(
-                    HttpResponse::InternalServerError().body("Failed to find certificate!"),
-                )
-            }
-            None => {
-                error!("Unable to read state data");
-                Either::Right(HttpResponse::InternalServerError().body("DB State is unavailable"))
-            }
-        }
-    }
+pub async fn by_id(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn CertificateRepository>,
+) -> Result<Certificate, CrsError> {
+    find_certificate(&repository, tenant, path.into_inner().0).await
+}
+
+pub async fn vc_by_id(
+    req: HttpRequest,
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn CertificateRepository>,
+) -> Result<impl Responder, CrsError> {
+    let certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    Ok(credential_for_request(&certificate, &req)?)
+}
+
+pub async fn badge_by_id(
+    req: HttpRequest,
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn CertificateRepository>,
+) -> Result<impl Responder, CrsError> {
+    let certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    Ok(assertion_for_request(&certificate, &req)?)
+}
+
+pub async fn pdf_by_id(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn CertificateRepository>,
+    templates: Option<web::Data<PdfTemplates>>,
+) -> Result<CertificatePdf, CrsError> {
+    let certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    // a certificate stored without its recipient details would render with blanks
+    certificate.issued_details()?;
+    let templates = templates.unwrap_or_else(|| web::Data::new(PdfTemplates::default()));
+    Ok(CertificatePdf::render(
+        &certificate,
+        templates.template_for(certificate.product_id),
+    )?)
 }
 
 pub async fn by_user_id(
+    tenant: Tenant,
     path: web::Path<(Uuid,)>,
-    data: web::Data<Option<Database>>,
-) -> impl Responder {
-    let user_id_result = Id::parse(path.into_inner().0);
-
-    if user_id_result.is_err() {
-        Either::Right(HttpResponse::BadRequest().body(user_id_result.err().unwrap().to_string()))
-    } else {
-        match data.into() {
-            Some(db) => {
-                if let Some(database) = db.as_ref() {
-                    if let Ok(user_id) = user_id_result {
-                        if let Some(certificate_models) =
-                            find_certificates_by_user_id(database, user_id.as_uuid()).await
-                        {
-                            let certificates: Vec<Certificate> = certificate_models
-                                .into_iter()
-                                .map(|certificate_model| {
-                                    let certificate = Certificate::try_from(certificate_model);
-                                    match certificate {
-                                        Ok(certificate) => certificate,
-                                        Err(err) => {
-                                            error!("{}", err);
-                                            panic!("Failed to parse certificate");
-                                        }
-                                    }
-                                })
-                                .collect();
-                            return Either::Left(Certificates(certificates));
-                        }
-                    }
-                }
-
-                Either::Right(
-                    HttpResponse::InternalServerError().body("Failed to find certificates!"),
-                )
-            }
-            None => {
-                error!("Unable to read state data");
-                Either::Right(HttpResponse::InternalServerError().body("DB State is unavailable"))
-            }
-        }
-    }
+    repository: web::Data<dyn CertificateRepository>,
+) -> Result<Certificates, CrsError> {
+    let user_id = Id::parse(path.into_inner().0)?;
+    tenant.ensure_user(user_id.as_uuid())?;
+    Ok(Certificates(
+        repository
+            .find_by_user_id(tenant.account_id(), user_id.as_uuid())
+            .await?,
+    ))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::sync::Arc;
 
     use actix_web::{
         dev::Service,
-        http::StatusCode,
+        http::{header, StatusCode},
         test::{self},
         web, App,
     };
+    use ed25519_dalek::SigningKey;
+    use serde_json::json;
     use uuid::Uuid;
 
-    use crate::{crs_service, db::init_db};
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        domain::person::CertificateRecipient,
+        export::{
+            open_badges::{OPEN_BADGES_V2_CONTENT_TYPE, OPEN_BADGES_V3_PROFILE},
+            verifiable_credential::{VerifiableCredential, VC_CONTENT_TYPE},
+        },
+        repository::{in_memory::InMemoryCertificateRepository, CertificateRepository},
+        signing::Keyring,
+        test_helpers::{
+            audited, authenticated, bearer_token, certificate_for, OTHER_ACCOUNT_API_KEY,
+            TEST_API_KEY,
+        },
+    };
 
     #[actix_web::test]
-    #[ignore = "requires MongoDB instance running"]
     async fn find_certificate_by_valid_id() {
-        let db = init_db().await.expect("failed to connect");
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
 
         let app = test::init_service(
             App::new()
-                .app_data(web::Data::new(Some(db)))
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
                 .configure(crs_service),
         )
         .await;
 
-        // Tried to insert a certificate into the database first and use the same id here,
-        // but it didn't work. So, I'm using a random id here.
-        // So, make sure to change the id to a valid one before running the test.
-        let certificate_id = Uuid::from_str("4e7fc873-cdec-4701-9511-7b56ddede695").unwrap();
         let uri = format!("/api/certificates/{}", certificate_id);
-        let req = test::TestRequest::with_uri(uri.as_str()).to_request();
+        let req = test::TestRequest::with_uri(uri.as_str())
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
 
         let resp = test::call_service(&app, req).await;
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
     #[actix_web::test]
-    #[ignore = "requires MongoDB instance running"]
     async fn test_get_certificate_by_invalid_id() {
-        let db = init_db().await.expect("failed to connect");
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
         let app = test::init_service(
             App::new()
-                .app_data(web::Data::new(Some(db)))
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
                 .configure(crs_service),
         )
         .await;
@@ -146,10 +160,355 @@ mod tests {
 
         let req = test::TestRequest::get()
             .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
             .to_request();
 
         let resp = app.call(req).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
+
+    #[actix_web::test]
+    async fn get_unknown_certificate_should_return_not_found_problem() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{}", Uuid::new_v4()))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/problem+json"
+        );
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["code"], "not_found");
+        assert_eq!(problem["status"], 404);
+    }
+
+    #[actix_web::test]
+    async fn certificates_of_other_accounts_should_not_be_found() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let user_id = Uuid::new_v4();
+        let certificate = certificate_for(user_id);
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/user/{user_id}"))
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        let certificates: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        assert!(certificates.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn users_should_only_read_their_own_certificates() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let user_id = Uuid::new_v4();
+        let own = certificate_for(user_id);
+        repository.insert(&own).await.unwrap();
+        let other_user_id = Uuid::new_v4();
+        let other = certificate_for(other_user_id);
+        repository.insert(&other).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+        let token = bearer_token(json!({"sub": user_id, "account_id": 20, "roles": ["learner"]}));
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{}", own.id.as_uuid()))
+            .insert_header((header::AUTHORIZATION, token.as_str()))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{}", other.id.as_uuid()))
+            .insert_header((header::AUTHORIZATION, token.as_str()))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/user/{other_user_id}"))
+            .insert_header((header::AUTHORIZATION, token.as_str()))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::FORBIDDEN
+        );
+
+        let req = test::TestRequest::get()
+            .uri("/api/certificates")
+            .insert_header((header::AUTHORIZATION, token.as_str()))
+            .to_request();
+        let page: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+        assert_eq!(page["items"][0]["id"], own.id.as_uuid().to_string());
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{}/revoke", own.id.as_uuid()))
+            .insert_header((header::AUTHORIZATION, token.as_str()))
+            .set_json(json!({"reason": "fraud"}))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::FORBIDDEN
+        );
+
+        let admin = bearer_token(json!({"sub": user_id, "account_id": 20, "roles": ["admin"]}));
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{}", other.id.as_uuid()))
+            .insert_header((header::AUTHORIZATION, admin.as_str()))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = test::TestRequest::get()
+            .uri("/api/certificates")
+            .insert_header((header::AUTHORIZATION, "Bearer not-a-token"))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[actix_web::test]
+    async fn find_certificates_by_user_id() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        repository
+            .insert(&certificate_for(Uuid::new_v4()))
+            .await
+            .unwrap();
+        let user_id = Uuid::new_v4();
+        repository.insert(&certificate_for(user_id)).await.unwrap();
+        repository.insert(&certificate_for(user_id)).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/user/{user_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+
+        let certificates: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(certificates.len(), 2);
+    }
+
+    #[actix_web::test]
+    async fn get_certificate_as_verifiable_credential() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+        let keyring = Keyring::with_default_key(SigningKey::from_bytes(&[7u8; 32]));
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .app_data(web::Data::new(keyring))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((header::ACCEPT, VC_CONTENT_TYPE))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            VC_CONTENT_TYPE
+        );
+        let by_accept: VerifiableCredential = test::read_body_json(resp).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{certificate_id}/vc"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let by_path: VerifiableCredential = test::call_and_read_body_json(&app, req).await;
+
+        let verifying_key = SigningKey::from_bytes(&[7u8; 32]).verifying_key();
+        assert_eq!(by_accept.id, format!("urn:uuid:{certificate_id}"));
+        assert!(by_accept.verify(&verifying_key));
+        assert!(by_path.verify(&verifying_key));
+    }
+
+    #[actix_web::test]
+    async fn get_certificate_as_open_badge() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((header::ACCEPT, OPEN_BADGES_V2_CONTENT_TYPE))
+            .to_request();
+        let assertion: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(assertion["type"], "Assertion");
+        assert_eq!(assertion["recipient"]["hashed"], true);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{certificate_id}/badge"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let hosted: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(hosted["id"], assertion["id"]);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((
+                header::ACCEPT,
+                format!(r#"{VC_CONTENT_TYPE}; profile="{OPEN_BADGES_V3_PROFILE}""#),
+            ))
+            .to_request();
+        let credential: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(credential["type"][1], "OpenBadgeCredential");
+    }
+
+    #[actix_web::test]
+    async fn get_certificate_as_pdf() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{certificate_id}/pdf"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/pdf"
+        );
+        let body = test::read_body(resp).await;
+        assert!(body.starts_with(b"%PDF"));
+    }
+
+    #[actix_web::test]
+    async fn version_1_certificates_should_stay_readable() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let user_id = Uuid::new_v4();
+        let mut certificate = certificate_for(user_id);
+        // version 1 documents only hold the recipient id
+        certificate.recipient = CertificateRecipient {
+            id: certificate.recipient.id,
+            name: None,
+            email: None,
+            phone: None,
+        };
+        certificate.authority = None;
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let read: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(read["recipient"], json!({ "id": user_id }));
+        assert!(read.get("authority").is_none());
+
+        for uri in [
+            format!("/api/certificates/user/{user_id}"),
+            "/api/certificates".to_string(),
+            format!("/api/verify/{certificate_id}"),
+        ] {
+            let req = test::TestRequest::get()
+                .uri(&uri)
+                .insert_header((API_KEY_HEADER, TEST_API_KEY))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK, "{uri}");
+        }
+
+        for uri in ["vc", "badge", "pdf"] {
+            let req = test::TestRequest::get()
+                .uri(&format!("/api/certificates/{certificate_id}/{uri}"))
+                .insert_header((API_KEY_HEADER, TEST_API_KEY))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::CONFLICT, "{uri}");
+        }
+    }
 }
@@ -0,0 +1,157 @@
+use actix_web::web;
+
+use crate::{
+    dto::certificate_query_dto::CertificateQueryDto,
+    error::CrsError,
+    repository::{
+        query::{CertificatePage, CertificateQuery},
+        CertificateRepository,
+    },
+    tenant::Tenant,
+};
+
+pub async fn index(
+    tenant: Tenant,
+    query: web::Query<CertificateQueryDto>,
+    repository: web::Data<dyn CertificateRepository>,
+) -> Result<CertificatePage, CrsError> {
+    if let Some(account_id) = query.account_id {
+        tenant.ensure_account(account_id)?;
+    }
+    let mut query = CertificateQuery::try_from(query.into_inner())?;
+    tenant.restrict(&mut query)?;
+    Ok(repository.find_page(tenant.account_id(), &query).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, web, App};
+    use chrono::Duration;
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        repository::{in_memory::InMemoryCertificateRepository, CertificateRepository},
+        test_helpers::{audited, authenticated, certificate_for, TEST_API_KEY},
+    };
+
+    #[actix_web::test]
+    async fn list_certificates_should_page_through_all_matches() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let user_id = Uuid::new_v4();
+        let mut issued = Vec::new();
+        for day in 0..5 {
+            let mut certificate = certificate_for(user_id);
+            certificate.created_date -= Duration::days(day);
+            issued.push(certificate.id.as_uuid().to_string());
+            repository.insert(&certificate).await.unwrap();
+        }
+        let mut other_account = certificate_for(user_id);
+        other_account.account_id = 99;
+        repository.insert(&other_account).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let mut listed = Vec::new();
+        let mut uri = format!("/api/certificates?account_id=20&user_id={user_id}&limit=2");
+        loop {
+            let req = test::TestRequest::get()
+                .uri(&uri)
+                .insert_header((API_KEY_HEADER, TEST_API_KEY))
+                .to_request();
+            let page: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+            for item in page["items"].as_array().unwrap() {
+                listed.push(item["id"].as_str().unwrap().to_string());
+            }
+            match page["next"].as_str() {
+                Some(next) => {
+                    uri = format!(
+                        "/api/certificates?account_id=20&user_id={user_id}&limit=2&cursor={next}"
+                    )
+                }
+                None => break,
+            }
+        }
+
+        assert_eq!(listed, issued);
+    }
+
+    #[actix_web::test]
+    async fn list_certificates_should_filter_by_status() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let revoked_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+        repository
+            .insert(&certificate_for(Uuid::new_v4()))
+            .await
+            .unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{revoked_id}/revoke"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(serde_json::json!({"reason": "superseded"}))
+            .to_request();
+        test::call_service(&app, req).await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/certificates?status=revoked")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let page: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+        assert_eq!(page["items"][0]["id"], revoked_id.to_string());
+        assert!(page["next"].is_null());
+    }
+
+    #[actix_web::test]
+    async fn list_certificates_with_invalid_query_should_return_bad_request() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        for uri in [
+            "/api/certificates?limit=0",
+            "/api/certificates?status=lost",
+            "/api/certificates?cursor=bogus",
+            "/api/certificates?created_from=2024-02-01T00:00:00Z&created_to=2024-01-01T00:00:00Z",
+        ] {
+            let req = test::TestRequest::get()
+                .uri(uri)
+                .insert_header((API_KEY_HEADER, TEST_API_KEY))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+}
@@ -1,2 +1,15 @@
+pub mod accreditation;
+pub mod api_keys;
+pub mod certificate_csv;
+pub mod certificate_history;
+pub mod expire_certificates;
 pub mod get_certificate;
+pub mod list_certificates;
+pub mod organizations;
+pub mod products;
+pub mod recipients;
+pub mod renew_certificate;
+pub mod revoke_certificate;
 pub mod store_certificate;
+pub mod update_certificate;
+pub mod verify_certificate;
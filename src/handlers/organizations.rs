@@ -0,0 +1,240 @@
+use actix_web::{web, HttpResponse};
+use log::info;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        base::Id,
+        organization::{Organization, Organizations},
+    },
+    dto::organization_dto::OrganizationDto,
+    error::CrsError,
+    repository::OrganizationRepository,
+    tenant::Tenant,
+};
+
+fn not_found() -> CrsError {
+    CrsError::NotFound("organization not found".to_string())
+}
+
+/// Registers an organization in the tenant's account
+pub async fn create(
+    tenant: Tenant,
+    organization: web::Json<OrganizationDto>,
+    repository: web::Data<dyn OrganizationRepository>,
+) -> Result<Organization, CrsError> {
+    organization.validate()?;
+    let organization = Organization::from_dto(Id::parse(Uuid::new_v4())?, organization.0)?;
+    repository
+        .insert(tenant.account_id(), &organization)
+        .await?;
+    info!("Created organization: {}", organization.id.as_uuid());
+    Ok(organization)
+}
+
+pub async fn index(
+    tenant: Tenant,
+    repository: web::Data<dyn OrganizationRepository>,
+) -> Result<Organizations, CrsError> {
+    Ok(Organizations(
+        repository.find_all(tenant.account_id()).await?,
+    ))
+}
+
+pub async fn by_id(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn OrganizationRepository>,
+) -> Result<Organization, CrsError> {
+    let organization_id = Id::parse(path.into_inner().0)?;
+    repository
+        .find_by_id(tenant.account_id(), organization_id.as_uuid())
+        .await?
+        .ok_or_else(not_found)
+}
+
+/// Replaces an organization, certificates issued before keep the authority they were issued with
+pub async fn update(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    organization: web::Json<OrganizationDto>,
+    repository: web::Data<dyn OrganizationRepository>,
+) -> Result<Organization, CrsError> {
+    let organization_id = Id::parse(path.into_inner().0)?;
+    organization.validate()?;
+    let organization = Organization::from_dto(organization_id, organization.0)?;
+    if !repository
+        .update(tenant.account_id(), &organization)
+        .await?
+    {
+        return Err(not_found());
+    }
+    info!("Updated organization: {}", organization.id.as_uuid());
+    Ok(organization)
+}
+
+pub async fn delete(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn OrganizationRepository>,
+) -> Result<HttpResponse, CrsError> {
+    let organization_id = Id::parse(path.into_inner().0)?;
+    if !repository
+        .delete(tenant.account_id(), organization_id.as_uuid())
+        .await?
+    {
+        return Err(not_found());
+    }
+    info!("Deleted organization: {}", organization_id.as_uuid());
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, App};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        repository::Repositories,
+        test_helpers::{authenticated, OTHER_ACCOUNT_API_KEY, TEST_API_KEY},
+    };
+
+    #[actix_web::test]
+    async fn organization_should_support_create_read_update_delete() {
+        let repositories = Repositories::in_memory();
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({
+                "name": "Acme Academy",
+                "email": "certificates@acme.com",
+                "phone": "+45 87654321"
+            }))
+            .to_request();
+        let created: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let uri = format!("/api/organizations/{}", created["id"].as_str().unwrap());
+
+        let req = test::TestRequest::put()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({
+                "name": "Acme University",
+                "email": "certificates@acme.com",
+                "phone": "+45 87654321"
+            }))
+            .to_request();
+        let updated: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(updated["name"], "Acme University");
+
+        let req = test::TestRequest::get()
+            .uri("/api/organizations")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let listed: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0]["name"], "Acme University");
+
+        let req = test::TestRequest::delete()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NO_CONTENT
+        );
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[actix_web::test]
+    async fn create_invalid_organization_should_report_violations() {
+        let repositories = Repositories::in_memory();
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"name": "Acme Academy", "email": "acme", "phone": "call us"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"].as_array().unwrap().len(), 2);
+    }
+
+    #[actix_web::test]
+    async fn organizations_of_other_accounts_should_not_be_reached() {
+        let repositories = Repositories::in_memory();
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let organization = json!({
+            "name": "Acme Academy",
+            "email": "certificates@acme.com",
+            "phone": "+45 87654321"
+        });
+        let req = test::TestRequest::post()
+            .uri("/api/organizations")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(&organization)
+            .to_request();
+        let created: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let uri = format!("/api/organizations/{}", created["id"].as_str().unwrap());
+
+        let req = test::TestRequest::get()
+            .uri("/api/organizations")
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        let listed: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        assert!(listed.is_empty());
+        for req in [
+            test::TestRequest::get(),
+            test::TestRequest::put().set_json(&organization),
+            test::TestRequest::delete(),
+        ] {
+            let req = req
+                .uri(&uri)
+                .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+                .to_request();
+            assert_eq!(
+                test::call_service(&app, req).await.status(),
+                StatusCode::NOT_FOUND
+            );
+        }
+
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+}
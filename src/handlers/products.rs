@@ -0,0 +1,247 @@
+use actix_web::{web, HttpResponse};
+use log::info;
+
+use crate::{
+    domain::product::{Product, Products},
+    dto::product_dto::{NewProductDto, ProductDto},
+    error::{CrsError, FieldError},
+    repository::{OrganizationRepository, ProductRepository},
+    tenant::Tenant,
+};
+
+fn not_found() -> CrsError {
+    CrsError::NotFound("product not found".to_string())
+}
+
+/// Ensures the issuing organization of a product is registered in the tenant's account
+async fn ensure_organization(
+    tenant: &Tenant,
+    product: &ProductDto,
+    organizations: &web::Data<dyn OrganizationRepository>,
+) -> Result<(), CrsError> {
+    if organizations
+        .find_by_id(tenant.account_id(), product.organization_id)
+        .await?
+        .is_none()
+    {
+        return Err(CrsError::Validation {
+            message: "invalid product".to_string(),
+            errors: vec![FieldError::new(
+                "organization_id",
+                "exists",
+                product.organization_id,
+                "organization does not exist",
+            )],
+        });
+    }
+    Ok(())
+}
+
+/// Registers a product in the tenant's account
+pub async fn create(
+    tenant: Tenant,
+    product: web::Json<NewProductDto>,
+    repository: web::Data<dyn ProductRepository>,
+    organizations: web::Data<dyn OrganizationRepository>,
+) -> Result<Product, CrsError> {
+    product.validate()?;
+    ensure_organization(&tenant, &product.product, &organizations).await?;
+    let NewProductDto {
+        product_id,
+        product,
+    } = product.into_inner();
+    let product = Product::from_dto(product_id, product)?;
+    if !repository.insert(tenant.account_id(), &product).await? {
+        return Err(CrsError::Conflict(format!(
+            "product {product_id} already exists"
+        )));
+    }
+    info!("Created product: {}", product.id);
+    Ok(product)
+}
+
+pub async fn index(
+    tenant: Tenant,
+    repository: web::Data<dyn ProductRepository>,
+) -> Result<Products, CrsError> {
+    Ok(Products(repository.find_all(tenant.account_id()).await?))
+}
+
+pub async fn by_id(
+    tenant: Tenant,
+    path: web::Path<(u32,)>,
+    repository: web::Data<dyn ProductRepository>,
+) -> Result<Product, CrsError> {
+    repository
+        .find_by_id(tenant.account_id(), path.into_inner().0)
+        .await?
+        .ok_or_else(not_found)
+}
+
+/// Replaces a product, certificates issued before keep the name, validity and
+/// assessment they were issued with
+pub async fn update(
+    tenant: Tenant,
+    path: web::Path<(u32,)>,
+    product: web::Json<ProductDto>,
+    repository: web::Data<dyn ProductRepository>,
+    organizations: web::Data<dyn OrganizationRepository>,
+) -> Result<Product, CrsError> {
+    product.validate()?;
+    ensure_organization(&tenant, &product, &organizations).await?;
+    let product = Product::from_dto(path.into_inner().0, product.into_inner())?;
+    if !repository.update(tenant.account_id(), &product).await? {
+        return Err(not_found());
+    }
+    info!("Updated product: {}", product.id);
+    Ok(product)
+}
+
+pub async fn delete(
+    tenant: Tenant,
+    path: web::Path<(u32,)>,
+    repository: web::Data<dyn ProductRepository>,
+) -> Result<HttpResponse, CrsError> {
+    let product_id = path.into_inner().0;
+    if !repository.delete(tenant.account_id(), product_id).await? {
+        return Err(not_found());
+    }
+    info!("Deleted product: {}", product_id);
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, App};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        test_helpers::{
+            authenticated, repositories_with_organization, OTHER_ACCOUNT_API_KEY, TEST_API_KEY,
+        },
+    };
+
+    #[actix_web::test]
+    async fn product_should_define_issued_certificates() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let product = json!({"product_id": 15, "name": "Rust fundamentals", "description": "Completed the Rust fundamentals course", "organization_id": organization_id, "validity_days": 365, "score_scale": {"max": 500, "passing_score": 300}, "required_accreditation": {"name": "ISO 9001", "institution": "ISO"}});
+        let req = test::TestRequest::post()
+            .uri("/api/products")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(&product)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri("/api/products")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(&product)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        let mut certificate = json!({"account_id": 20, "product_id": 15, "organization_id": organization_id, "recipient": {"id": Uuid::new_v4(), "first_name": "John", "last_name": "Doe", "email": "john.doe@email.com", "phone": "12345678"}, "metadata": {"score": 420, "progress": 1.0, "acquired_date": "2024-01-15T10:00:00Z"}});
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(&certificate)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"][0]["field"], "metadata.accreditation");
+        assert_eq!(problem["errors"][0]["rule"], "product");
+
+        certificate["metadata"]["accreditation"] = json!({"name": "ISO 9001", "institution": "ISO", "start_date": "2024-01-01T00:00:00Z", "status": "active"});
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(&certificate)
+            .to_request();
+        let issued: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(issued["name"], "Rust fundamentals");
+        assert_eq!(
+            issued["description"],
+            "Completed the Rust fundamentals course"
+        );
+        assert_eq!(issued["assessment"]["score"]["max"], 500);
+        assert_eq!(issued["assessment"]["result"], "Pass");
+        assert_eq!(
+            issued["validity"]["valid_until"],
+            json!({"Expiry": "2025-01-14T10:00:00Z"})
+        );
+    }
+
+    #[actix_web::test]
+    async fn products_of_other_accounts_should_not_be_reached() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let product = json!({"product_id": 15, "name": "Rust fundamentals", "organization_id": organization_id});
+        let req = test::TestRequest::post()
+            .uri("/api/products")
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .set_json(&product)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"][0]["field"], "organization_id");
+
+        let req = test::TestRequest::post()
+            .uri("/api/products")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(&product)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = test::TestRequest::get()
+            .uri("/api/products")
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        let listed: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        assert!(listed.is_empty());
+        // the organization of account 20 cannot back a product of account 99 either
+        for (req, status) in [
+            (test::TestRequest::get(), StatusCode::NOT_FOUND),
+            (
+                test::TestRequest::put()
+                    .set_json(json!({"name": "Taken over", "organization_id": organization_id})),
+                StatusCode::BAD_REQUEST,
+            ),
+            (test::TestRequest::delete(), StatusCode::NOT_FOUND),
+        ] {
+            let req = req
+                .uri("/api/products/15")
+                .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+                .to_request();
+            assert_eq!(test::call_service(&app, req).await.status(), status);
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/api/products/15")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let stored: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(stored["name"], "Rust fundamentals");
+    }
+}
@@ -0,0 +1,458 @@
+use actix_web::web;
+use log::info;
+use uuid::Uuid;
+
+use crate::{
+    audit::Auditor,
+    domain::{
+        audit::{snapshot, AuditAction},
+        base::{Email, Id},
+        person::{CertificateRecipient, Person, Recipients},
+        revocation::CertificateStatus,
+    },
+    dto::recipient_dto::{RecipientProfileDto, RecipientQueryDto},
+    error::{CrsError, FieldError},
+    repository::{CertificateRepository, RecipientRepository},
+    signing::{sign_changed, Keyring},
+    tenant::Tenant,
+};
+
+fn not_found() -> CrsError {
+    CrsError::NotFound("recipient not found".to_string())
+}
+
+fn duplicate_email(existing: &Person) -> CrsError {
+    CrsError::Conflict(format!(
+        "the email is already registered to recipient {}",
+        existing.id.as_uuid()
+    ))
+}
+
+/// Registers a recipient in the tenant's account, unless their email is already
+/// registered there
+pub async fn create(
+    tenant: Tenant,
+    profile: web::Json<RecipientProfileDto>,
+    repository: web::Data<dyn RecipientRepository>,
+) -> Result<Person, CrsError> {
+    profile.validate()?;
+    let recipient = Person::from_profile(Id::parse(Uuid::new_v4())?, profile.into_inner())?;
+    let registered = repository.register(tenant.account_id(), &recipient).await?;
+    if registered.id.as_uuid() != recipient.id.as_uuid() {
+        return Err(duplicate_email(&registered));
+    }
+    info!("Registered recipient: {}", registered.id.as_uuid());
+    Ok(registered)
+}
+
+/// Lists every recipient of the tenant's account, or the one registered with the
+/// `email` filter
+pub async fn index(
+    tenant: Tenant,
+    query: web::Query<RecipientQueryDto>,
+    repository: web::Data<dyn RecipientRepository>,
+) -> Result<Recipients, CrsError> {
+    let recipients = match query.into_inner().email {
+        Some(email) => {
+            let email = Email::parse(email.clone()).map_err(|_| CrsError::Validation {
+                message: "invalid recipient query".to_string(),
+                errors: vec![FieldError::new(
+                    "email",
+                    "email",
+                    email,
+                    "must be a valid email address",
+                )],
+            })?;
+            repository
+                .find_by_email(tenant.account_id(), &email)
+                .await?
+                .into_iter()
+                .collect()
+        }
+        None => repository.find_all(tenant.account_id()).await?,
+    };
+    Ok(Recipients(recipients))
+}
+
+pub async fn by_id(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn RecipientRepository>,
+) -> Result<Person, CrsError> {
+    let recipient_id = Id::parse(path.into_inner().0)?;
+    repository
+        .find_by_id(tenant.account_id(), recipient_id.as_uuid())
+        .await?
+        .ok_or_else(not_found)
+}
+
+/// Replaces the profile of a recipient and refreshes the recipient details of the
+/// certificates the tenant's account issued to them.
+///
+/// Every refreshed certificate gets a new version, is signed again and has the
+/// change recorded in its history, like any other certificate update. Revoked
+/// certificates keep the details they were revoked with. The registry is only
+/// updated once every certificate is refreshed, so a request failing on a
+/// concurrently changed certificate can be retried.
+pub async fn update(
+    tenant: Tenant,
+    auditor: Auditor,
+    path: web::Path<(Uuid,)>,
+    profile: web::Json<RecipientProfileDto>,
+    recipients: web::Data<dyn RecipientRepository>,
+    certificates: web::Data<dyn CertificateRepository>,
+    keyring: Option<web::Data<Keyring>>,
+) -> Result<Person, CrsError> {
+    let recipient_id = Id::parse(path.into_inner().0)?;
+    profile.validate()?;
+    let recipient = Person::from_profile(recipient_id, profile.into_inner())?;
+    if let Some(existing) = recipients
+        .find_by_email(tenant.account_id(), &recipient.email)
+        .await?
+    {
+        if existing.id.as_uuid() != recipient.id.as_uuid() {
+            return Err(duplicate_email(&existing));
+        }
+    }
+    if recipients
+        .find_by_id(tenant.account_id(), recipient.id.as_uuid())
+        .await?
+        .is_none()
+    {
+        return Err(not_found());
+    }
+    let issued = certificates
+        .find_by_user_id(tenant.account_id(), recipient.id.as_uuid())
+        .await?;
+
+    let mut refreshed = 0;
+    let details = CertificateRecipient::from(recipient.clone());
+    // certificates already carrying the details are left alone, so a retry after a
+    // conflict only refreshes the remaining ones
+    for mut certificate in issued.into_iter().filter(|certificate| {
+        certificate.status != CertificateStatus::Revoked && certificate.recipient != details
+    }) {
+        let expected_version = certificate.version;
+        let before = snapshot(&certificate);
+        certificate.refresh_recipient(recipient.clone())?;
+        sign_changed(
+            keyring.as_ref().map(|keyring| keyring.get_ref()),
+            &mut certificate,
+        );
+        if !certificates.update(&certificate, expected_version).await? {
+            return Err(CrsError::Conflict(format!(
+                "certificate {} was modified concurrently, retry",
+                certificate.id.as_uuid()
+            )));
+        }
+        auditor
+            .record(AuditAction::Update, Some(before), &certificate)
+            .await;
+        refreshed += 1;
+    }
+    if !recipients.update(tenant.account_id(), &recipient).await? {
+        return Err(not_found());
+    }
+    info!(
+        "Updated recipient {}, refreshed {} certificates",
+        recipient.id.as_uuid(),
+        refreshed
+    );
+    Ok(recipient)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, web, App};
+    use chrono::Utc;
+    use ed25519_dalek::SigningKey;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        domain::{
+            audit::AuditAction,
+            revocation::{Revocation, RevocationReason},
+            signature::SignatureStatus,
+        },
+        signing::Keyring,
+        test_helpers::{
+            authenticated, certificate_for, recipient, repositories_with_organization,
+            OTHER_ACCOUNT_API_KEY, TEST_API_KEY,
+        },
+    };
+
+    #[actix_web::test]
+    async fn recipient_with_registered_email_should_be_rejected() {
+        let (repositories, _) = repositories_with_organization().await;
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/recipients")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(
+                json!({"first_name": "Jane", "last_name": "Doe", "email": "jane.doe@email.com"}),
+            )
+            .to_request();
+        let created: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/recipients")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(
+                json!({"first_name": "Jane", "last_name": "Doe", "email": "Jane.Doe@Email.com"}),
+            )
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        let req = test::TestRequest::get()
+            .uri("/api/recipients?email=JANE.DOE@email.com")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let found: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0]["id"], created["id"]);
+    }
+
+    #[actix_web::test]
+    async fn issuance_should_reuse_recipient_and_reads_should_reflect_profile_updates() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let first_id = Uuid::new_v4();
+        let certificate = |recipient_id: Uuid, email: &str| {
+            json!({"account_id": 20, "product_id": 15, "organization_id": organization_id,
+                "recipient": {"id": recipient_id, "first_name": "Jane", "last_name": "Doe", "email": email, "phone": ""},
+                "metadata": {"score": 90, "progress": 1.0}})
+        };
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(certificate(first_id, "jane.doe@email.com"))
+            .to_request();
+        let first: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        // the same learner with a differently cased email
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(certificate(first_id, "Jane.Doe@Email.com"))
+            .to_request();
+        let second: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(second["recipient"]["id"], first_id.to_string());
+
+        // the registered email under another id is reported, not issued to the registered recipient
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(certificate(Uuid::new_v4(), "jane.doe@email.com"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"][0]["field"], "recipient.id");
+        assert_eq!(problem["errors"][0]["rule"], "registered");
+
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/recipients/{first_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({
+                "first_name": "Jane",
+                "last_name": "Smith",
+                "email": "jane.smith@email.com"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/api/certificates/{}",
+                first["id"].as_str().unwrap()
+            ))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let read: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(read["recipient"]["name"]["last_name"], "Smith");
+        assert_eq!(read["recipient"]["email"], "jane.smith@email.com");
+
+        // details that no longer match the registered ones are reported
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(certificate(first_id, "jane.smith@email.com"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"][0]["field"], "recipient.last_name");
+
+        let req = test::TestRequest::get()
+            .uri("/api/recipients")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let listed: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(listed.len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn recipient_update_should_leave_revoked_and_other_account_certificates_unchanged() {
+        let (repositories, _) = repositories_with_organization().await;
+        let user_id = Uuid::new_v4();
+        repositories
+            .recipients
+            .register(20, &recipient(user_id))
+            .await
+            .unwrap();
+        let keyring = web::Data::new(Keyring::with_default_key(SigningKey::from_bytes(
+            &[7u8; 32],
+        )));
+        let mut own = certificate_for(user_id);
+        keyring.sign(&mut own);
+        let mut other = certificate_for(user_id);
+        other.account_id = 99;
+        let mut revoked = certificate_for(user_id);
+        revoked
+            .revoke(Revocation {
+                reason: RevocationReason::IssuedInError,
+                revoked_at: Utc::now(),
+                comment: None,
+            })
+            .unwrap();
+        repositories.certificates.insert(&own).await.unwrap();
+        repositories.certificates.insert(&other).await.unwrap();
+        repositories.certificates.insert(&revoked).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .app_data(keyring.clone())
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/recipients/{user_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({
+                "first_name": "Jane",
+                "last_name": "Smith",
+                "email": "jane.smith@email.com"
+            }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let own = repositories
+            .certificates
+            .find_by_id(20, own.id.as_uuid())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            own.recipient.email.as_ref().unwrap().as_string(),
+            "jane.smith@email.com"
+        );
+        assert_eq!(own.version, 2);
+        assert_eq!(keyring.verify(&own), SignatureStatus::Valid);
+        assert!(own.updated_date.is_some());
+        let history = repositories
+            .audit
+            .find_by_certificate_id(own.id.as_uuid())
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].action, AuditAction::Update);
+        assert_eq!(
+            history[0].before.as_ref().unwrap()["recipient"]["name"]["last_name"],
+            "Doe"
+        );
+        let other = repositories
+            .certificates
+            .find_by_id(99, other.id.as_uuid())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(other.recipient, recipient(user_id).into());
+        assert_eq!(other.version, 1);
+        let stored = repositories
+            .certificates
+            .find_by_id(20, revoked.id.as_uuid())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.recipient, recipient(user_id).into());
+        assert_eq!(stored.version, revoked.version);
+    }
+
+    #[actix_web::test]
+    async fn recipients_should_be_kept_per_account() {
+        let (repositories, _) = repositories_with_organization().await;
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+        let profile =
+            json!({"first_name": "Jane", "last_name": "Doe", "email": "jane.doe@email.com"});
+
+        let req = test::TestRequest::post()
+            .uri("/api/recipients")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(&profile)
+            .to_request();
+        let created: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let uri = format!("/api/recipients/{}", created["id"].as_str().unwrap());
+
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+        let req = test::TestRequest::put()
+            .uri(&uri)
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .set_json(&profile)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+        let req = test::TestRequest::get()
+            .uri("/api/recipients?email=jane.doe@email.com")
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .to_request();
+        let found: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        assert!(found.is_empty());
+
+        // the same email is a different recipient in another account
+        let req = test::TestRequest::post()
+            .uri("/api/recipients")
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .set_json(&profile)
+            .to_request();
+        let other: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_ne!(other["id"], created["id"]);
+    }
+}
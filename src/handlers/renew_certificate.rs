@@ -0,0 +1,405 @@
+use std::collections::HashSet;
+
+use actix_web::web;
+use log::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    audit::Auditor,
+    domain::{
+        audit::{snapshot, AuditAction},
+        certificate::{Certificate, Certificates},
+    },
+    dto::renewal_dto::RenewalDto,
+    error::CrsError,
+    repository::CertificateRepository,
+    signing::Keyring,
+    tenant::Tenant,
+};
+
+use super::get_certificate::find_certificate;
+
+/// Renewal chains longer than this are reported as broken rather than followed
+const MAX_CHAIN_LENGTH: usize = 1000;
+
+/// Issues the successor of a certificate for a new validity window
+pub async fn index(
+    tenant: Tenant,
+    auditor: Auditor,
+    path: web::Path<(Uuid,)>,
+    body: web::Bytes,
+    repository: web::Data<dyn CertificateRepository>,
+    keyring: Option<web::Data<Keyring>>,
+) -> Result<Certificate, CrsError> {
+    // the body is optional, an empty one renews with the defaults
+    let renewal = if body.is_empty() {
+        RenewalDto::default()
+    } else {
+        serde_json::from_slice::<RenewalDto>(&body)
+            .map_err(|err| CrsError::BadRequest(err.to_string()))?
+    };
+    renewal.validate()?;
+
+    let mut certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    let expected_version = certificate.version;
+    let before = snapshot(&certificate);
+    let mut successor = certificate.renew(renewal.valid_from, renewal.valid_until)?;
+    if keyring.is_some_and(|keyring| !keyring.sign(&mut successor)) {
+        warn!(
+            "No signing key for the issuer of renewed certificate {}, it is stored unsigned",
+            successor.id.as_uuid()
+        );
+    }
+
+    // the successor is stored before the renewed certificate links to it, so a link
+    // never points to a missing certificate; it is removed again when linking fails,
+    // which is also how concurrent renewals of the same certificate are turned down
+    repository.insert(&successor).await?;
+    let linked = repository.update(&certificate, expected_version).await;
+    if !matches!(linked, Ok(true)) {
+        let account_id = successor.account_id;
+        let successor_id = successor.id.as_uuid();
+        if let Err(err) = repository.delete(account_id, successor_id).await {
+            error!("Could not remove unlinked renewed certificate {successor_id}: {err}");
+        }
+        linked?;
+        return Err(CrsError::Conflict(
+            "certificate was modified concurrently, retry".to_string(),
+        ));
+    }
+    auditor
+        .record(AuditAction::Renew, Some(before), &certificate)
+        .await;
+    auditor.record(AuditAction::Issue, None, &successor).await;
+    info!(
+        "Renewed certificate {} as {}",
+        certificate.id.as_uuid(),
+        successor.id.as_uuid()
+    );
+    Ok(successor)
+}
+
+/// Lists every certificate of the renewal chain of a certificate, oldest first
+pub async fn chain(
+    tenant: Tenant,
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn CertificateRepository>,
+) -> Result<Certificates, CrsError> {
+    let certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    let broken =
+        |id: Uuid| CrsError::Internal(format!("renewal chain of certificate {} is broken", id));
+
+    let mut seen = HashSet::from([certificate.id.as_uuid()]);
+    let mut predecessors = Vec::new();
+    let mut previous = certificate.renewal_of.as_ref().map(|id| id.as_uuid());
+    while let Some(id) = previous {
+        if !seen.insert(id) || seen.len() > MAX_CHAIN_LENGTH {
+            return Err(broken(id));
+        }
+        let predecessor = repository
+            .find_by_id(tenant.account_id(), id)
+            .await?
+            .ok_or_else(|| broken(id))?;
+        previous = predecessor.renewal_of.as_ref().map(|id| id.as_uuid());
+        predecessors.push(predecessor);
+    }
+
+    let mut next = certificate.renewed_by.as_ref().map(|id| id.as_uuid());
+    let mut chain: Vec<Certificate> = predecessors.into_iter().rev().collect();
+    chain.push(certificate);
+    while let Some(id) = next {
+        if !seen.insert(id) || seen.len() > MAX_CHAIN_LENGTH {
+            return Err(broken(id));
+        }
+        let successor = repository
+            .find_by_id(tenant.account_id(), id)
+            .await?
+            .ok_or_else(|| broken(id))?;
+        next = successor.renewed_by.as_ref().map(|id| id.as_uuid());
+        chain.push(successor);
+    }
+    Ok(Certificates(chain))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, web, App};
+    use async_trait::async_trait;
+    use chrono::{DateTime, Duration, SubsecRound, Utc};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        domain::{
+            audit::AuditEntry,
+            base::Id,
+            certificate::Certificate,
+            validity::{ValidUntil, Validity},
+        },
+        repository::{
+            in_memory::InMemoryCertificateRepository,
+            query::{CertificatePage, CertificateQuery},
+            AuditRepository, CertificateRepository, RepositoryError,
+        },
+        test_helpers::{audited, authenticated, certificate_for, TEST_API_KEY},
+    };
+
+    /// Store whose certificates are always changed concurrently, so updates fail
+    #[derive(Default)]
+    struct ContendedRepository(InMemoryCertificateRepository);
+
+    #[async_trait]
+    impl CertificateRepository for ContendedRepository {
+        async fn insert(&self, certificate: &Certificate) -> Result<(), RepositoryError> {
+            self.0.insert(certificate).await
+        }
+
+        async fn insert_many(&self, certificates: &[Certificate]) -> Result<(), RepositoryError> {
+            self.0.insert_many(certificates).await
+        }
+
+        async fn update(&self, _: &Certificate, _: u32) -> Result<bool, RepositoryError> {
+            Ok(false)
+        }
+
+        async fn delete(&self, account_id: u32, id: Uuid) -> Result<bool, RepositoryError> {
+            self.0.delete(account_id, id).await
+        }
+
+        async fn find_by_id(
+            &self,
+            account_id: u32,
+            id: Uuid,
+        ) -> Result<Option<Certificate>, RepositoryError> {
+            self.0.find_by_id(account_id, id).await
+        }
+
+        async fn find_by_id_across_accounts(
+            &self,
+            id: Uuid,
+        ) -> Result<Option<Certificate>, RepositoryError> {
+            self.0.find_by_id_across_accounts(id).await
+        }
+
+        async fn find_by_user_id(
+            &self,
+            account_id: u32,
+            user_id: Uuid,
+        ) -> Result<Vec<Certificate>, RepositoryError> {
+            self.0.find_by_user_id(account_id, user_id).await
+        }
+
+        async fn find_expiring(
+            &self,
+            at: DateTime<Utc>,
+            limit: usize,
+        ) -> Result<Vec<Certificate>, RepositoryError> {
+            self.0.find_expiring(at, limit).await
+        }
+
+        async fn find_page(
+            &self,
+            account_id: u32,
+            query: &CertificateQuery,
+        ) -> Result<CertificatePage, RepositoryError> {
+            self.0.find_page(account_id, query).await
+        }
+    }
+
+    /// Audit log that is unavailable
+    struct UnavailableAuditRepository;
+
+    #[async_trait]
+    impl AuditRepository for UnavailableAuditRepository {
+        async fn append(&self, _: &AuditEntry) -> Result<bool, RepositoryError> {
+            Err(RepositoryError("audit log is unavailable".to_string()))
+        }
+
+        async fn find_last(&self, _: Uuid) -> Result<Option<AuditEntry>, RepositoryError> {
+            Err(RepositoryError("audit log is unavailable".to_string()))
+        }
+
+        async fn find_by_certificate_id(
+            &self,
+            _: Uuid,
+        ) -> Result<Vec<AuditEntry>, RepositoryError> {
+            Err(RepositoryError("audit log is unavailable".to_string()))
+        }
+    }
+
+    #[actix_web::test]
+    async fn renewals_should_keep_first_valid_from_and_form_a_chain() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let first_valid_from = Utc::now().trunc_subsecs(3) - Duration::days(400);
+        let mut certificate = certificate_for(Uuid::new_v4());
+        certificate.validity = Some(Validity {
+            first_valid_from,
+            valid_from: first_valid_from,
+            valid_until: ValidUntil::Expiry(first_valid_from + Duration::days(365)),
+        });
+        let original_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{original_id}/renew"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let second: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(second["renewal_of"], original_id.to_string());
+        assert_eq!(
+            second["validity"]["first_valid_from"],
+            json!(first_valid_from)
+        );
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{original_id}/renew"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        let second_id = second["id"].as_str().unwrap();
+        let valid_until = Utc::now().trunc_subsecs(3) + Duration::days(730);
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{second_id}/renew"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({ "valid_until": valid_until }))
+            .to_request();
+        let third: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(
+            third["validity"]["valid_until"]["Expiry"],
+            json!(valid_until)
+        );
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{second_id}/renewals"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let chain: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        let ids: Vec<&str> = chain.iter().map(|c| c["id"].as_str().unwrap()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                original_id.to_string().as_str(),
+                second_id,
+                third["id"].as_str().unwrap()
+            ]
+        );
+    }
+
+    #[actix_web::test]
+    async fn renewal_that_cannot_be_linked_should_not_leave_its_successor() {
+        let repository: Arc<dyn CertificateRepository> = Arc::new(ContendedRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let user_id = certificate.recipient.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository.clone()))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!(
+                "/api/certificates/{}/renew",
+                certificate.id.as_uuid()
+            ))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        let stored = repository.find_by_user_id(20, user_id).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id.as_uuid(), certificate.id.as_uuid());
+    }
+
+    #[actix_web::test]
+    async fn chain_with_missing_successor_should_be_reported_broken() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let mut certificate = certificate_for(Uuid::new_v4());
+        certificate.renewed_by = Some(Id::parse(Uuid::new_v4()).unwrap());
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/api/certificates/{}/renewals",
+                certificate.id.as_uuid()
+            ))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[actix_web::test]
+    async fn renewal_should_succeed_when_it_cannot_be_recorded() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let audit: Arc<dyn AuditRepository> = Arc::new(UnavailableAuditRepository);
+        let certificate = certificate_for(Uuid::new_v4());
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository.clone()))
+                .app_data(web::Data::from(audit))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!(
+                "/api/certificates/{}/renew",
+                certificate.id.as_uuid()
+            ))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let successor: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        let renewed = repository
+            .find_by_id(20, certificate.id.as_uuid())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            renewed.renewed_by.unwrap().as_uuid().to_string(),
+            successor["id"].as_str().unwrap()
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use actix_web::web;
+use log::info;
+use uuid::Uuid;
+
+use crate::{
+    audit::Auditor,
+    domain::{
+        audit::{snapshot, AuditAction},
+        certificate::Certificate,
+        revocation::Revocation,
+    },
+    dto::revocation_dto::RevocationDto,
+    error::CrsError,
+    repository::CertificateRepository,
+    tenant::Tenant,
+};
+
+use super::get_certificate::find_certificate;
+
+pub async fn index(
+    tenant: Tenant,
+    auditor: Auditor,
+    path: web::Path<(Uuid,)>,
+    revocation: web::Json<RevocationDto>,
+    repository: web::Data<dyn CertificateRepository>,
+) -> Result<Certificate, CrsError> {
+    if !RevocationDto::is_valid(&revocation) {
+        return Err(CrsError::invalid("invalid revocation"));
+    }
+    let revocation = Revocation::try_from(revocation.into_inner())?;
+
+    let mut certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    let expected_version = certificate.version;
+    let before = snapshot(&certificate);
+    certificate.revoke(revocation)?;
+
+    if !repository.update(&certificate, expected_version).await? {
+        return Err(CrsError::Conflict(
+            "certificate was modified concurrently, retry".to_string(),
+        ));
+    }
+    auditor
+        .record(AuditAction::Revoke, Some(before), &certificate)
+        .await;
+    info!("Revoked certificate: {}", certificate.id.as_uuid());
+    Ok(certificate)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, web, App};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        repository::{in_memory::InMemoryCertificateRepository, CertificateRepository},
+        test_helpers::{audited, authenticated, certificate_for, TEST_API_KEY},
+    };
+
+    #[actix_web::test]
+    async fn revoke_certificate_should_show_revoked_status() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{certificate_id}/revoke"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"reason": "issued_in_error", "comment": "Wrong recipient"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .to_request();
+        let certificate: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(certificate["status"], "Revoked");
+        assert_eq!(certificate["revocation"]["reason"], "IssuedInError");
+        assert_eq!(certificate["revocation"]["comment"], "Wrong recipient");
+    }
+
+    #[actix_web::test]
+    async fn revoke_revoked_certificate_should_return_conflict() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        for expected_status in [StatusCode::OK, StatusCode::CONFLICT] {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/certificates/{certificate_id}/revoke"))
+                .insert_header((API_KEY_HEADER, TEST_API_KEY))
+                .set_json(json!({"reason": "fraud"}))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), expected_status);
+        }
+    }
+
+    #[actix_web::test]
+    async fn revoke_with_unknown_reason_should_return_bad_request() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{certificate_id}/revoke"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"reason": "bored"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn revoke_unknown_certificate_should_return_not_found() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{}/revoke", Uuid::new_v4()))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"reason": "fraud"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,45 +1,437 @@
-use actix_web::{web, Either, HttpResponse, Responder};
-use log::{error, info};
-use mongodb::Database;
+use std::collections::HashMap;
+
+use actix_web::web;
+use log::{error, info, warn};
+use serde_json::Value;
+use uuid::Uuid;
 
 use crate::{
-    db::store_one, domain::certificate::Certificate, dto::certificate_dto::CertificateDto,
-    helpers::SaveType, model::CertificateModel,
+    audit::Auditor,
+    batch::{BatchConfig, BatchReport},
+    domain::{
+        audit::AuditAction, certificate::Certificate, organization::Organization, person::Person,
+        product::Product,
+    },
+    dto::{certificate_dto::CertificateDto, recipient_dto::RecipientDto},
+    error::{CrsError, FieldError},
+    repository::{
+        CertificateRepository, OrganizationRepository, ProductRepository, RecipientRepository,
+    },
+    signing::Keyring,
+    tenant::Tenant,
 };
 
-pub async fn index(
-    certificate: web::Json<CertificateDto>,
-    data: web::Data<Option<Database>>,
-) -> impl Responder {
-    if !CertificateDto::is_valid(&certificate) {
-        Either::Right(HttpResponse::BadRequest().body("Invalid ceritificate"))
-    } else {
-        match data.into() {
-            Some(db) => {
-                if let Ok(cert_to_store) = Certificate::try_from(certificate.0) {
-                    let doc = CertificateModel::from_domain(&cert_to_store, SaveType::Insert);
-                    if let Some(database) = db.as_ref() {
-                        if let Some(insert_one_result) = store_one(database, &doc).await {
-                            info!(
-                                "The inserted record id is: {}",
-                                insert_one_result.inserted_id
-                            );
-                            return Either::Left(cert_to_store);
-                        }
-                    }
-                }
-                Either::Right(
-                    HttpResponse::InternalServerError().body("Failed to store certificate!"),
-                )
+/// Everything needed to issue certificates of the tenant
+pub(super) struct Issuer<'a> {
+    pub tenant: Tenant,
+    pub auditor: &'a Auditor,
+    pub certificates: &'a dyn CertificateRepository,
+    pub organizations: &'a dyn OrganizationRepository,
+    pub recipients: &'a dyn RecipientRepository,
+    pub products: &'a dyn ProductRepository,
+    pub keyring: Option<&'a Keyring>,
+}
+
+/// Violations of the payload recipient disagreeing with the registered one, registered
+/// details are never silently replaced by those of a payload or the other way around
+fn registered_mismatches(recipient: &RecipientDto, registered: &Person) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let mut check = |matches: bool, field: &str, value: &str, registered: &str| {
+        if !matches {
+            errors.push(FieldError::new(
+                format!("recipient.{field}"),
+                "registered",
+                value,
+                format!("must match the registered recipient, {registered}"),
+            ));
+        }
+    };
+    check(
+        recipient.first_name == registered.name.first_name,
+        "first_name",
+        &recipient.first_name,
+        &registered.name.first_name,
+    );
+    check(
+        recipient.last_name == registered.name.last_name,
+        "last_name",
+        &recipient.last_name,
+        &registered.name.last_name,
+    );
+    check(
+        recipient.email.trim().to_lowercase() == registered.email.normalized(),
+        "email",
+        &recipient.email,
+        &registered.email.as_string(),
+    );
+    errors
+}
+
+/// Organizations, recipients and products already resolved, so a batch looks each one up once
+#[derive(Default)]
+struct Resolved {
+    authorities: HashMap<Uuid, Option<Organization>>,
+    recipients: HashMap<Uuid, Person>,
+    /// Recipients not registered yet, they are registered once a certificate issued
+    /// to them is stored
+    unregistered: HashMap<Uuid, Person>,
+    products: HashMap<u32, Option<Product>>,
+}
+
+impl<'a> Issuer<'a> {
+    pub fn new(
+        tenant: Tenant,
+        auditor: &'a Auditor,
+        certificates: &'a web::Data<dyn CertificateRepository>,
+        organizations: &'a web::Data<dyn OrganizationRepository>,
+        recipients: &'a web::Data<dyn RecipientRepository>,
+        products: &'a web::Data<dyn ProductRepository>,
+        keyring: Option<&'a web::Data<Keyring>>,
+    ) -> Self {
+        Issuer {
+            tenant,
+            auditor,
+            certificates: &***certificates,
+            organizations: &***organizations,
+            recipients: &***recipients,
+            products: &***products,
+            keyring: keyring.map(|keyring| keyring.get_ref()),
+        }
+    }
+
+    /// Signs the certificate when signing is enabled
+    fn sign(&self, certificate: &mut Certificate) {
+        if let Some(keyring) = self.keyring {
+            if !keyring.sign(certificate) {
+                warn!(
+                    "No signing key for the issuer of certificate {}, it is stored unsigned",
+                    certificate.id.as_uuid()
+                );
             }
+        }
+    }
+
+    /// Finds the recipient of a certificate registered in the tenant's account, or
+    /// takes them from the dto when neither their id nor their email is known there yet.
+    ///
+    /// A payload whose email is registered under another id, or whose details differ
+    /// from the registered ones, is reported rather than issued to the registered
+    /// recipient.
+    async fn resolve_recipient(
+        &self,
+        recipient: &RecipientDto,
+        resolved: &mut Resolved,
+    ) -> Result<Result<Person, Vec<FieldError>>, CrsError> {
+        let recipient_id = recipient.id;
+        let person = match resolved.recipients.get(&recipient_id) {
+            Some(person) => person.clone(),
             None => {
-                error!("Invalid db instance");
-                Either::Right(HttpResponse::BadRequest().body("Invalid ceritificate"))
+                let person = match self
+                    .recipients
+                    .find_by_id(self.tenant.account_id(), recipient_id)
+                    .await?
+                {
+                    Some(person) => person,
+                    None => match self.new_recipient(recipient, resolved).await? {
+                        Ok(person) => person,
+                        Err(errors) => return Ok(Err(errors)),
+                    },
+                };
+                resolved.recipients.insert(recipient_id, person.clone());
+                person
+            }
+        };
+        let errors = registered_mismatches(recipient, &person);
+        Ok(if errors.is_empty() {
+            Ok(person)
+        } else {
+            Err(errors)
+        })
+    }
+
+    /// Takes an unknown recipient from the dto, unless their email is registered, or
+    /// taken by another new recipient of the batch, under another id
+    async fn new_recipient(
+        &self,
+        recipient: &RecipientDto,
+        resolved: &mut Resolved,
+    ) -> Result<Result<Person, Vec<FieldError>>, CrsError> {
+        let person = match Person::from_dto(recipient) {
+            Ok(person) => person,
+            Err(err) => {
+                return Ok(Err(vec![FieldError::new(
+                    "recipient",
+                    "format",
+                    (),
+                    err.to_string(),
+                )]))
             }
+        };
+        let email_key = person.email.normalized();
+        let registered_id = match self
+            .recipients
+            .find_by_email(self.tenant.account_id(), &person.email)
+            .await?
+        {
+            Some(registered) => Some(registered.id.as_uuid()),
+            None => resolved
+                .unregistered
+                .values()
+                .find(|other| other.email.normalized() == email_key)
+                .map(|other| other.id.as_uuid()),
+        };
+        if let Some(registered_id) = registered_id {
+            return Ok(Err(vec![FieldError::new(
+                "recipient.id",
+                "registered",
+                recipient.id,
+                format!("the email is registered to recipient {registered_id}"),
+            )]));
         }
+        resolved.unregistered.insert(recipient.id, person.clone());
+        Ok(Ok(person))
+    }
+
+    /// Registers the new recipients of stored certificates.
+    ///
+    /// The certificates are already stored, so failures are logged rather than reported.
+    async fn register_recipients(&self, certificates: &[Certificate], resolved: &mut Resolved) {
+        for certificate in certificates {
+            let Some(person) = resolved
+                .unregistered
+                .remove(&certificate.recipient.id.as_uuid())
+            else {
+                continue;
+            };
+            match self
+                .recipients
+                .register(self.tenant.account_id(), &person)
+                .await
+            {
+                Ok(registered) if registered.id.as_uuid() != person.id.as_uuid() => warn!(
+                    "Recipient {} of certificate {} was registered concurrently as {}",
+                    person.id.as_uuid(),
+                    certificate.id.as_uuid(),
+                    registered.id.as_uuid()
+                ),
+                Ok(_) => {}
+                Err(err) => error!(
+                    "Could not register recipient {} of certificate {}: {}",
+                    person.id.as_uuid(),
+                    certificate.id.as_uuid(),
+                    err
+                ),
+            }
+        }
+    }
+
+    /// Finds the product of the tenant's account a certificate is issued for,
+    /// certificates of unregistered products are issued without one
+    async fn resolve_product(
+        &self,
+        product_id: u32,
+        resolved: &mut Resolved,
+    ) -> Result<Option<Product>, CrsError> {
+        if let Some(product) = resolved.products.get(&product_id) {
+            return Ok(product.clone());
+        }
+        let product = self
+            .products
+            .find_by_id(self.tenant.account_id(), product_id)
+            .await?;
+        resolved.products.insert(product_id, product.clone());
+        Ok(product)
+    }
+
+    /// Builds a signed certificate out of a validated dto of the tenant, resolving its
+    /// product, its issuing organization and its recipient within the tenant's account
+    async fn prepare(
+        &self,
+        mut certificate: CertificateDto,
+        resolved: &mut Resolved,
+    ) -> Result<Result<Certificate, Vec<FieldError>>, CrsError> {
+        if certificate.account_id != self.tenant.account_id() {
+            return Ok(Err(vec![self
+                .tenant
+                .account_mismatch(certificate.account_id)]));
+        }
+        let product = self
+            .resolve_product(certificate.product_id, resolved)
+            .await?;
+        let violations = certificate.apply_product(product.as_ref());
+        if !violations.is_empty() {
+            return Ok(Err(violations.into_inner()));
+        }
+
+        let organization_id = certificate.organization_id;
+        let authority = match resolved.authorities.get(&organization_id) {
+            Some(authority) => authority.clone(),
+            None => {
+                let authority = self
+                    .organizations
+                    .find_by_id(self.tenant.account_id(), organization_id)
+                    .await?;
+                resolved
+                    .authorities
+                    .insert(organization_id, authority.clone());
+                authority
+            }
+        };
+        let Some(authority) = authority else {
+            return Ok(Err(vec![FieldError::new(
+                "organization_id",
+                "exists",
+                organization_id,
+                "organization does not exist",
+            )]));
+        };
+
+        let recipient = match self
+            .resolve_recipient(&certificate.recipient, resolved)
+            .await?
+        {
+            Ok(recipient) => recipient,
+            Err(errors) => return Ok(Err(errors)),
+        };
+
+        let mut certificate =
+            match Certificate::issue(certificate, recipient, authority, product.as_ref()) {
+                Ok(certificate) => certificate,
+                Err(err) => {
+                    return Ok(Err(vec![FieldError::new(
+                        "",
+                        "format",
+                        (),
+                        err.to_string(),
+                    )]))
+                }
+            };
+        self.sign(&mut certificate);
+        Ok(Ok(certificate))
+    }
+
+    /// Issues a single certificate
+    pub async fn issue(&self, certificate: CertificateDto) -> Result<Certificate, CrsError> {
+        certificate.validate()?;
+        let mut resolved = Resolved::default();
+        let certificate = self
+            .prepare(certificate, &mut resolved)
+            .await?
+            .map_err(|errors| CrsError::Validation {
+                message: "invalid certificate".to_string(),
+                errors,
+            })?;
+        self.certificates.insert(&certificate).await?;
+        self.register_recipients(std::slice::from_ref(&certificate), &mut resolved)
+            .await;
+        self.auditor
+            .record(AuditAction::Issue, None, &certificate)
+            .await;
+        info!("The inserted record id is: {}", certificate.id.as_uuid());
+        Ok(certificate)
+    }
+
+    /// Issues every valid certificate in one insert, rejected items do not fail the others
+    pub async fn issue_all(
+        &self,
+        items: impl IntoIterator<Item = (usize, Result<CertificateDto, Vec<FieldError>>)>,
+    ) -> Result<BatchReport, CrsError> {
+        let mut report = BatchReport::default();
+        let mut certificates = Vec::new();
+        let mut resolved = Resolved::default();
+        for (index, item) in items {
+            let certificate = match item {
+                Ok(certificate) => self.prepare(certificate, &mut resolved).await?,
+                Err(errors) => Err(errors),
+            };
+            match certificate {
+                Ok(certificate) => {
+                    report.created(index, certificate.id.as_uuid());
+                    certificates.push(certificate);
+                }
+                Err(errors) => report.rejected(index, errors),
+            }
+        }
+
+        self.certificates.insert_many(&certificates).await?;
+        self.register_recipients(&certificates, &mut resolved).await;
+        for certificate in &certificates {
+            self.auditor
+                .record(AuditAction::Issue, None, certificate)
+                .await;
+        }
+        info!(
+            "Issued {} certificates in a batch, rejected {}",
+            report.created, report.rejected
+        );
+        Ok(report)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn index(
+    tenant: Tenant,
+    auditor: Auditor,
+    certificate: web::Json<CertificateDto>,
+    certificates: web::Data<dyn CertificateRepository>,
+    organizations: web::Data<dyn OrganizationRepository>,
+    recipients: web::Data<dyn RecipientRepository>,
+    products: web::Data<dyn ProductRepository>,
+    keyring: Option<web::Data<Keyring>>,
+) -> Result<Certificate, CrsError> {
+    Issuer::new(
+        tenant,
+        &auditor,
+        &certificates,
+        &organizations,
+        &recipients,
+        &products,
+        keyring.as_ref(),
+    )
+    .issue(certificate.into_inner())
+    .await
+}
+
+/// Parses a single batch item, reporting why it was rejected otherwise
+fn batch_item(item: Value) -> Result<CertificateDto, Vec<FieldError>> {
+    let certificate = serde_json::from_value::<CertificateDto>(item)
+        .map_err(|err| vec![FieldError::new("", "format", (), err.to_string())])?;
+    let violations = certificate.violations();
+    if violations.is_empty() {
+        Ok(certificate)
+    } else {
+        Err(violations.into_inner())
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+pub async fn batch(
+    tenant: Tenant,
+    auditor: Auditor,
+    items: web::Json<Vec<Value>>,
+    certificates: web::Data<dyn CertificateRepository>,
+    organizations: web::Data<dyn OrganizationRepository>,
+    recipients: web::Data<dyn RecipientRepository>,
+    products: web::Data<dyn ProductRepository>,
+    keyring: Option<web::Data<Keyring>>,
+    config: Option<web::Data<BatchConfig>>,
+) -> Result<BatchReport, CrsError> {
+    BatchConfig::or_default(config.as_ref().map(|config| config.get_ref()))
+        .ensure_fits(items.len())?;
+    let items = items.into_inner().into_iter().map(batch_item).enumerate();
+    Issuer::new(
+        tenant,
+        &auditor,
+        &certificates,
+        &organizations,
+        &recipients,
+        &products,
+        keyring.as_ref(),
+    )
+    .issue_all(items)
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use actix_web::{
@@ -48,66 +440,321 @@ mod tests {
         test, web, App,
     };
 
-    use crate::{crs_service, db::init_db, model::CertificateModel};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        batch::BatchConfig,
+        crs_service,
+        test_helpers::{
+            authenticated, repositories_with_organization, OTHER_ACCOUNT_API_KEY, TEST_API_KEY,
+        },
+    };
 
     #[actix_web::test]
-    #[ignore = "requires MongoDB instance running"]
     async fn post_valid_certificate() {
-        let db = init_db().await.expect("failed to connect");
-
-        // Clear any data currently in the users collection.
-        db.collection::<CertificateModel>("certificates")
-            .drop()
-            .await
-            .expect("drop collection should succeed");
+        let (repositories, organization_id) = repositories_with_organization().await;
 
         let app = test::init_service(
             App::new()
-                .app_data(web::Data::new(Some(db)))
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
                 .configure(crs_service),
         )
         .await;
 
-        let payload = r#"{"user_id":"a2382a52-2e84-4db6-bcd9-4fe378a92b10","account_id":20,"product_id":15,"metadata":{"score":100,"progress":100,"acquired_date":"2023-11-28T12:45:59.324310806Z"}}"#.as_bytes();
+        let payload = format!(
+            r#"{{"account_id":20,"product_id":15,"organization_id":"{organization_id}","recipient":{{"id":"a2382a52-2e84-4db6-bcd9-4fe378a92b10","first_name":"John","last_name":"Doe","email":"john.doe@email.com","phone":"12345678"}},"metadata":{{"score":100,"progress":1.0,"acquired_date":"2023-11-28T12:45:59.324310806Z"}}}}"#
+        );
 
         let req = test::TestRequest::post()
             .insert_header((header::CONTENT_TYPE, "application/json"))
             .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
             .set_payload(payload)
             .to_request();
 
         let resp = app.call(req).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::OK);
+        let certificate: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(certificate["authority"]["name"], "Acme Academy");
     }
 
     #[actix_web::test]
-    #[ignore = "requires MongoDB instance running"]
     async fn post_invalid_certificate_should_return_bad_request() {
-        let db = init_db().await.expect("failed to connect");
+        let (repositories, organization_id) = repositories_with_organization().await;
 
-        // Clear any data currently in the users collection.
-        db.collection::<CertificateModel>("certificates")
-            .drop()
-            .await
-            .expect("drop collection should succeed");
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+        let payload = format!(
+            r#"{{"account_id":20,"product_id":15,"organization_id":"{organization_id}","recipient":{{"id":"00000000-0000-0000-0000-000000000000","first_name":"John","last_name":"Doe","email":"john.doe@email.com","phone":"12345678"}},"metadata":{{"score":100,"progress":1.0,"acquired_date":"2023-11-28T12:45:59.324310806Z"}}}}"#
+        );
+
+        let req = test::TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_payload(payload)
+            .to_request();
+
+        let resp = app.call(req).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn post_certificate_of_unknown_organization_should_return_bad_request() {
+        let (repositories, _) = repositories_with_organization().await;
 
         let app = test::init_service(
             App::new()
-                .app_data(web::Data::new(Some(db)))
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
                 .configure(crs_service),
         )
         .await;
-        let payload = r#"{"user_id":"00000000-0000-0000-0000-000000000000","account_id":20,"product_id":15,"metadata":{"score":100,"progress":100,"acquired_date":"2023-11-28T12:45:59.324310806Z"}}"#.as_bytes();
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({
+                "account_id": 20,
+                "product_id": 15,
+                "organization_id": Uuid::new_v4(),
+                "recipient": {"id": Uuid::new_v4(), "first_name": "John", "last_name": "Doe", "email": "john.doe@email.com", "phone": "12345678"},
+                "metadata": {"score": 100, "progress": 1.0}
+            }))
+            .to_request();
+        let resp = app.call(req).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"][0]["field"], "organization_id");
+        assert_eq!(problem["errors"][0]["rule"], "exists");
+    }
+
+    #[actix_web::test]
+    async fn post_certificate_of_organization_of_other_account_should_return_bad_request() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, OTHER_ACCOUNT_API_KEY))
+            .set_json(json!({
+                "account_id": 99,
+                "product_id": 15,
+                "organization_id": organization_id,
+                "recipient": {"id": Uuid::new_v4(), "first_name": "John", "last_name": "Doe", "email": "john.doe@email.com", "phone": "12345678"},
+                "metadata": {"score": 100, "progress": 1.0}
+            }))
+            .to_request();
+        let resp = app.call(req).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"][0]["field"], "organization_id");
+        assert_eq!(problem["errors"][0]["rule"], "exists");
+    }
+
+    #[actix_web::test]
+    async fn post_invalid_certificate_should_report_every_violation() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+        let payload = format!(
+            r#"{{"account_id":0,"product_id":15,"organization_id":"{organization_id}","recipient":{{"id":"a2382a52-2e84-4db6-bcd9-4fe378a92b10","first_name":"John","last_name":"Doe","email":"john.doe","phone":"12345678"}},"metadata":{{"score":100,"progress":1.0,"accreditation":{{"name":"ISO","institution":"Board","start_date":"2023-11-28T12:45:59Z","status":"approved"}}}}}}"#
+        );
 
         let req = test::TestRequest::post()
             .insert_header((header::CONTENT_TYPE, "application/json"))
             .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
             .set_payload(payload)
             .to_request();
+        let resp = app.call(req).await.unwrap();
 
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        let violations: Vec<(&str, &str)> = problem["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|err| {
+                (
+                    err["field"].as_str().unwrap(),
+                    err["rule"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                ("account_id", "required"),
+                ("recipient.email", "email"),
+                ("metadata.accreditation.status", "accreditation_status"),
+            ]
+        );
+        assert_eq!(problem["errors"][1]["value"], "john.doe");
+    }
+
+    #[actix_web::test]
+    async fn post_failed_assessment_should_be_refused() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+        let mut payload = json!({"account_id":20,"product_id":15,"organization_id":organization_id,"recipient":{"id":Uuid::new_v4(),"first_name":"John","last_name":"Doe","email":"john.doe@email.com","phone":"12345678"},"metadata":{"score":45,"progress":1.0,"score_scale":{"max":60,"passing_score":50}}});
+
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(&payload)
+            .to_request();
         let resp = app.call(req).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(problem["errors"][0]["field"], "metadata.score");
+        assert_eq!(problem["errors"][0]["rule"], "passing_score");
+
+        payload["metadata"]["score"] = json!(50);
+        let req = test::TestRequest::post()
+            .uri("/api/certificates")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(&payload)
+            .to_request();
+        let resp = app.call(req).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let certificate: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(certificate["assessment"]["result"], "Pass");
+        assert_eq!(certificate["assessment"]["score"]["max"], 60);
+    }
+
+    #[actix_web::test]
+    async fn post_batch_should_issue_valid_items_and_report_rejected_ones() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+        let user_id = Uuid::new_v4();
+        let valid = json!({"account_id":20,"product_id":15,"organization_id":organization_id,"recipient":{"id":user_id,"first_name":"John","last_name":"Doe","email":"john.doe@email.com","phone":"12345678"},"metadata":{"score":100,"progress":1.0}});
+        let mut invalid = valid.clone();
+        invalid["metadata"]["score"] = json!(120);
+
+        let req = test::TestRequest::post()
+            .uri("/api/certificates/batch")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!([valid, invalid, {"account_id": "twenty"}, valid]))
+            .to_request();
+        let resp = app.call(req).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let report: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(report["created"], 2);
+        assert_eq!(report["rejected"], 2);
+        let statuses: Vec<&str> = report["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["status"].as_str().unwrap())
+            .collect();
+        assert_eq!(statuses, vec!["created", "rejected", "rejected", "created"]);
+        assert_eq!(report["items"][1]["errors"][0]["field"], "metadata.score");
+        assert_eq!(report["items"][2]["errors"][0]["rule"], "format");
+
+        let stored = repositories
+            .certificates
+            .find_by_user_id(20, user_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 2);
+    }
+
+    #[actix_web::test]
+    async fn post_batch_should_register_only_recipients_of_issued_items() {
+        let (repositories, organization_id) = repositories_with_organization().await;
+
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+        let issued_id = Uuid::new_v4();
+        let rejected_id = Uuid::new_v4();
+        let valid = json!({"account_id":20,"product_id":15,"organization_id":organization_id,"recipient":{"id":issued_id,"first_name":"John","last_name":"Doe","email":"john.doe@email.com","phone":"12345678"},"metadata":{"score":100,"progress":1.0}});
+        let invalid = json!({"account_id":20,"product_id":15,"organization_id":organization_id,"recipient":{"id":rejected_id,"first_name":"Jane","last_name":"Doe","email":"jane.doe@email.com","phone":"12345678"},"metadata":{"score":120,"progress":1.0}});
+
+        let req = test::TestRequest::post()
+            .uri("/api/certificates/batch")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!([valid, invalid]))
+            .to_request();
+        let resp = app.call(req).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let report: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(report["created"], 1);
+        assert_eq!(report["rejected"], 1);
+        let registered = repositories.recipients.find_all(20).await.unwrap();
+        let registered_ids: Vec<Uuid> = registered
+            .iter()
+            .map(|person| person.id.as_uuid())
+            .collect();
+        assert_eq!(registered_ids, vec![issued_id]);
+    }
+
+    #[actix_web::test]
+    async fn post_batch_over_the_limit_should_be_rejected() {
+        let (repositories, _) = repositories_with_organization().await;
+
+        let app = test::init_service(
+            App::new()
+                .configure(|cfg| repositories.configure(cfg))
+                .app_data(web::Data::new(BatchConfig { max_size: 1 }))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/certificates/batch")
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!([{}, {}]))
+            .to_request();
+        let resp = app.call(req).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
 }
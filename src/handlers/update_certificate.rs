@@ -0,0 +1,308 @@
+use actix_web::{
+    http::header::{Header, IfMatch},
+    web, HttpRequest,
+};
+use log::info;
+use uuid::Uuid;
+
+use crate::{
+    audit::Auditor,
+    domain::{
+        audit::{snapshot, AuditAction},
+        certificate::Certificate,
+    },
+    dto::certificate_update_dto::CertificateUpdateDto,
+    error::CrsError,
+    repository::CertificateRepository,
+    signing::{sign_changed, Keyring},
+    tenant::Tenant,
+};
+
+use super::get_certificate::find_certificate;
+
+fn version_mismatch() -> CrsError {
+    CrsError::PreconditionFailed("certificate version mismatch".to_string())
+}
+
+/// Version the client expects to update, taken from `If-Match` or the body `version`.
+///
+/// `Ok(None)` means the client sent `If-Match: *` and accepts any current version.
+fn expected_version(
+    req: &HttpRequest,
+    update: &CertificateUpdateDto,
+) -> Result<Option<u32>, CrsError> {
+    match IfMatch::parse(req) {
+        Ok(IfMatch::Any) => Ok(None),
+        Ok(IfMatch::Items(tags)) if !tags.is_empty() => tags
+            .iter()
+            .find_map(|tag| tag.tag().parse::<u32>().ok())
+            .map(Some)
+            .ok_or_else(version_mismatch),
+        _ => update.version.map(Some).ok_or_else(|| {
+            CrsError::PreconditionRequired(
+                "updates require an If-Match header or a version".to_string(),
+            )
+        }),
+    }
+}
+
+pub async fn index(
+    tenant: Tenant,
+    auditor: Auditor,
+    req: HttpRequest,
+    path: web::Path<(Uuid,)>,
+    update: web::Json<CertificateUpdateDto>,
+    repository: web::Data<dyn CertificateRepository>,
+    keyring: Option<web::Data<Keyring>>,
+) -> Result<Certificate, CrsError> {
+    let expected_version = expected_version(&req, &update)?;
+    update.validate()?;
+
+    let mut certificate = find_certificate(&repository, tenant, path.into_inner().0).await?;
+    let current_version = certificate.version;
+    if expected_version.is_some_and(|expected| expected != current_version) {
+        return Err(version_mismatch());
+    }
+
+    let before = snapshot(&certificate);
+    certificate.apply(update.into_inner())?;
+    // the signature covers the assessment and validity, so it must follow the update
+    sign_changed(
+        keyring.as_ref().map(|keyring| keyring.get_ref()),
+        &mut certificate,
+    );
+
+    if !repository.update(&certificate, current_version).await? {
+        return Err(version_mismatch());
+    }
+    auditor
+        .record(AuditAction::Update, Some(before), &certificate)
+        .await;
+    info!("Updated certificate: {}", certificate.id.as_uuid());
+    Ok(certificate)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        domain::revocation::{Revocation, RevocationReason},
+        repository::{in_memory::InMemoryCertificateRepository, CertificateRepository},
+        test_helpers::{audited, authenticated, certificate_for, TEST_API_KEY},
+    };
+
+    async fn stored_certificate(repository: &Arc<dyn CertificateRepository>) -> Uuid {
+        let certificate = certificate_for(Uuid::new_v4());
+        repository.insert(&certificate).await.unwrap();
+        certificate.id.as_uuid()
+    }
+
+    #[actix_web::test]
+    async fn update_certificate_should_apply_changes_and_bump_version() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate_id = stored_certificate(&repository).await;
+        let created_date = repository
+            .find_by_id(20, certificate_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .created_date;
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository.clone()))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((header::IF_MATCH, "\"1\""))
+            .set_json(json!({"progress": 0.5, "description": "Halfway there"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::ETAG).unwrap(), "\"2\"");
+
+        let stored = repository
+            .find_by_id(20, certificate_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version, 2);
+        assert_eq!(stored.assessment.progress, 0.5);
+        assert_eq!(stored.description, "Halfway there");
+        assert_eq!(stored.created_date, created_date);
+        assert!(stored.updated_date.is_some());
+    }
+
+    #[actix_web::test]
+    async fn update_certificate_with_stale_version_should_fail_precondition() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate_id = stored_certificate(&repository).await;
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let first = test::TestRequest::put()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"score": 90, "version": 1}))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, first).await.status(),
+            StatusCode::OK
+        );
+
+        let second = test::TestRequest::put()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((header::IF_MATCH, "\"1\""))
+            .set_json(json!({"score": 80}))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, second).await.status(),
+            StatusCode::PRECONDITION_FAILED
+        );
+    }
+
+    #[actix_web::test]
+    async fn update_certificate_without_version_should_require_precondition() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate_id = stored_certificate(&repository).await;
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"progress": 0.5}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::PRECONDITION_REQUIRED);
+    }
+
+    #[actix_web::test]
+    async fn update_certificate_with_invalid_progress_should_return_bad_request() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate_id = stored_certificate(&repository).await;
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/certificates/{certificate_id}"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((header::IF_MATCH, "\"1\""))
+            .set_json(json!({"progress": 1.5}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn update_revoked_certificate_should_conflict() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let mut certificate = certificate_for(Uuid::new_v4());
+        certificate
+            .revoke(Revocation {
+                reason: RevocationReason::IssuedInError,
+                revoked_at: Utc::now(),
+                comment: None,
+            })
+            .unwrap();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository.clone()))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/certificates/{}", certificate.id.as_uuid()))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((header::IF_MATCH, "*"))
+            .set_json(json!({"progress": 0.5}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let stored = repository
+            .find_by_id(20, certificate.id.as_uuid())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version, certificate.version);
+    }
+
+    #[actix_web::test]
+    async fn update_unknown_certificate_should_return_not_found() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/certificates/{}", Uuid::new_v4()))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .insert_header((header::IF_MATCH, "*"))
+            .set_json(json!({"progress": 0.5}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,248 @@
+use actix_web::web;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{base::Id, signature::SignatureStatus, verification::Verification},
+    error::CrsError,
+    repository::CertificateRepository,
+    signing::Keyring,
+};
+
+pub async fn by_id(
+    path: web::Path<(Uuid,)>,
+    repository: web::Data<dyn CertificateRepository>,
+    keyring: Option<web::Data<Keyring>>,
+) -> Result<Verification, CrsError> {
+    let certificate_id = Id::parse(path.into_inner().0)?;
+
+    // verification is public, so it looks in every account, and unknown certificates
+    // are a verdict of their own rather than an error
+    let Some(certificate) = repository
+        .find_by_id_across_accounts(certificate_id.as_uuid())
+        .await?
+    else {
+        return Ok(Verification::unknown(certificate_id.as_uuid()));
+    };
+    let signature = match keyring {
+        Some(keyring) => keyring.verify(&certificate),
+        None if certificate.signature.is_none() => SignatureStatus::Unsigned,
+        None => SignatureStatus::Unverifiable,
+    };
+    Ok(Verification::of(&certificate, signature, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, web, App};
+    use chrono::{Duration, Utc};
+    use ed25519_dalek::SigningKey;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        auth::API_KEY_HEADER,
+        crs_service,
+        domain::validity::{ValidUntil, Validity},
+        repository::{in_memory::InMemoryCertificateRepository, CertificateRepository},
+        signing::Keyring,
+        test_helpers::{audited, authenticated, certificate_for, TEST_API_KEY},
+    };
+
+    #[actix_web::test]
+    async fn verify_certificate_should_not_expose_recipient() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let mut certificate = certificate_for(Uuid::new_v4());
+        certificate.name = "Rust fundamentals".to_string();
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/verify/{certificate_id}"))
+            .to_request();
+        let verification: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(verification["verdict"], "Valid");
+        assert_eq!(verification["name"], "Rust fundamentals");
+        assert!(!verification.to_string().contains("john.doe@email.com"));
+        assert!(!verification.to_string().contains("12345678"));
+    }
+
+    #[actix_web::test]
+    async fn verify_expired_certificate() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let mut certificate = certificate_for(Uuid::new_v4());
+        let issued = Utc::now() - Duration::days(400);
+        certificate.validity = Some(Validity {
+            first_valid_from: issued,
+            valid_from: issued,
+            valid_until: ValidUntil::Expiry(issued + Duration::days(365)),
+        });
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/verify/{certificate_id}"))
+            .to_request();
+        let verification: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(verification["verdict"], "Expired");
+        assert!(verification["valid_until"].is_string());
+    }
+
+    #[actix_web::test]
+    async fn verify_revoked_certificate() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let certificate = certificate_for(Uuid::new_v4());
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/certificates/{certificate_id}/revoke"))
+            .insert_header((API_KEY_HEADER, TEST_API_KEY))
+            .set_json(json!({"reason": "issued_in_error"}))
+            .to_request();
+        test::call_service(&app, req).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/verify/{certificate_id}"))
+            .to_request();
+        let verification: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(verification["verdict"], "Revoked");
+    }
+
+    #[actix_web::test]
+    async fn verify_unknown_certificate() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .configure(authenticated)
+                .configure(audited)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/verify/{}", Uuid::new_v4()))
+            .to_request();
+        let verification: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(verification["verdict"], "Unknown");
+        assert!(verification.get("issuer").is_none());
+    }
+
+    #[actix_web::test]
+    async fn verify_signed_certificate() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let keyring = Keyring::with_default_key(SigningKey::from_bytes(&[7u8; 32]));
+        let mut certificate = certificate_for(Uuid::new_v4());
+        keyring.sign(&mut certificate);
+        let certificate_id = certificate.id.as_uuid();
+        repository.insert(&certificate).await.unwrap();
+
+        let mut forged = certificate_for(Uuid::new_v4());
+        keyring.sign(&mut forged);
+        forged.description = "Tampered".to_string();
+        let forged_id = forged.id.as_uuid();
+        repository.insert(&forged).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .app_data(web::Data::new(keyring))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/verify/{certificate_id}"))
+            .to_request();
+        let verification: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(verification["verdict"], "Valid");
+        assert_eq!(verification["signature"], "Valid");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/verify/{forged_id}"))
+            .to_request();
+        let verification: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(verification["verdict"], "Invalid");
+        assert_eq!(verification["signature"], "Invalid");
+    }
+
+    #[actix_web::test]
+    async fn verify_unsigned_certificates_of_issuer_with_key() {
+        let repository: Arc<dyn CertificateRepository> =
+            Arc::new(InMemoryCertificateRepository::default());
+        let keyring = Keyring::with_default_key(SigningKey::from_bytes(&[7u8; 32]));
+        // issued before signing was enabled for its issuer
+        let unsigned = certificate_for(Uuid::new_v4());
+        let unsigned_id = unsigned.id.as_uuid();
+        repository.insert(&unsigned).await.unwrap();
+
+        let mut stripped = certificate_for(Uuid::new_v4());
+        keyring.sign(&mut stripped);
+        stripped.signature = None;
+        let stripped_id = stripped.id.as_uuid();
+        repository.insert(&stripped).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(repository))
+                .app_data(web::Data::new(keyring))
+                .configure(authenticated)
+                .configure(crs_service),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/verify/{unsigned_id}"))
+            .to_request();
+        let verification: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(verification["signature"], "Unsigned");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/verify/{stripped_id}"))
+            .to_request();
+        let verification: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(verification["verdict"], "Invalid");
+        assert_eq!(verification["signature"], "Invalid");
+    }
+}
@@ -1,4 +1,26 @@
+use actix_web::{
+    body::BoxBody,
+    http::header::{ContentType, TryIntoHeaderValue},
+    HttpResponse,
+};
+use serde::Serialize;
+
 pub enum SaveType {
     Insert,
     Update,
 }
+
+pub fn respond_with_json<T: Serialize>(obj: T) -> HttpResponse<BoxBody> {
+    respond_with_json_as(obj, ContentType::json())
+}
+
+/// Responds with a JSON body served under a more specific media type
+pub fn respond_with_json_as<T: Serialize>(
+    obj: T,
+    content_type: impl TryIntoHeaderValue,
+) -> HttpResponse<BoxBody> {
+    match serde_json::to_string(&obj) {
+        Ok(body) => HttpResponse::Ok().content_type(content_type).body(body),
+        Err(_) => HttpResponse::BadRequest().body("Unable to serialize the response"),
+    }
+}
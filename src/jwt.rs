@@ -0,0 +1,389 @@
+use std::{error::Error, fs};
+
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{AlgorithmParameters, JwkSet, PublicKeyUse},
+    Algorithm, DecodingKey, Validation,
+};
+use log::{info, warn};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::{auth::Principal, domain::api_key::Scope};
+
+/// Algorithms accepted for RSA keys
+const RSA_ALGORITHMS: [Algorithm; 6] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+];
+/// Algorithms accepted for elliptic curve keys
+const EC_ALGORITHMS: [Algorithm; 2] = [Algorithm::ES256, Algorithm::ES384];
+/// Algorithms accepted for Ed25519 keys
+const ED_ALGORITHMS: [Algorithm; 1] = [Algorithm::EdDSA];
+
+/// Scopes of subjects holding the admin role, within their account
+const ADMIN_SCOPES: [Scope; 5] = [
+    Scope::CertificatesRead,
+    Scope::CertificatesIssue,
+    Scope::CertificatesUpdate,
+    Scope::CertificatesRevoke,
+    Scope::RegistryRead,
+];
+
+#[derive(Debug)]
+pub struct JwtError(pub String);
+
+impl Error for JwtError {}
+
+impl std::fmt::Display for JwtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid bearer token: {}", self.0)
+    }
+}
+
+/// Names of the claims a subject is read from
+#[derive(Debug, Clone)]
+pub struct ClaimNames {
+    /// Claim holding the id of the user, a UUID
+    pub user_id: String,
+    /// Claim holding the account of the user, a number
+    pub account_id: String,
+    /// Claim holding the roles of the user, a list or a space separated string
+    pub roles: String,
+}
+
+impl Default for ClaimNames {
+    fn default() -> Self {
+        ClaimNames {
+            user_id: "sub".to_string(),
+            account_id: "account_id".to_string(),
+            roles: "roles".to_string(),
+        }
+    }
+}
+
+/// A public key tokens can be signed with
+struct VerificationKey {
+    key_id: Option<String>,
+    key: DecodingKey,
+    algorithms: Vec<Algorithm>,
+}
+
+/// Verifies the bearer tokens issued by the single sign-on and maps their claims to
+/// a [`Principal`].
+///
+/// Subjects holding the admin role act on every certificate of their account, any
+/// other subject can only read its own certificates.
+pub struct JwtVerifier {
+    keys: Vec<VerificationKey>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    claims: ClaimNames,
+    admin_role: String,
+}
+
+impl JwtVerifier {
+    fn new(keys: Vec<VerificationKey>) -> Result<Self, JwtError> {
+        if keys.is_empty() {
+            return Err(JwtError("no usable verification key".to_string()));
+        }
+        Ok(JwtVerifier {
+            keys,
+            issuer: None,
+            audience: None,
+            claims: ClaimNames::default(),
+            admin_role: "admin".to_string(),
+        })
+    }
+
+    /// Builds a verifier out of a JWKS document, keys for symmetric algorithms are
+    /// ignored since their secret would have to be shared
+    pub fn from_jwks(content: &str) -> Result<Self, JwtError> {
+        let jwks: JwkSet =
+            serde_json::from_str(content).map_err(|err| JwtError(err.to_string()))?;
+        let mut keys = Vec::new();
+        for jwk in &jwks.keys {
+            let family: &[Algorithm] = match &jwk.algorithm {
+                AlgorithmParameters::RSA(_) => &RSA_ALGORITHMS,
+                AlgorithmParameters::EllipticCurve(_) => &EC_ALGORITHMS,
+                AlgorithmParameters::OctetKeyPair(_) => &ED_ALGORITHMS,
+                AlgorithmParameters::OctetKey(_) => {
+                    warn!("Ignoring symmetric JWKS key {:?}", jwk.common.key_id);
+                    continue;
+                }
+            };
+            if jwk.common.public_key_use == Some(PublicKeyUse::Encryption) {
+                continue;
+            }
+            // a key announcing its algorithm is only accepted with that algorithm
+            let algorithms = match jwk
+                .common
+                .key_algorithm
+                .map(|alg| alg.to_string().parse::<Algorithm>())
+            {
+                Some(Ok(alg)) if family.contains(&alg) => vec![alg],
+                Some(_) => {
+                    warn!(
+                        "Ignoring JWKS key {:?} of unsupported algorithm",
+                        jwk.common.key_id
+                    );
+                    continue;
+                }
+                None => family.to_vec(),
+            };
+            keys.push(VerificationKey {
+                key_id: jwk.common.key_id.clone(),
+                key: DecodingKey::from_jwk(jwk).map_err(|err| JwtError(err.to_string()))?,
+                algorithms,
+            });
+        }
+        JwtVerifier::new(keys)
+    }
+
+    /// Builds a verifier out of a PEM encoded RSA, elliptic curve or Ed25519 public key
+    pub fn from_pem(content: &str) -> Result<Self, JwtError> {
+        let pem = content.as_bytes();
+        let (key, algorithms) = if let Ok(key) = DecodingKey::from_rsa_pem(pem) {
+            (key, RSA_ALGORITHMS.to_vec())
+        } else if let Ok(key) = DecodingKey::from_ec_pem(pem) {
+            (key, EC_ALGORITHMS.to_vec())
+        } else if let Ok(key) = DecodingKey::from_ed_pem(pem) {
+            (key, ED_ALGORITHMS.to_vec())
+        } else {
+            return Err(JwtError(
+                "PEM must hold an RSA, EC or Ed25519 public key".to_string(),
+            ));
+        };
+        JwtVerifier::new(vec![VerificationKey {
+            key_id: None,
+            key,
+            algorithms,
+        }])
+    }
+
+    /// Only accepts tokens of the given issuer
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Only accepts tokens issued for the given audience
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    pub fn with_claims(mut self, claims: ClaimNames) -> Self {
+        self.claims = claims;
+        self
+    }
+
+    pub fn with_admin_role(mut self, admin_role: impl Into<String>) -> Self {
+        self.admin_role = admin_role.into();
+        self
+    }
+
+    /// Loads the verification keys from the environment.
+    ///
+    /// `CRS_JWT_JWKS_FILE` points to a JWKS document and `CRS_JWT_PEM_FILE` to a PEM
+    /// public key. `CRS_JWT_ISSUER` and `CRS_JWT_AUDIENCE` restrict the accepted tokens,
+    /// `CRS_JWT_USER_CLAIM`, `CRS_JWT_ACCOUNT_CLAIM` and `CRS_JWT_ROLES_CLAIM` rename the
+    /// claims and `CRS_JWT_ADMIN_ROLE` the admin role. Returns `None` when no key is
+    /// configured, in which case bearer tokens are refused.
+    pub fn from_env() -> Result<Option<JwtVerifier>, JwtError> {
+        let read = |path: String| {
+            fs::read_to_string(&path).map_err(|err| JwtError(format!("{}: {}", path, err)))
+        };
+        let verifier = if let Ok(path) = dotenvy::var("CRS_JWT_JWKS_FILE") {
+            JwtVerifier::from_jwks(&read(path)?)?
+        } else if let Ok(path) = dotenvy::var("CRS_JWT_PEM_FILE") {
+            JwtVerifier::from_pem(&read(path)?)?
+        } else {
+            return Ok(None);
+        };
+        let defaults = ClaimNames::default();
+        let mut verifier = verifier.with_claims(ClaimNames {
+            user_id: dotenvy::var("CRS_JWT_USER_CLAIM").unwrap_or(defaults.user_id),
+            account_id: dotenvy::var("CRS_JWT_ACCOUNT_CLAIM").unwrap_or(defaults.account_id),
+            roles: dotenvy::var("CRS_JWT_ROLES_CLAIM").unwrap_or(defaults.roles),
+        });
+        if let Ok(issuer) = dotenvy::var("CRS_JWT_ISSUER") {
+            verifier = verifier.with_issuer(issuer);
+        }
+        if let Ok(audience) = dotenvy::var("CRS_JWT_AUDIENCE") {
+            verifier = verifier.with_audience(audience);
+        }
+        if let Ok(admin_role) = dotenvy::var("CRS_JWT_ADMIN_ROLE") {
+            verifier = verifier.with_admin_role(admin_role);
+        }
+        info!("Bearer token authentication is enabled");
+        Ok(Some(verifier))
+    }
+
+    /// Verifies the signature and validity of a token and maps its claims to the
+    /// principal it authenticates
+    pub fn verify(&self, token: &str) -> Result<Principal, JwtError> {
+        let header = decode_header(token).map_err(|err| JwtError(err.to_string()))?;
+        let key = self
+            .keys
+            .iter()
+            .filter(|key| key.algorithms.contains(&header.alg))
+            .find(|key| header.kid.is_none() || key.key_id.is_none() || key.key_id == header.kid)
+            .ok_or_else(|| JwtError("no key matches the token".to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.algorithms = key.algorithms.clone();
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        let claims = decode::<Map<String, Value>>(token, &key.key, &validation)
+            .map_err(|err| JwtError(err.to_string()))?
+            .claims;
+        self.principal(&claims)
+    }
+
+    /// Maps the claims of a verified token to a principal
+    fn principal(&self, claims: &Map<String, Value>) -> Result<Principal, JwtError> {
+        let claim = |name: &str| {
+            claims
+                .get(name)
+                .ok_or_else(|| JwtError(format!("missing {name} claim")))
+        };
+        let user_id = claim(&self.claims.user_id)?
+            .as_str()
+            .and_then(|user_id| Uuid::parse_str(user_id).ok())
+            .ok_or_else(|| JwtError(format!("{} claim must be a UUID", self.claims.user_id)))?;
+        let account_id = match claim(&self.claims.account_id)? {
+            Value::Number(number) => number.as_u64(),
+            Value::String(number) => number.parse::<u64>().ok(),
+            _ => None,
+        }
+        .and_then(|account_id| u32::try_from(account_id).ok())
+        .filter(|account_id| *account_id != 0)
+        .ok_or_else(|| {
+            JwtError(format!(
+                "{} claim must be an account id",
+                self.claims.account_id
+            ))
+        })?;
+        let roles: Vec<&str> = match claims.get(&self.claims.roles) {
+            Some(Value::Array(roles)) => roles.iter().filter_map(Value::as_str).collect(),
+            Some(Value::String(roles)) => roles.split_whitespace().collect(),
+            _ => Vec::new(),
+        };
+
+        let admin = roles.contains(&self.admin_role.as_str());
+        Ok(Principal {
+            subject: format!("user:{user_id}"),
+            account_id: Some(account_id),
+            user_id: (!admin).then_some(user_id),
+            scopes: if admin {
+                ADMIN_SCOPES.to_vec()
+            } else {
+                vec![Scope::CertificatesRead]
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use chrono::Utc;
+    use ed25519_dalek::SigningKey;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        domain::api_key::Scope,
+        test_helpers::{bearer_token, jwks},
+    };
+
+    use super::JwtVerifier;
+
+    fn token(claims: serde_json::Value) -> String {
+        bearer_token(claims)
+            .strip_prefix("Bearer ")
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn token_should_authenticate_its_user() {
+        let verifier = JwtVerifier::from_jwks(&jwks()).unwrap();
+        let user_id = Uuid::new_v4();
+
+        let learner = verifier
+            .verify(&token(json!({"sub": user_id, "account_id": 20})))
+            .unwrap();
+        assert_eq!(learner.account_id, Some(20));
+        assert_eq!(learner.user_id, Some(user_id));
+        assert_eq!(learner.scopes, vec![Scope::CertificatesRead]);
+
+        let admin = verifier
+            .verify(&token(
+                json!({"sub": user_id, "account_id": "20", "roles": "staff admin"}),
+            ))
+            .unwrap();
+        assert_eq!(admin.user_id, None);
+        assert!(admin.scopes.contains(&Scope::CertificatesRevoke));
+    }
+
+    #[test]
+    fn invalid_token_should_be_refused() {
+        let verifier = JwtVerifier::from_jwks(&jwks()).unwrap().with_issuer("sso");
+        let claims = json!({"sub": Uuid::new_v4(), "account_id": 20, "iss": "sso"});
+        assert!(verifier.verify(&token(claims.clone())).is_ok());
+
+        let mut expired = claims.clone();
+        expired["exp"] = json!(Utc::now().timestamp() - 3600);
+        assert!(verifier.verify(&token(expired)).is_err());
+
+        let mut other_issuer = claims.clone();
+        other_issuer["iss"] = json!("elsewhere");
+        assert!(verifier.verify(&token(other_issuer)).is_err());
+
+        let mut tampered: Vec<String> = token(claims).split('.').map(String::from).collect();
+        tampered[1] = token(json!({"sub": Uuid::new_v4(), "account_id": 21, "iss": "sso"}))
+            .split('.')
+            .nth(1)
+            .unwrap()
+            .to_string();
+        assert!(verifier.verify(&tampered.join(".")).is_err());
+
+        let without_account = json!({"sub": Uuid::new_v4(), "iss": "sso"});
+        assert!(verifier.verify(&token(without_account)).is_err());
+    }
+
+    #[test]
+    fn pem_key_should_verify_tokens() {
+        // SubjectPublicKeyInfo of an Ed25519 key is a fixed prefix followed by the key
+        let mut der = vec![
+            0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+        ];
+        der.extend_from_slice(
+            SigningKey::from_bytes(&[9u8; 32])
+                .verifying_key()
+                .as_bytes(),
+        );
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            STANDARD.encode(der)
+        );
+        let verifier = JwtVerifier::from_pem(&pem).unwrap();
+
+        assert!(verifier
+            .verify(&token(json!({"sub": Uuid::new_v4(), "account_id": 20})))
+            .is_ok());
+        assert!(JwtVerifier::from_pem("not a key").is_err());
+    }
+}
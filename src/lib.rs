@@ -1,31 +1,349 @@
+pub mod audit;
+pub mod auth;
+pub mod batch;
 pub mod db;
 pub mod domain;
 pub mod dto;
+pub mod error;
+pub mod expiry;
+pub mod export;
 mod handlers;
 mod helpers;
+pub mod jwt;
 pub mod model;
+pub mod repository;
+pub mod signing;
+pub mod tenant;
+#[cfg(test)]
+mod test_helpers;
 
 use actix_web::{web, HttpResponse};
-use handlers::{get_certificate, store_certificate};
+use auth::Require;
+use domain::api_key::Scope;
+use handlers::{
+    accreditation, api_keys, certificate_csv, certificate_history, expire_certificates,
+    get_certificate, list_certificates, organizations, products, recipients, renew_certificate,
+    revoke_certificate, store_certificate, update_certificate, verify_certificate,
+};
 
 pub fn crs_service(cfg: &mut web::ServiceConfig) {
+    // report malformed requests as problem details too
+    cfg.app_data(web::JsonConfig::default().error_handler(error::json_error_handler))
+        .app_data(web::QueryConfig::default().error_handler(error::query_error_handler));
     // register scoped services
     cfg.service(
         web::scope("/api/certificates")
             .service(
                 web::resource("")
-                    .route(web::post().to(store_certificate::index))
+                    .route(
+                        web::get()
+                            .to(list_certificates::index)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(
+                        web::post()
+                            .to(store_certificate::index)
+                            .wrap(Require(Scope::CertificatesIssue)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/batch")
+                    .app_data(
+                        web::JsonConfig::default()
+                            .limit(batch::BATCH_PAYLOAD_LIMIT)
+                            .error_handler(error::json_error_handler),
+                    )
+                    .route(
+                        web::post()
+                            .to(store_certificate::batch)
+                            .wrap(Require(Scope::CertificatesIssue)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/csv")
+                    .route(
+                        web::get()
+                            .to(certificate_csv::export)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(
+                        web::post()
+                            .to(certificate_csv::import)
+                            .wrap(Require(Scope::CertificatesIssue)),
+                    )
                     .route(web::head().to(HttpResponse::MethodNotAllowed)),
             )
             .service(
                 web::resource("/{certificate_id}")
-                    .route(web::get().to(get_certificate::by_id))
+                    .route(
+                        web::get()
+                            .to(get_certificate::by_id)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(
+                        web::put()
+                            .to(update_certificate::index)
+                            .wrap(Require(Scope::CertificatesUpdate)),
+                    )
+                    .route(
+                        web::patch()
+                            .to(update_certificate::index)
+                            .wrap(Require(Scope::CertificatesUpdate)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{certificate_id}/vc")
+                    .route(
+                        web::get()
+                            .to(get_certificate::vc_by_id)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{certificate_id}/badge")
+                    .route(
+                        web::get()
+                            .to(get_certificate::badge_by_id)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{certificate_id}/pdf")
+                    .route(
+                        web::get()
+                            .to(get_certificate::pdf_by_id)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{certificate_id}/accreditation")
+                    .route(
+                        web::get()
+                            .to(accreditation::by_id)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(
+                        web::post()
+                            .to(accreditation::create)
+                            .wrap(Require(Scope::CertificatesUpdate)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{certificate_id}/accreditation/status")
+                    .route(
+                        web::post()
+                            .to(accreditation::change_status)
+                            .wrap(Require(Scope::CertificatesUpdate)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{certificate_id}/renew")
+                    .route(
+                        web::post()
+                            .to(renew_certificate::index)
+                            .wrap(Require(Scope::CertificatesIssue)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{certificate_id}/renewals")
+                    .route(
+                        web::get()
+                            .to(renew_certificate::chain)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{certificate_id}/history")
+                    .route(
+                        web::get()
+                            .to(certificate_history::index)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{certificate_id}/revoke")
+                    .route(
+                        web::post()
+                            .to(revoke_certificate::index)
+                            .wrap(Require(Scope::CertificatesRevoke)),
+                    )
                     .route(web::head().to(HttpResponse::MethodNotAllowed)),
             )
             .service(
                 web::resource("/user/{user_id}")
-                    .route(web::get().to(get_certificate::by_user_id))
+                    .route(
+                        web::get()
+                            .to(get_certificate::by_user_id)
+                            .wrap(Require(Scope::CertificatesRead)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            ),
+    );
+    cfg.service(
+        web::scope("/api/organizations")
+            .service(
+                web::resource("")
+                    .route(
+                        web::get()
+                            .to(organizations::index)
+                            .wrap(Require(Scope::RegistryRead)),
+                    )
+                    .route(
+                        web::post()
+                            .to(organizations::create)
+                            .wrap(Require(Scope::RegistryWrite)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{organization_id}")
+                    .route(
+                        web::get()
+                            .to(organizations::by_id)
+                            .wrap(Require(Scope::RegistryRead)),
+                    )
+                    .route(
+                        web::put()
+                            .to(organizations::update)
+                            .wrap(Require(Scope::RegistryWrite)),
+                    )
+                    .route(
+                        web::delete()
+                            .to(organizations::delete)
+                            .wrap(Require(Scope::RegistryWrite)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            ),
+    );
+    cfg.service(
+        web::scope("/api/products")
+            .service(
+                web::resource("")
+                    .route(
+                        web::get()
+                            .to(products::index)
+                            .wrap(Require(Scope::RegistryRead)),
+                    )
+                    .route(
+                        web::post()
+                            .to(products::create)
+                            .wrap(Require(Scope::RegistryWrite)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{product_id}")
+                    .route(
+                        web::get()
+                            .to(products::by_id)
+                            .wrap(Require(Scope::RegistryRead)),
+                    )
+                    .route(
+                        web::put()
+                            .to(products::update)
+                            .wrap(Require(Scope::RegistryWrite)),
+                    )
+                    .route(
+                        web::delete()
+                            .to(products::delete)
+                            .wrap(Require(Scope::RegistryWrite)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            ),
+    );
+    cfg.service(
+        web::scope("/api/recipients")
+            .service(
+                web::resource("")
+                    .route(
+                        web::get()
+                            .to(recipients::index)
+                            .wrap(Require(Scope::RegistryRead)),
+                    )
+                    .route(
+                        web::post()
+                            .to(recipients::create)
+                            .wrap(Require(Scope::RegistryWrite)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{recipient_id}")
+                    .route(
+                        web::get()
+                            .to(recipients::by_id)
+                            .wrap(Require(Scope::RegistryRead)),
+                    )
+                    .route(
+                        web::put()
+                            .to(recipients::update)
+                            .wrap(Require(Scope::RegistryWrite)),
+                    )
                     .route(web::head().to(HttpResponse::MethodNotAllowed)),
             ),
     );
+    cfg.service(
+        web::scope("/api/keys")
+            .service(
+                web::resource("")
+                    .route(
+                        web::get()
+                            .to(api_keys::index)
+                            .wrap(Require(Scope::KeysManage)),
+                    )
+                    .route(
+                        web::post()
+                            .to(api_keys::create)
+                            .wrap(Require(Scope::KeysManage)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{key_id}")
+                    .route(
+                        web::delete()
+                            .to(api_keys::delete)
+                            .wrap(Require(Scope::KeysManage)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            )
+            .service(
+                web::resource("/{key_id}/rotate")
+                    .route(
+                        web::post()
+                            .to(api_keys::rotate)
+                            .wrap(Require(Scope::KeysManage)),
+                    )
+                    .route(web::head().to(HttpResponse::MethodNotAllowed)),
+            ),
+    );
+    cfg.service(
+        web::scope("/api/jobs").service(
+            web::resource("/expiry")
+                .route(
+                    web::post()
+                        .to(expire_certificates::run)
+                        .wrap(Require(Scope::JobsRun)),
+                )
+                .route(web::head().to(HttpResponse::MethodNotAllowed)),
+        ),
+    );
+    cfg.service(
+        web::scope("/api/verify").service(
+            web::resource("/{certificate_id}")
+                .route(web::get().to(verify_certificate::by_id))
+                .route(web::head().to(HttpResponse::MethodNotAllowed)),
+        ),
+    );
 }
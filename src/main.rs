@@ -1,9 +1,20 @@
+use std::io::Error;
+
 use actix_web::{middleware, web, App, HttpServer};
-use crs::{crs_service, db};
+use crs::{
+    auth::ensure_admin_key,
+    batch::BatchConfig,
+    crs_service,
+    expiry::{spawn_expiry_job, ExpiryConfig},
+    export::pdf::PdfTemplates,
+    jwt::JwtVerifier,
+    repository,
+    signing::Keyring,
+};
 use dotenvy::dotenv;
 
-use db::init_db;
 use log::info;
+use repository::init_repositories;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -12,13 +23,65 @@ async fn main() -> std::io::Result<()> {
 
     info!("Initializing CRS!");
 
-    let db = init_db().await;
+    let repositories = init_repositories()
+        .await
+        .ok_or_else(|| Error::other("Certificate storage is unavailable"))?;
+
+    // the admin key mints the keys of every integration of its account
+    if let Ok(secret) = dotenvy::var("CRS_ADMIN_API_KEY") {
+        let account_id = dotenvy::var("CRS_ADMIN_ACCOUNT_ID")
+            .ok()
+            .and_then(|account_id| account_id.parse::<u32>().ok())
+            .filter(|account_id| *account_id > 0)
+            .ok_or_else(|| {
+                Error::other(
+                    "CRS_ADMIN_ACCOUNT_ID must be a positive number with CRS_ADMIN_API_KEY",
+                )
+            })?;
+        ensure_admin_key(repositories.api_keys.as_ref(), account_id, &secret)
+            .await
+            .map_err(|err| Error::other(err.to_string()))?;
+    }
+
+    let keyring = Keyring::from_env()
+        .map_err(|err| Error::other(err.to_string()))?
+        .map(web::Data::new);
+    let jwt_verifier = JwtVerifier::from_env()
+        .map_err(|err| Error::other(err.to_string()))?
+        .map(web::Data::new);
+    let pdf_templates = PdfTemplates::from_env()
+        .map_err(|err| Error::other(err.to_string()))?
+        .map(web::Data::new);
+    let batch_config =
+        web::Data::new(BatchConfig::from_env().map_err(|err| Error::other(err.to_string()))?);
+
+    if let Some(expiry_config) =
+        ExpiryConfig::from_env().map_err(|err| Error::other(err.to_string()))?
+    {
+        spawn_expiry_job(
+            repositories.certificates.clone(),
+            repositories.audit.clone(),
+            keyring.clone(),
+            &expiry_config,
+        );
+    }
 
     HttpServer::new(move || {
-        App::new()
+        let mut app = App::new()
+            .configure(|cfg| repositories.configure(cfg))
+            .app_data(batch_config.clone());
+        if let Some(keyring) = &keyring {
+            app = app.app_data(keyring.clone());
+        }
+        if let Some(jwt_verifier) = &jwt_verifier {
+            app = app.app_data(jwt_verifier.clone());
+        }
+        if let Some(pdf_templates) = &pdf_templates {
+            app = app.app_data(pdf_templates.clone());
+        }
+        app
             // enable logger
             .wrap(middleware::Logger::default())
-            .app_data(web::Data::new(db.clone()))
             // configure services
             .configure(crs_service)
     })
@@ -1,50 +1,479 @@
 use mongodb::bson::{doc, DateTime, Uuid};
 use serde::{Deserialize, Serialize};
 
-use crate::{domain::certificate::Certificate, helpers::SaveType};
+use crate::{
+    domain::{
+        accreditation::Accreditation,
+        api_key::ApiKey,
+        audit::AuditEntry,
+        base::{Address, AssessmentResult},
+        certificate::Certificate,
+        organization::Organization,
+        person::{CertificateRecipient, Person},
+        product::Product,
+        revocation::{CertificateStatus, Revocation},
+        signature::CertificateSignature,
+        validity::{ValidUntil, Validity},
+    },
+    helpers::SaveType,
+};
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Current layout version of the certificate document.
+///
+/// Version 1 documents were written before the field was introduced and only
+/// hold the ids, the achieved progress and the dates, so they are read back
+/// without recipient details and issuing authority. Version 2 documents only
+/// hold accreditations given at issuance, which never changed status.
+pub const CERTIFICATE_SCHEMA_VERSION: u32 = 3;
+
+fn legacy_schema_version() -> u32 {
+    1
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CertificateModel {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub certificate_id: Uuid,
     pub user_id: Uuid,
     pub account_id: u32,
     pub product_id: u32,
+    #[serde(default)]
+    pub recipient: Option<PersonModel>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub authority: Option<OrganizationModel>,
+    #[serde(default)]
+    pub validity: Option<ValidityModel>,
     pub metadata: CertificateMetadataModel,
+    #[serde(default)]
+    pub accreditation: Option<AccreditationModel>,
+    #[serde(default)]
+    pub status: CertificateStatus,
+    #[serde(default)]
+    pub revocation: Option<RevocationModel>,
+    #[serde(default)]
+    pub expired_at: Option<DateTime>,
+    #[serde(default)]
+    pub renewal_of: Option<Uuid>,
+    #[serde(default)]
+    pub renewed_by: Option<Uuid>,
+    #[serde(default)]
+    pub signature: Option<SignatureModel>,
+    #[serde(default)]
+    pub signature_required: bool,
+    #[serde(default)]
+    pub version: u32,
     pub created_date: DateTime,
     pub updated_date: Option<DateTime>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CertificateMetadataModel {
     pub score: u32,
     pub progress: f32,
+    #[serde(default)]
+    pub score_scale: Option<ScoreScaleModel>,
+    #[serde(default)]
+    pub result: Option<AssessmentResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScoreScaleModel {
+    pub max: u32,
+    pub min: u32,
+    pub passing_score: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PersonModel {
+    pub first_name: String,
+    pub middle_name: Option<String>,
+    pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+}
+
+/// A recipient registered in an account, `email_key` holds the normalized email used
+/// for deduplication within the account
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecipientModel {
+    pub account_id: u32,
+    pub recipient_id: Uuid,
+    #[serde(flatten)]
+    pub person: PersonModel,
+    pub email_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrganizationModel {
+    pub organization_id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+    pub address: AddressModel,
+}
+
+/// An issuing organization registered in an account
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RegisteredOrganizationModel {
+    pub account_id: u32,
+    #[serde(flatten)]
+    pub organization: OrganizationModel,
+}
+
+/// A product registered in an account, identified within the account by the product
+/// id certificates refer to
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProductModel {
+    pub account_id: u32,
+    pub product_id: u32,
+    pub name: String,
+    pub description: String,
+    pub organization_id: Uuid,
+    pub validity_days: Option<u32>,
+    pub score_scale: Option<ScoreScaleModel>,
+    pub required_accreditation: Option<RequiredAccreditationModel>,
+}
+
+/// An API key, looked up by the hash of its secret
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKeyModel {
+    pub key_id: Uuid,
+    pub name: String,
+    pub account_id: Option<u32>,
+    pub scopes: Vec<String>,
+    pub created_date: DateTime,
+    pub rotated_date: Option<DateTime>,
+    pub secret_hash: String,
+}
+
+/// An entry of the audit log, snapshots are kept as the JSON the API serves
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEntryModel {
+    pub certificate_id: Uuid,
+    pub account_id: u32,
+    pub sequence: u64,
+    pub action: String,
+    pub actor: String,
+    pub recorded_at: DateTime,
+    pub before: Option<serde_json::Value>,
+    pub after: serde_json::Value,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RequiredAccreditationModel {
+    pub name: String,
+    pub institution: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AddressModel {
+    pub street: String,
+    pub city: String,
+    pub building_number: String,
+    pub country: String,
+    pub postal_code: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValidityModel {
+    pub first_valid_from: DateTime,
+    pub valid_from: DateTime,
+    /// `None` means the certificate is valid until the end of time
+    pub valid_until: Option<DateTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AccreditationModel {
     pub name: String,
     pub institution: String,
     pub start_date: DateTime,
     pub end_date: Option<DateTime>,
     pub status: String,
+    #[serde(default)]
+    pub status_at_issuance: Option<String>,
+    #[serde(default)]
+    pub history: Vec<AccreditationStatusChangeModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccreditationStatusChangeModel {
+    pub from: String,
+    pub to: String,
+    pub changed_at: DateTime,
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RevocationModel {
+    pub reason: String,
+    pub revoked_at: DateTime,
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignatureModel {
+    pub algorithm: String,
+    pub value: String,
 }
 
 impl CertificateModel {
     pub fn from_domain(certificate: &Certificate, save_type: SaveType) -> CertificateModel {
+        let score = certificate.assessment.score.as_ref();
         CertificateModel {
+            schema_version: CERTIFICATE_SCHEMA_VERSION,
             certificate_id: Uuid::from_uuid_1(certificate.id.as_uuid()),
             user_id: Uuid::from_uuid_1(certificate.recipient.id.as_uuid()),
             account_id: certificate.account_id,
             product_id: certificate.product_id,
+            recipient: PersonModel::from_recipient(&certificate.recipient),
+            name: certificate.name.clone(),
+            description: certificate.description.clone(),
+            authority: certificate
+                .authority
+                .as_ref()
+                .map(OrganizationModel::from_domain),
+            validity: certificate
+                .validity
+                .as_ref()
+                .map(ValidityModel::from_domain),
             metadata: CertificateMetadataModel {
-                score: 0,
+                score: score.map_or(0, |score| score.value),
                 progress: certificate.assessment.progress,
+                score_scale: score.map(|score| ScoreScaleModel {
+                    max: score.max,
+                    min: score.min,
+                    passing_score: score.passing_score,
+                }),
+                result: Some(certificate.assessment.result.clone()),
             },
+            accreditation: certificate
+                .accreditation
+                .as_ref()
+                .map(AccreditationModel::from_domain),
+            status: certificate.status.clone(),
+            revocation: certificate
+                .revocation
+                .as_ref()
+                .map(RevocationModel::from_domain),
+            expired_at: certificate.expired_at.map(DateTime::from_chrono),
+            renewal_of: certificate
+                .renewal_of
+                .as_ref()
+                .map(|id| Uuid::from_uuid_1(id.as_uuid())),
+            renewed_by: certificate
+                .renewed_by
+                .as_ref()
+                .map(|id| Uuid::from_uuid_1(id.as_uuid())),
+            signature: certificate
+                .signature
+                .as_ref()
+                .map(SignatureModel::from_domain),
+            signature_required: certificate.signature_required,
+            version: certificate.version,
             created_date: DateTime::from_chrono(certificate.created_date),
             updated_date: match save_type {
                 SaveType::Insert => None,
-                SaveType::Update => Some(DateTime::now()),
+                SaveType::Update => Some(
+                    certificate
+                        .updated_date
+                        .map_or_else(DateTime::now, DateTime::from_chrono),
+                ),
+            },
+        }
+    }
+}
+
+impl PersonModel {
+    pub fn from_domain(person: &Person) -> PersonModel {
+        PersonModel {
+            first_name: person.name.first_name.clone(),
+            middle_name: person.name.middle_name.clone(),
+            last_name: person.name.last_name.clone(),
+            email: person.email.as_string(),
+            phone: person.phone.as_ref().map(|phone| phone.as_string()),
+        }
+    }
+
+    /// Details of the recipient kept with a certificate, absent for certificates read
+    /// from version 1 documents
+    pub fn from_recipient(recipient: &CertificateRecipient) -> Option<PersonModel> {
+        match (&recipient.name, &recipient.email) {
+            (Some(name), Some(email)) => Some(PersonModel {
+                first_name: name.first_name.clone(),
+                middle_name: name.middle_name.clone(),
+                last_name: name.last_name.clone(),
+                email: email.as_string(),
+                phone: recipient.phone.as_ref().map(|phone| phone.as_string()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl RecipientModel {
+    pub fn from_domain(account_id: u32, person: &Person) -> RecipientModel {
+        RecipientModel {
+            account_id,
+            recipient_id: Uuid::from_uuid_1(person.id.as_uuid()),
+            person: PersonModel::from_domain(person),
+            email_key: person.email.normalized(),
+        }
+    }
+}
+
+impl OrganizationModel {
+    pub fn from_domain(organization: &Organization) -> OrganizationModel {
+        OrganizationModel {
+            organization_id: Uuid::from_uuid_1(organization.id.as_uuid()),
+            name: organization.name.clone(),
+            email: organization.email.as_string(),
+            phone: organization.phone.as_string(),
+            address: AddressModel::from_domain(&organization.address),
+        }
+    }
+}
+
+impl RegisteredOrganizationModel {
+    pub fn from_domain(
+        account_id: u32,
+        organization: &Organization,
+    ) -> RegisteredOrganizationModel {
+        RegisteredOrganizationModel {
+            account_id,
+            organization: OrganizationModel::from_domain(organization),
+        }
+    }
+}
+
+impl ProductModel {
+    pub fn from_domain(account_id: u32, product: &Product) -> ProductModel {
+        ProductModel {
+            account_id,
+            product_id: product.id,
+            name: product.name.clone(),
+            description: product.description.clone(),
+            organization_id: Uuid::from_uuid_1(product.organization_id.as_uuid()),
+            validity_days: product.validity_days,
+            score_scale: product.score_scale.as_ref().map(|scale| ScoreScaleModel {
+                max: scale.max,
+                min: scale.min,
+                passing_score: scale.passing_score,
+            }),
+            required_accreditation: product
+                .required_accreditation
+                .as_ref()
+                .map(|accreditation| RequiredAccreditationModel {
+                    name: accreditation.name.clone(),
+                    institution: accreditation.institution.clone(),
+                }),
+        }
+    }
+}
+
+impl ApiKeyModel {
+    pub fn from_domain(key: &ApiKey) -> ApiKeyModel {
+        ApiKeyModel {
+            key_id: Uuid::from_uuid_1(key.id.as_uuid()),
+            name: key.name.clone(),
+            account_id: key.account_id,
+            scopes: key.scopes.iter().map(|scope| scope.to_string()).collect(),
+            created_date: DateTime::from_chrono(key.created_date),
+            rotated_date: key.rotated_date.map(DateTime::from_chrono),
+            secret_hash: key.secret_hash.clone(),
+        }
+    }
+}
+
+impl AuditEntryModel {
+    pub fn from_domain(entry: &AuditEntry) -> AuditEntryModel {
+        AuditEntryModel {
+            certificate_id: Uuid::from_uuid_1(entry.certificate_id.as_uuid()),
+            account_id: entry.account_id,
+            sequence: entry.sequence,
+            action: entry.action.to_string(),
+            actor: entry.actor.clone(),
+            recorded_at: DateTime::from_chrono(entry.recorded_at),
+            before: entry.before.clone(),
+            after: entry.after.clone(),
+            previous_hash: entry.previous_hash.clone(),
+            hash: entry.hash.clone(),
+        }
+    }
+}
+
+impl AddressModel {
+    pub fn from_domain(address: &Address) -> AddressModel {
+        AddressModel {
+            street: address.street.clone(),
+            city: address.city.clone(),
+            building_number: address.building_number.clone(),
+            country: address.country.clone(),
+            postal_code: address.postal_code.clone(),
+        }
+    }
+}
+
+impl ValidityModel {
+    pub fn from_domain(validity: &Validity) -> ValidityModel {
+        ValidityModel {
+            first_valid_from: DateTime::from_chrono(validity.first_valid_from),
+            valid_from: DateTime::from_chrono(validity.valid_from),
+            valid_until: match validity.valid_until {
+                ValidUntil::EndOfTime => None,
+                ValidUntil::Expiry(expiry) => Some(DateTime::from_chrono(expiry)),
             },
         }
     }
 }
+
+impl AccreditationModel {
+    pub fn from_domain(accreditation: &Accreditation) -> AccreditationModel {
+        AccreditationModel {
+            name: accreditation.name.clone(),
+            institution: accreditation.institution.clone(),
+            start_date: DateTime::from_chrono(accreditation.start_date),
+            end_date: accreditation.end_date.map(DateTime::from_chrono),
+            status: accreditation.status.to_string(),
+            status_at_issuance: accreditation
+                .status_at_issuance
+                .as_ref()
+                .map(|status| status.to_string()),
+            history: accreditation
+                .history
+                .iter()
+                .map(|change| AccreditationStatusChangeModel {
+                    from: change.from.to_string(),
+                    to: change.to.to_string(),
+                    changed_at: DateTime::from_chrono(change.changed_at),
+                    comment: change.comment.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl RevocationModel {
+    pub fn from_domain(revocation: &Revocation) -> RevocationModel {
+        RevocationModel {
+            reason: revocation.reason.to_string(),
+            revoked_at: DateTime::from_chrono(revocation.revoked_at),
+            comment: revocation.comment.clone(),
+        }
+    }
+}
+
+impl SignatureModel {
+    pub fn from_domain(signature: &CertificateSignature) -> SignatureModel {
+        SignatureModel {
+            algorithm: signature.algorithm.clone(),
+            value: signature.value.clone(),
+        }
+    }
+}
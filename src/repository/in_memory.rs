@@ -0,0 +1,659 @@
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mongodb::bson::Uuid as BsonUuid;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        accreditation::AccreditationStatus,
+        api_key::ApiKey,
+        audit::AuditEntry,
+        base::{AssessmentResult, Email},
+        certificate::Certificate,
+        organization::Organization,
+        person::Person,
+        product::Product,
+        revocation::CertificateStatus,
+    },
+    helpers::SaveType,
+    model::{
+        ApiKeyModel, AuditEntryModel, CertificateModel, ProductModel, RecipientModel,
+        RegisteredOrganizationModel,
+    },
+};
+
+use super::{
+    query::{CertificatePage, CertificateQuery},
+    ApiKeyRepository, AuditRepository, CertificateRepository, OrganizationRepository,
+    ProductRepository, RecipientRepository, RepositoryError,
+};
+
+/// In-memory certificate repository for tests and local demos.
+///
+/// Certificates are kept as [`CertificateModel`] documents, so reads go through
+/// the same conversion as the MongoDB backend.
+#[derive(Default)]
+pub struct InMemoryCertificateRepository {
+    certificates: RwLock<Vec<CertificateModel>>,
+}
+
+impl InMemoryCertificateRepository {
+    fn poisoned<T>(_: T) -> RepositoryError {
+        RepositoryError("in-memory store lock is poisoned".to_string())
+    }
+}
+
+/// In-memory organization repository for tests and local demos
+#[derive(Default)]
+pub struct InMemoryOrganizationRepository {
+    organizations: RwLock<Vec<RegisteredOrganizationModel>>,
+}
+
+/// In-memory recipient registry for tests and local demos
+#[derive(Default)]
+pub struct InMemoryRecipientRepository {
+    recipients: RwLock<Vec<RecipientModel>>,
+}
+
+/// In-memory product catalog for tests and local demos
+#[derive(Default)]
+pub struct InMemoryProductRepository {
+    products: RwLock<Vec<ProductModel>>,
+}
+
+/// In-memory API key storage for tests and local demos
+#[derive(Default)]
+pub struct InMemoryApiKeyRepository {
+    keys: RwLock<Vec<ApiKeyModel>>,
+}
+
+/// In-memory audit log for tests and local demos
+#[derive(Default)]
+pub struct InMemoryAuditRepository {
+    entries: RwLock<Vec<AuditEntryModel>>,
+}
+
+/// Indicates if a stored certificate of the account matches the filters of a listing query
+fn matches(model: &CertificateModel, account_id: u32, query: &CertificateQuery) -> bool {
+    let created_date = model.created_date.to_chrono();
+    let certificate_id = model.certificate_id.to_uuid_1();
+    model.account_id == account_id
+        && query.product_id.is_none_or(|id| model.product_id == id)
+        && query
+            .user_id
+            .is_none_or(|id| model.user_id == BsonUuid::from_uuid_1(id))
+        && query.created_from.is_none_or(|from| created_date >= from)
+        && query.created_to.is_none_or(|to| created_date <= to)
+        && query
+            .status
+            .as_ref()
+            .is_none_or(|status| &model.status == status)
+        && query.result.as_ref().is_none_or(|result| {
+            model
+                .metadata
+                .result
+                .as_ref()
+                .unwrap_or(&AssessmentResult::Pass)
+                == result
+        })
+        && query.after.as_ref().is_none_or(|after| {
+            (created_date, certificate_id) < (after.created_date, after.certificate_id)
+        })
+}
+
+#[async_trait]
+impl CertificateRepository for InMemoryCertificateRepository {
+    async fn insert(&self, certificate: &Certificate) -> Result<(), RepositoryError> {
+        let doc = CertificateModel::from_domain(certificate, SaveType::Insert);
+        self.certificates.write().map_err(Self::poisoned)?.push(doc);
+        Ok(())
+    }
+
+    async fn insert_many(&self, certificates: &[Certificate]) -> Result<(), RepositoryError> {
+        let docs = certificates
+            .iter()
+            .map(|certificate| CertificateModel::from_domain(certificate, SaveType::Insert));
+        self.certificates
+            .write()
+            .map_err(Self::poisoned)?
+            .extend(docs);
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        certificate: &Certificate,
+        expected_version: u32,
+    ) -> Result<bool, RepositoryError> {
+        let doc = CertificateModel::from_domain(certificate, SaveType::Update);
+        let mut certificates = self.certificates.write().map_err(Self::poisoned)?;
+        match certificates.iter_mut().find(|model| {
+            model.account_id == doc.account_id
+                && model.certificate_id == doc.certificate_id
+                && model.version == expected_version
+        }) {
+            Some(model) => {
+                *model = doc;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete(&self, account_id: u32, certificate_id: Uuid) -> Result<bool, RepositoryError> {
+        let certificate_id = BsonUuid::from_uuid_1(certificate_id);
+        let mut certificates = self.certificates.write().map_err(Self::poisoned)?;
+        let count = certificates.len();
+        certificates.retain(|model| {
+            model.account_id != account_id || model.certificate_id != certificate_id
+        });
+        Ok(certificates.len() < count)
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        certificate_id: Uuid,
+    ) -> Result<Option<Certificate>, RepositoryError> {
+        Ok(self
+            .find_by_id_across_accounts(certificate_id)
+            .await?
+            .filter(|certificate| certificate.account_id == account_id))
+    }
+
+    async fn find_by_id_across_accounts(
+        &self,
+        certificate_id: Uuid,
+    ) -> Result<Option<Certificate>, RepositoryError> {
+        let certificate_id = BsonUuid::from_uuid_1(certificate_id);
+        let certificates = self.certificates.read().map_err(Self::poisoned)?;
+        match certificates
+            .iter()
+            .find(|model| model.certificate_id == certificate_id)
+        {
+            Some(model) => Ok(Some(Certificate::try_from(model.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_user_id(
+        &self,
+        account_id: u32,
+        user_id: Uuid,
+    ) -> Result<Vec<Certificate>, RepositoryError> {
+        let user_id = BsonUuid::from_uuid_1(user_id);
+        let certificates = self.certificates.read().map_err(Self::poisoned)?;
+        certificates
+            .iter()
+            .filter(|model| model.account_id == account_id && model.user_id == user_id)
+            .map(|model| Certificate::try_from(model.clone()).map_err(RepositoryError::from))
+            .collect()
+    }
+
+    async fn find_expiring(
+        &self,
+        at: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Certificate>, RepositoryError> {
+        let certificates = self.certificates.read().map_err(Self::poisoned)?;
+        certificates
+            .iter()
+            .filter(|model| {
+                let certificate_due = model.status == CertificateStatus::Active
+                    && model
+                        .validity
+                        .as_ref()
+                        .and_then(|validity| validity.valid_until)
+                        .is_some_and(|valid_until| valid_until.to_chrono() <= at);
+                let accreditation_due = model.accreditation.as_ref().is_some_and(|acc| {
+                    acc.status == AccreditationStatus::Active.to_string()
+                        && acc
+                            .end_date
+                            .is_some_and(|end_date| end_date.to_chrono() <= at)
+                });
+                certificate_due || accreditation_due
+            })
+            .take(limit)
+            .map(|model| Certificate::try_from(model.clone()).map_err(RepositoryError::from))
+            .collect()
+    }
+
+    async fn find_page(
+        &self,
+        account_id: u32,
+        query: &CertificateQuery,
+    ) -> Result<CertificatePage, RepositoryError> {
+        let certificates = self.certificates.read().map_err(Self::poisoned)?;
+        let mut matching: Vec<&CertificateModel> = certificates
+            .iter()
+            .filter(|model| matches(model, account_id, query))
+            .collect();
+        matching.sort_by_key(|model| {
+            std::cmp::Reverse((model.created_date, model.certificate_id.to_uuid_1()))
+        });
+        let certificates = matching
+            .into_iter()
+            .take(query.limit + 1)
+            .map(|model| Certificate::try_from(model.clone()).map_err(RepositoryError::from))
+            .collect::<Result<Vec<Certificate>, RepositoryError>>()?;
+        Ok(CertificatePage::from_overfetched(certificates, query.limit))
+    }
+}
+
+#[async_trait]
+impl OrganizationRepository for InMemoryOrganizationRepository {
+    async fn insert(
+        &self,
+        account_id: u32,
+        organization: &Organization,
+    ) -> Result<(), RepositoryError> {
+        let doc = RegisteredOrganizationModel::from_domain(account_id, organization);
+        self.organizations
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?
+            .push(doc);
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        account_id: u32,
+        organization: &Organization,
+    ) -> Result<bool, RepositoryError> {
+        let doc = RegisteredOrganizationModel::from_domain(account_id, organization);
+        let mut organizations = self
+            .organizations
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match organizations.iter_mut().find(|model| {
+            model.account_id == account_id
+                && model.organization.organization_id == doc.organization.organization_id
+        }) {
+            Some(model) => {
+                *model = doc;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete(
+        &self,
+        account_id: u32,
+        organization_id: Uuid,
+    ) -> Result<bool, RepositoryError> {
+        let organization_id = BsonUuid::from_uuid_1(organization_id);
+        let mut organizations = self
+            .organizations
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        let count = organizations.len();
+        organizations.retain(|model| {
+            model.account_id != account_id || model.organization.organization_id != organization_id
+        });
+        Ok(organizations.len() < count)
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        organization_id: Uuid,
+    ) -> Result<Option<Organization>, RepositoryError> {
+        let organization_id = BsonUuid::from_uuid_1(organization_id);
+        let organizations = self
+            .organizations
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match organizations.iter().find(|model| {
+            model.account_id == account_id && model.organization.organization_id == organization_id
+        }) {
+            Some(model) => Ok(Some(Organization::try_from(model.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self, account_id: u32) -> Result<Vec<Organization>, RepositoryError> {
+        let mut organizations: Vec<RegisteredOrganizationModel> = self
+            .organizations
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?
+            .iter()
+            .filter(|model| model.account_id == account_id)
+            .cloned()
+            .collect();
+        organizations.sort_by(|a, b| a.organization.name.cmp(&b.organization.name));
+        organizations
+            .into_iter()
+            .map(|model| Organization::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl RecipientRepository for InMemoryRecipientRepository {
+    async fn register(
+        &self,
+        account_id: u32,
+        recipient: &Person,
+    ) -> Result<Person, RepositoryError> {
+        let doc = RecipientModel::from_domain(account_id, recipient);
+        let mut recipients = self
+            .recipients
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match recipients
+            .iter()
+            .find(|model| model.account_id == account_id && model.email_key == doc.email_key)
+        {
+            Some(model) => Ok(Person::try_from(model.clone())?),
+            None => {
+                recipients.push(doc);
+                Ok(recipient.clone())
+            }
+        }
+    }
+
+    async fn update(&self, account_id: u32, recipient: &Person) -> Result<bool, RepositoryError> {
+        let doc = RecipientModel::from_domain(account_id, recipient);
+        let mut recipients = self
+            .recipients
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        if recipients.iter().any(|model| {
+            model.account_id == account_id
+                && model.email_key == doc.email_key
+                && model.recipient_id != doc.recipient_id
+        }) {
+            return Err(RepositoryError(format!(
+                "duplicate recipient email `{}`",
+                doc.email_key
+            )));
+        }
+        match recipients
+            .iter_mut()
+            .find(|model| model.account_id == account_id && model.recipient_id == doc.recipient_id)
+        {
+            Some(model) => {
+                *model = doc;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        recipient_id: Uuid,
+    ) -> Result<Option<Person>, RepositoryError> {
+        let recipient_id = BsonUuid::from_uuid_1(recipient_id);
+        let recipients = self
+            .recipients
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match recipients
+            .iter()
+            .find(|model| model.account_id == account_id && model.recipient_id == recipient_id)
+        {
+            Some(model) => Ok(Some(Person::try_from(model.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_email(
+        &self,
+        account_id: u32,
+        email: &Email,
+    ) -> Result<Option<Person>, RepositoryError> {
+        let email_key = email.normalized();
+        let recipients = self
+            .recipients
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match recipients
+            .iter()
+            .find(|model| model.account_id == account_id && model.email_key == email_key)
+        {
+            Some(model) => Ok(Some(Person::try_from(model.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self, account_id: u32) -> Result<Vec<Person>, RepositoryError> {
+        let mut recipients: Vec<RecipientModel> = self
+            .recipients
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?
+            .iter()
+            .filter(|model| model.account_id == account_id)
+            .cloned()
+            .collect();
+        recipients.sort_by(|a, b| {
+            (&a.person.last_name, &a.person.first_name)
+                .cmp(&(&b.person.last_name, &b.person.first_name))
+        });
+        recipients
+            .into_iter()
+            .map(|model| Person::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ProductRepository for InMemoryProductRepository {
+    async fn insert(&self, account_id: u32, product: &Product) -> Result<bool, RepositoryError> {
+        let doc = ProductModel::from_domain(account_id, product);
+        let mut products = self
+            .products
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        if products
+            .iter()
+            .any(|model| model.account_id == account_id && model.product_id == doc.product_id)
+        {
+            return Ok(false);
+        }
+        products.push(doc);
+        Ok(true)
+    }
+
+    async fn update(&self, account_id: u32, product: &Product) -> Result<bool, RepositoryError> {
+        let doc = ProductModel::from_domain(account_id, product);
+        let mut products = self
+            .products
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match products
+            .iter_mut()
+            .find(|model| model.account_id == account_id && model.product_id == doc.product_id)
+        {
+            Some(model) => {
+                *model = doc;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete(&self, account_id: u32, product_id: u32) -> Result<bool, RepositoryError> {
+        let mut products = self
+            .products
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        let count = products.len();
+        products.retain(|model| model.account_id != account_id || model.product_id != product_id);
+        Ok(products.len() < count)
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        product_id: u32,
+    ) -> Result<Option<Product>, RepositoryError> {
+        let products = self
+            .products
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match products
+            .iter()
+            .find(|model| model.account_id == account_id && model.product_id == product_id)
+        {
+            Some(model) => Ok(Some(Product::try_from(model.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self, account_id: u32) -> Result<Vec<Product>, RepositoryError> {
+        let mut products: Vec<ProductModel> = self
+            .products
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?
+            .iter()
+            .filter(|model| model.account_id == account_id)
+            .cloned()
+            .collect();
+        products.sort_by_key(|model| model.product_id);
+        products
+            .into_iter()
+            .map(|model| Product::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for InMemoryApiKeyRepository {
+    async fn insert(&self, key: &ApiKey) -> Result<(), RepositoryError> {
+        let doc = ApiKeyModel::from_domain(key);
+        self.keys
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?
+            .push(doc);
+        Ok(())
+    }
+
+    async fn update(&self, key: &ApiKey) -> Result<bool, RepositoryError> {
+        let doc = ApiKeyModel::from_domain(key);
+        let mut keys = self
+            .keys
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match keys.iter_mut().find(|model| model.key_id == doc.key_id) {
+            Some(model) => {
+                *model = doc;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete(&self, account_id: u32, key_id: Uuid) -> Result<bool, RepositoryError> {
+        let key_id = BsonUuid::from_uuid_1(key_id);
+        let mut keys = self
+            .keys
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        let count = keys.len();
+        keys.retain(|model| model.key_id != key_id || model.account_id != Some(account_id));
+        Ok(keys.len() < count)
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        key_id: Uuid,
+    ) -> Result<Option<ApiKey>, RepositoryError> {
+        let key_id = BsonUuid::from_uuid_1(key_id);
+        let keys = self
+            .keys
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match keys
+            .iter()
+            .find(|model| model.key_id == key_id && model.account_id == Some(account_id))
+        {
+            Some(model) => Ok(Some(ApiKey::try_from(model.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_secret_hash(
+        &self,
+        secret_hash: &str,
+    ) -> Result<Option<ApiKey>, RepositoryError> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match keys.iter().find(|model| model.secret_hash == secret_hash) {
+            Some(model) => Ok(Some(ApiKey::try_from(model.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self, account_id: u32) -> Result<Vec<ApiKey>, RepositoryError> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        keys.iter()
+            .filter(|model| model.account_id == Some(account_id))
+            .map(|model| ApiKey::try_from(model.clone()).map_err(RepositoryError::from))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AuditRepository for InMemoryAuditRepository {
+    async fn append(&self, entry: &AuditEntry) -> Result<bool, RepositoryError> {
+        let doc = AuditEntryModel::from_domain(entry);
+        let mut entries = self
+            .entries
+            .write()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        if entries.iter().any(|model| {
+            model.certificate_id == doc.certificate_id && model.sequence == doc.sequence
+        }) {
+            return Ok(false);
+        }
+        entries.push(doc);
+        Ok(true)
+    }
+
+    async fn find_last(&self, certificate_id: Uuid) -> Result<Option<AuditEntry>, RepositoryError> {
+        let certificate_id = BsonUuid::from_uuid_1(certificate_id);
+        let entries = self
+            .entries
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        match entries
+            .iter()
+            .filter(|model| model.certificate_id == certificate_id)
+            .max_by_key(|model| model.sequence)
+        {
+            Some(model) => Ok(Some(AuditEntry::try_from(model.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_certificate_id(
+        &self,
+        certificate_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepositoryError> {
+        let certificate_id = BsonUuid::from_uuid_1(certificate_id);
+        let entries = self
+            .entries
+            .read()
+            .map_err(InMemoryCertificateRepository::poisoned)?;
+        let mut history: Vec<&AuditEntryModel> = entries
+            .iter()
+            .filter(|model| model.certificate_id == certificate_id)
+            .collect();
+        history.sort_by_key(|model| model.sequence);
+        history
+            .into_iter()
+            .map(|model| AuditEntry::try_from(model.clone()).map_err(RepositoryError::from))
+            .collect()
+    }
+}
@@ -0,0 +1,325 @@
+pub mod in_memory;
+pub mod mongo;
+pub mod query;
+
+use std::{error::Error, sync::Arc};
+
+use actix_web::web;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use mongodb::Database;
+use uuid::Uuid;
+
+use crate::{
+    db::{init_db, init_indexes},
+    domain::{
+        api_key::ApiKey, audit::AuditEntry, base::Email, certificate::Certificate,
+        error::CertificateParseError, organization::Organization, person::Person, product::Product,
+    },
+};
+
+use self::{
+    in_memory::{
+        InMemoryApiKeyRepository, InMemoryAuditRepository, InMemoryCertificateRepository,
+        InMemoryOrganizationRepository, InMemoryProductRepository, InMemoryRecipientRepository,
+    },
+    mongo::{
+        MongoApiKeyRepository, MongoAuditRepository, MongoCertificateRepository,
+        MongoOrganizationRepository, MongoProductRepository, MongoRecipientRepository,
+    },
+    query::{CertificatePage, CertificateQuery},
+};
+
+/// Storage backend for certificates.
+///
+/// Handlers only depend on this trait, so the backing store can be swapped
+/// between MongoDB and the in-memory implementation without touching them.
+#[async_trait]
+pub trait CertificateRepository: Send + Sync {
+    /// Persists a newly issued certificate
+    async fn insert(&self, certificate: &Certificate) -> Result<(), RepositoryError>;
+
+    /// Persists several newly issued certificates in one round trip
+    async fn insert_many(&self, certificates: &[Certificate]) -> Result<(), RepositoryError>;
+
+    /// Replaces a stored certificate of its account with its updated state, provided
+    /// the stored version still matches `expected_version`.
+    ///
+    /// Returns `false` when the certificate does not exist or was changed concurrently.
+    async fn update(
+        &self,
+        certificate: &Certificate,
+        expected_version: u32,
+    ) -> Result<bool, RepositoryError>;
+
+    /// Removes a certificate of the account, only meant to undo an insert whose
+    /// follow-up failed. Returns `false` when the certificate does not exist.
+    async fn delete(&self, account_id: u32, certificate_id: Uuid) -> Result<bool, RepositoryError>;
+
+    /// Finds a certificate of the account by its id, returning `None` when it does not
+    /// exist or belongs to another account
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        certificate_id: Uuid,
+    ) -> Result<Option<Certificate>, RepositoryError>;
+
+    /// Finds a certificate by its id in any account, only meant for public verification
+    async fn find_by_id_across_accounts(
+        &self,
+        certificate_id: Uuid,
+    ) -> Result<Option<Certificate>, RepositoryError>;
+
+    /// Finds every certificate of the account issued to the given user
+    async fn find_by_user_id(
+        &self,
+        account_id: u32,
+        user_id: Uuid,
+    ) -> Result<Vec<Certificate>, RepositoryError>;
+
+    /// Finds up to `limit` active certificates of every account whose validity ended at
+    /// the given point in time, or whose active accreditation ended by then
+    async fn find_expiring(
+        &self,
+        at: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Certificate>, RepositoryError>;
+
+    /// Finds a page of the certificates of the account matching the query
+    async fn find_page(
+        &self,
+        account_id: u32,
+        query: &CertificateQuery,
+    ) -> Result<CertificatePage, RepositoryError>;
+}
+
+/// Storage backend for issuing organizations.
+///
+/// Every account keeps its own organizations, an organization of another account is
+/// reported as missing.
+#[async_trait]
+pub trait OrganizationRepository: Send + Sync {
+    async fn insert(
+        &self,
+        account_id: u32,
+        organization: &Organization,
+    ) -> Result<(), RepositoryError>;
+
+    /// Replaces an organization of the account, returning `false` when it does not exist
+    async fn update(
+        &self,
+        account_id: u32,
+        organization: &Organization,
+    ) -> Result<bool, RepositoryError>;
+
+    /// Deletes an organization of the account, returning `false` when it does not exist
+    async fn delete(&self, account_id: u32, organization_id: Uuid)
+        -> Result<bool, RepositoryError>;
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        organization_id: Uuid,
+    ) -> Result<Option<Organization>, RepositoryError>;
+
+    /// Finds every organization of the account, ordered by name
+    async fn find_all(&self, account_id: u32) -> Result<Vec<Organization>, RepositoryError>;
+}
+
+/// Registry of certificate recipients.
+///
+/// Every account keeps its own recipients, each identified within the account by
+/// their normalized email.
+#[async_trait]
+pub trait RecipientRepository: Send + Sync {
+    /// Stores the recipient in the account unless one with the same normalized email
+    /// is registered there, returning the registered recipient either way
+    async fn register(
+        &self,
+        account_id: u32,
+        recipient: &Person,
+    ) -> Result<Person, RepositoryError>;
+
+    /// Replaces a recipient registered in the account, returning `false` when it does
+    /// not exist
+    async fn update(&self, account_id: u32, recipient: &Person) -> Result<bool, RepositoryError>;
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        recipient_id: Uuid,
+    ) -> Result<Option<Person>, RepositoryError>;
+
+    /// Finds the recipient registered in the account with the email, ignoring case and
+    /// surrounding whitespace
+    async fn find_by_email(
+        &self,
+        account_id: u32,
+        email: &Email,
+    ) -> Result<Option<Person>, RepositoryError>;
+
+    /// Finds every recipient of the account, ordered by last and first name
+    async fn find_all(&self, account_id: u32) -> Result<Vec<Person>, RepositoryError>;
+}
+
+/// Catalog of the products certificates are issued for.
+///
+/// Every account keeps its own catalog, so product ids only need to be unique within
+/// an account.
+#[async_trait]
+pub trait ProductRepository: Send + Sync {
+    /// Stores the product in the account unless one with the same id exists there,
+    /// returning `false` then
+    async fn insert(&self, account_id: u32, product: &Product) -> Result<bool, RepositoryError>;
+
+    /// Replaces a product of the account, returning `false` when it does not exist
+    async fn update(&self, account_id: u32, product: &Product) -> Result<bool, RepositoryError>;
+
+    /// Deletes a product of the account, returning `false` when it does not exist
+    async fn delete(&self, account_id: u32, product_id: u32) -> Result<bool, RepositoryError>;
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        product_id: u32,
+    ) -> Result<Option<Product>, RepositoryError>;
+
+    /// Finds every product of the account, ordered by id
+    async fn find_all(&self, account_id: u32) -> Result<Vec<Product>, RepositoryError>;
+}
+
+/// Storage of API keys, each identified by the hash of its secret.
+///
+/// Keys are managed per account, a key of another account is reported as missing.
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn insert(&self, key: &ApiKey) -> Result<(), RepositoryError>;
+
+    /// Replaces a stored key, returning `false` when it does not exist
+    async fn update(&self, key: &ApiKey) -> Result<bool, RepositoryError>;
+
+    /// Deletes a key of the account, returning `false` when it does not exist
+    async fn delete(&self, account_id: u32, key_id: Uuid) -> Result<bool, RepositoryError>;
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        key_id: Uuid,
+    ) -> Result<Option<ApiKey>, RepositoryError>;
+
+    /// Finds the key whose secret has the given hash
+    async fn find_by_secret_hash(
+        &self,
+        secret_hash: &str,
+    ) -> Result<Option<ApiKey>, RepositoryError>;
+
+    /// Finds every key of the account, oldest first
+    async fn find_all(&self, account_id: u32) -> Result<Vec<ApiKey>, RepositoryError>;
+}
+
+/// Append-only log of the changes of every certificate, entries are never replaced
+/// or deleted
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    /// Stores the entry unless the history of its certificate already holds one with
+    /// the same sequence number, returning `false` then
+    async fn append(&self, entry: &AuditEntry) -> Result<bool, RepositoryError>;
+
+    /// Finds the newest entry of the history of a certificate
+    async fn find_last(&self, certificate_id: Uuid) -> Result<Option<AuditEntry>, RepositoryError>;
+
+    /// Finds the history of a certificate, oldest entry first
+    async fn find_by_certificate_id(
+        &self,
+        certificate_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepositoryError>;
+}
+
+#[derive(Debug)]
+pub struct RepositoryError(pub String);
+
+impl Error for RepositoryError {}
+
+impl std::fmt::Display for RepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "storage operation failed: {}", self.0)
+    }
+}
+
+impl From<CertificateParseError> for RepositoryError {
+    fn from(err: CertificateParseError) -> Self {
+        RepositoryError(err.to_string())
+    }
+}
+
+/// Every repository of the service, backed by the same store
+#[derive(Clone)]
+pub struct Repositories {
+    pub certificates: Arc<dyn CertificateRepository>,
+    pub organizations: Arc<dyn OrganizationRepository>,
+    pub recipients: Arc<dyn RecipientRepository>,
+    pub products: Arc<dyn ProductRepository>,
+    pub api_keys: Arc<dyn ApiKeyRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+}
+
+impl Repositories {
+    /// Repositories keeping everything in memory, for tests and local demos
+    pub fn in_memory() -> Self {
+        Repositories {
+            certificates: Arc::new(InMemoryCertificateRepository::default()),
+            organizations: Arc::new(InMemoryOrganizationRepository::default()),
+            recipients: Arc::new(InMemoryRecipientRepository::default()),
+            products: Arc::new(InMemoryProductRepository::default()),
+            api_keys: Arc::new(InMemoryApiKeyRepository::default()),
+            audit: Arc::new(InMemoryAuditRepository::default()),
+        }
+    }
+
+    pub fn mongo(db: Database) -> Self {
+        Repositories {
+            certificates: Arc::new(MongoCertificateRepository::new(db.clone())),
+            organizations: Arc::new(MongoOrganizationRepository::new(db.clone())),
+            recipients: Arc::new(MongoRecipientRepository::new(db.clone())),
+            products: Arc::new(MongoProductRepository::new(db.clone())),
+            api_keys: Arc::new(MongoApiKeyRepository::new(db.clone())),
+            audit: Arc::new(MongoAuditRepository::new(db)),
+        }
+    }
+
+    /// Registers every repository as application data
+    pub fn configure(&self, cfg: &mut web::ServiceConfig) {
+        cfg.app_data(web::Data::from(self.certificates.clone()))
+            .app_data(web::Data::from(self.organizations.clone()))
+            .app_data(web::Data::from(self.recipients.clone()))
+            .app_data(web::Data::from(self.products.clone()))
+            .app_data(web::Data::from(self.api_keys.clone()))
+            .app_data(web::Data::from(self.audit.clone()));
+    }
+}
+
+/// Creates the repositories of the storage selected by the `CRS_STORAGE` variable.
+///
+/// `memory` selects the in-memory backend, anything else (or no value) selects MongoDB.
+/// Returns `None` when MongoDB is selected but cannot be initialized.
+pub async fn init_repositories() -> Option<Repositories> {
+    match dotenvy::var("CRS_STORAGE").as_deref() {
+        Ok("memory") => {
+            info!("Using in-memory storage");
+            Some(Repositories::in_memory())
+        }
+        _ => match init_db().await {
+            Some(db) => {
+                if let Err(err) = init_indexes(&db).await {
+                    error!("Unable to create storage indexes. {}", err);
+                }
+                Some(Repositories::mongo(db))
+            }
+            None => {
+                error!("Unable to initialize MongoDB storage");
+                None
+            }
+        },
+    }
+}
@@ -0,0 +1,474 @@
+use async_trait::async_trait;
+use mongodb::{
+    bson::{doc, Bson, DateTime, Document, Uuid as BsonUuid},
+    Database,
+};
+use uuid::Uuid;
+
+use crate::{
+    db::{
+        append_audit_entry, delete_api_key, delete_certificate, delete_organization,
+        delete_product, find_api_key_by_id, find_api_key_by_secret_hash, find_api_keys,
+        find_audit_entries, find_certificate_by_id, find_certificates,
+        find_certificates_by_user_id, find_expiring_certificates, find_last_audit_entry,
+        find_organization_by_id, find_organizations, find_product_by_id, find_products,
+        find_recipient_by_email_key, find_recipient_by_id, find_recipients, register_product,
+        register_recipient, replace_api_key, replace_one, replace_organization, replace_product,
+        replace_recipient, store_api_key, store_many, store_one, store_organization,
+    },
+    domain::{
+        api_key::ApiKey,
+        audit::AuditEntry,
+        base::{AssessmentResult, Email},
+        certificate::Certificate,
+        organization::Organization,
+        person::Person,
+        product::Product,
+        revocation::CertificateStatus,
+    },
+    helpers::SaveType,
+    model::{
+        ApiKeyModel, AuditEntryModel, CertificateModel, ProductModel, RecipientModel,
+        RegisteredOrganizationModel,
+    },
+};
+
+use super::{
+    query::{CertificatePage, CertificateQuery},
+    ApiKeyRepository, AuditRepository, CertificateRepository, OrganizationRepository,
+    ProductRepository, RecipientRepository, RepositoryError,
+};
+
+/// MongoDB backed certificate repository
+pub struct MongoCertificateRepository {
+    db: Database,
+}
+
+impl MongoCertificateRepository {
+    pub fn new(db: Database) -> Self {
+        MongoCertificateRepository { db }
+    }
+}
+
+/// MongoDB backed organization repository
+pub struct MongoOrganizationRepository {
+    db: Database,
+}
+
+impl MongoOrganizationRepository {
+    pub fn new(db: Database) -> Self {
+        MongoOrganizationRepository { db }
+    }
+}
+
+/// MongoDB backed recipient registry
+pub struct MongoRecipientRepository {
+    db: Database,
+}
+
+impl MongoRecipientRepository {
+    pub fn new(db: Database) -> Self {
+        MongoRecipientRepository { db }
+    }
+}
+
+/// MongoDB backed product catalog
+pub struct MongoProductRepository {
+    db: Database,
+}
+
+impl MongoProductRepository {
+    pub fn new(db: Database) -> Self {
+        MongoProductRepository { db }
+    }
+}
+
+pub struct MongoApiKeyRepository {
+    db: Database,
+}
+
+impl MongoApiKeyRepository {
+    pub fn new(db: Database) -> Self {
+        MongoApiKeyRepository { db }
+    }
+}
+
+/// MongoDB backed audit log
+pub struct MongoAuditRepository {
+    db: Database,
+}
+
+impl MongoAuditRepository {
+    pub fn new(db: Database) -> Self {
+        MongoAuditRepository { db }
+    }
+}
+
+impl From<mongodb::error::Error> for RepositoryError {
+    fn from(err: mongodb::error::Error) -> Self {
+        RepositoryError(err.to_string())
+    }
+}
+
+/// Translates a listing query within an account into a MongoDB filter
+fn query_filter(account_id: u32, query: &CertificateQuery) -> Document {
+    let mut filter = doc! {"account_id": account_id as i64};
+    if let Some(product_id) = query.product_id {
+        filter.insert("product_id", product_id as i64);
+    }
+    if let Some(user_id) = query.user_id {
+        filter.insert("user_id", BsonUuid::from_uuid_1(user_id));
+    }
+    let mut created_date = Document::new();
+    if let Some(created_from) = query.created_from {
+        created_date.insert("$gte", DateTime::from_chrono(created_from));
+    }
+    if let Some(created_to) = query.created_to {
+        created_date.insert("$lte", DateTime::from_chrono(created_to));
+    }
+    if !created_date.is_empty() {
+        filter.insert("created_date", created_date);
+    }
+    // Documents written before the status and result were stored default to active and passed
+    if let Some(status) = &query.status {
+        match status {
+            CertificateStatus::Active => {
+                filter.insert("status", doc! {"$in": [status.to_string(), Bson::Null]})
+            }
+            _ => filter.insert("status", status.to_string()),
+        };
+    }
+    if let Some(result) = &query.result {
+        match result {
+            AssessmentResult::Pass => filter.insert(
+                "metadata.result",
+                doc! {"$in": [result.to_string(), Bson::Null]},
+            ),
+            _ => filter.insert("metadata.result", result.to_string()),
+        };
+    }
+    if let Some(after) = &query.after {
+        let created_date = DateTime::from_chrono(after.created_date);
+        let certificate_id = BsonUuid::from_uuid_1(after.certificate_id);
+        filter.insert(
+            "$or",
+            vec![
+                doc! {"created_date": {"$lt": created_date}},
+                doc! {"created_date": created_date, "certificate_id": {"$lt": certificate_id}},
+            ],
+        );
+    }
+    filter
+}
+
+#[async_trait]
+impl CertificateRepository for MongoCertificateRepository {
+    async fn insert(&self, certificate: &Certificate) -> Result<(), RepositoryError> {
+        let doc = CertificateModel::from_domain(certificate, SaveType::Insert);
+        store_one(&self.db, &doc).await?;
+        Ok(())
+    }
+
+    async fn insert_many(&self, certificates: &[Certificate]) -> Result<(), RepositoryError> {
+        if certificates.is_empty() {
+            return Ok(());
+        }
+        let docs: Vec<CertificateModel> = certificates
+            .iter()
+            .map(|certificate| CertificateModel::from_domain(certificate, SaveType::Insert))
+            .collect();
+        store_many(&self.db, &docs).await?;
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        certificate: &Certificate,
+        expected_version: u32,
+    ) -> Result<bool, RepositoryError> {
+        let doc = CertificateModel::from_domain(certificate, SaveType::Update);
+        let update_result = replace_one(&self.db, &doc, expected_version).await?;
+        Ok(update_result.matched_count > 0)
+    }
+
+    async fn delete(&self, account_id: u32, certificate_id: Uuid) -> Result<bool, RepositoryError> {
+        let delete_result = delete_certificate(&self.db, account_id, certificate_id).await?;
+        Ok(delete_result.deleted_count > 0)
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        certificate_id: Uuid,
+    ) -> Result<Option<Certificate>, RepositoryError> {
+        match find_certificate_by_id(&self.db, Some(account_id), certificate_id).await? {
+            Some(model) => Ok(Some(Certificate::try_from(model)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_id_across_accounts(
+        &self,
+        certificate_id: Uuid,
+    ) -> Result<Option<Certificate>, RepositoryError> {
+        match find_certificate_by_id(&self.db, None, certificate_id).await? {
+            Some(model) => Ok(Some(Certificate::try_from(model)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_user_id(
+        &self,
+        account_id: u32,
+        user_id: Uuid,
+    ) -> Result<Vec<Certificate>, RepositoryError> {
+        find_certificates_by_user_id(&self.db, account_id, user_id)
+            .await?
+            .into_iter()
+            .map(|model| Certificate::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+
+    async fn find_expiring(
+        &self,
+        at: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<Certificate>, RepositoryError> {
+        find_expiring_certificates(&self.db, DateTime::from_chrono(at), limit as i64)
+            .await?
+            .into_iter()
+            .map(|model| Certificate::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+
+    async fn find_page(
+        &self,
+        account_id: u32,
+        query: &CertificateQuery,
+    ) -> Result<CertificatePage, RepositoryError> {
+        let filter = query_filter(account_id, query);
+        let certificates = find_certificates(&self.db, filter, query.limit as i64 + 1)
+            .await?
+            .into_iter()
+            .map(|model| Certificate::try_from(model).map_err(RepositoryError::from))
+            .collect::<Result<Vec<Certificate>, RepositoryError>>()?;
+        Ok(CertificatePage::from_overfetched(certificates, query.limit))
+    }
+}
+
+#[async_trait]
+impl OrganizationRepository for MongoOrganizationRepository {
+    async fn insert(
+        &self,
+        account_id: u32,
+        organization: &Organization,
+    ) -> Result<(), RepositoryError> {
+        let doc = RegisteredOrganizationModel::from_domain(account_id, organization);
+        store_organization(&self.db, &doc).await?;
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        account_id: u32,
+        organization: &Organization,
+    ) -> Result<bool, RepositoryError> {
+        let doc = RegisteredOrganizationModel::from_domain(account_id, organization);
+        let update_result = replace_organization(&self.db, &doc).await?;
+        Ok(update_result.matched_count > 0)
+    }
+
+    async fn delete(
+        &self,
+        account_id: u32,
+        organization_id: Uuid,
+    ) -> Result<bool, RepositoryError> {
+        let delete_result = delete_organization(&self.db, account_id, organization_id).await?;
+        Ok(delete_result.deleted_count > 0)
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        organization_id: Uuid,
+    ) -> Result<Option<Organization>, RepositoryError> {
+        match find_organization_by_id(&self.db, account_id, organization_id).await? {
+            Some(model) => Ok(Some(Organization::try_from(model)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self, account_id: u32) -> Result<Vec<Organization>, RepositoryError> {
+        find_organizations(&self.db, account_id)
+            .await?
+            .into_iter()
+            .map(|model| Organization::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl RecipientRepository for MongoRecipientRepository {
+    async fn register(
+        &self,
+        account_id: u32,
+        recipient: &Person,
+    ) -> Result<Person, RepositoryError> {
+        let doc = RecipientModel::from_domain(account_id, recipient);
+        match register_recipient(&self.db, &doc).await? {
+            Some(model) => Ok(Person::try_from(model)?),
+            None => Err(RepositoryError(
+                "registered recipient could not be read back".to_string(),
+            )),
+        }
+    }
+
+    async fn update(&self, account_id: u32, recipient: &Person) -> Result<bool, RepositoryError> {
+        let doc = RecipientModel::from_domain(account_id, recipient);
+        let update_result = replace_recipient(&self.db, &doc).await?;
+        Ok(update_result.matched_count > 0)
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        recipient_id: Uuid,
+    ) -> Result<Option<Person>, RepositoryError> {
+        match find_recipient_by_id(&self.db, account_id, recipient_id).await? {
+            Some(model) => Ok(Some(Person::try_from(model)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_email(
+        &self,
+        account_id: u32,
+        email: &Email,
+    ) -> Result<Option<Person>, RepositoryError> {
+        match find_recipient_by_email_key(&self.db, account_id, &email.normalized()).await? {
+            Some(model) => Ok(Some(Person::try_from(model)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self, account_id: u32) -> Result<Vec<Person>, RepositoryError> {
+        find_recipients(&self.db, account_id)
+            .await?
+            .into_iter()
+            .map(|model| Person::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ProductRepository for MongoProductRepository {
+    async fn insert(&self, account_id: u32, product: &Product) -> Result<bool, RepositoryError> {
+        let doc = ProductModel::from_domain(account_id, product);
+        let update_result = register_product(&self.db, &doc).await?;
+        Ok(update_result.upserted_id.is_some())
+    }
+
+    async fn update(&self, account_id: u32, product: &Product) -> Result<bool, RepositoryError> {
+        let doc = ProductModel::from_domain(account_id, product);
+        let update_result = replace_product(&self.db, &doc).await?;
+        Ok(update_result.matched_count > 0)
+    }
+
+    async fn delete(&self, account_id: u32, product_id: u32) -> Result<bool, RepositoryError> {
+        let delete_result = delete_product(&self.db, account_id, product_id).await?;
+        Ok(delete_result.deleted_count > 0)
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        product_id: u32,
+    ) -> Result<Option<Product>, RepositoryError> {
+        match find_product_by_id(&self.db, account_id, product_id).await? {
+            Some(model) => Ok(Some(Product::try_from(model)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self, account_id: u32) -> Result<Vec<Product>, RepositoryError> {
+        find_products(&self.db, account_id)
+            .await?
+            .into_iter()
+            .map(|model| Product::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for MongoApiKeyRepository {
+    async fn insert(&self, key: &ApiKey) -> Result<(), RepositoryError> {
+        store_api_key(&self.db, &ApiKeyModel::from_domain(key)).await?;
+        Ok(())
+    }
+
+    async fn update(&self, key: &ApiKey) -> Result<bool, RepositoryError> {
+        let update_result = replace_api_key(&self.db, &ApiKeyModel::from_domain(key)).await?;
+        Ok(update_result.matched_count > 0)
+    }
+
+    async fn delete(&self, account_id: u32, key_id: Uuid) -> Result<bool, RepositoryError> {
+        let delete_result = delete_api_key(&self.db, account_id, key_id).await?;
+        Ok(delete_result.deleted_count > 0)
+    }
+
+    async fn find_by_id(
+        &self,
+        account_id: u32,
+        key_id: Uuid,
+    ) -> Result<Option<ApiKey>, RepositoryError> {
+        match find_api_key_by_id(&self.db, account_id, key_id).await? {
+            Some(model) => Ok(Some(ApiKey::try_from(model)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_secret_hash(
+        &self,
+        secret_hash: &str,
+    ) -> Result<Option<ApiKey>, RepositoryError> {
+        match find_api_key_by_secret_hash(&self.db, secret_hash).await? {
+            Some(model) => Ok(Some(ApiKey::try_from(model)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self, account_id: u32) -> Result<Vec<ApiKey>, RepositoryError> {
+        find_api_keys(&self.db, account_id)
+            .await?
+            .into_iter()
+            .map(|model| ApiKey::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AuditRepository for MongoAuditRepository {
+    async fn append(&self, entry: &AuditEntry) -> Result<bool, RepositoryError> {
+        let update_result =
+            append_audit_entry(&self.db, &AuditEntryModel::from_domain(entry)).await?;
+        Ok(update_result.upserted_id.is_some())
+    }
+
+    async fn find_last(&self, certificate_id: Uuid) -> Result<Option<AuditEntry>, RepositoryError> {
+        match find_last_audit_entry(&self.db, certificate_id).await? {
+            Some(model) => Ok(Some(AuditEntry::try_from(model)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_certificate_id(
+        &self,
+        certificate_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepositoryError> {
+        find_audit_entries(&self.db, certificate_id)
+            .await?
+            .into_iter()
+            .map(|model| AuditEntry::try_from(model).map_err(RepositoryError::from))
+            .collect()
+    }
+}
@@ -0,0 +1,147 @@
+use actix_web::{body::BoxBody, HttpRequest, HttpResponse, Responder};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        base::AssessmentResult, certificate::Certificate, error::InvalidCursorError,
+        revocation::CertificateStatus,
+    },
+    helpers::respond_with_json,
+};
+
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// Filters of a certificate listing within one account.
+///
+/// Listings are sorted by creation date and then certificate id, newest first,
+/// so that pages stay stable while certificates are being issued.
+#[derive(Debug, Clone)]
+pub struct CertificateQuery {
+    pub product_id: Option<u32>,
+    pub user_id: Option<Uuid>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub status: Option<CertificateStatus>,
+    pub result: Option<AssessmentResult>,
+    /// Position of the last certificate of the previous page
+    pub after: Option<PageCursor>,
+    pub limit: usize,
+}
+
+impl Default for CertificateQuery {
+    fn default() -> Self {
+        CertificateQuery {
+            product_id: None,
+            user_id: None,
+            created_from: None,
+            created_to: None,
+            status: None,
+            result: None,
+            after: None,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+/// Opaque position in a certificate listing
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageCursor {
+    pub created_date: DateTime<Utc>,
+    pub certificate_id: Uuid,
+}
+
+impl PageCursor {
+    pub fn of(certificate: &Certificate) -> Self {
+        PageCursor {
+            created_date: certificate.created_date,
+            certificate_id: certificate.id.as_uuid(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!(
+            "{}_{}",
+            self.created_date.timestamp_millis(),
+            self.certificate_id
+        ))
+    }
+
+    pub fn decode(cursor: &str) -> Result<PageCursor, InvalidCursorError> {
+        let decoded = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or(InvalidCursorError)?;
+        let (millis, certificate_id) = decoded.split_once('_').ok_or(InvalidCursorError)?;
+        Ok(PageCursor {
+            created_date: millis
+                .parse()
+                .ok()
+                .and_then(DateTime::from_timestamp_millis)
+                .ok_or(InvalidCursorError)?,
+            certificate_id: Uuid::parse_str(certificate_id).map_err(|_| InvalidCursorError)?,
+        })
+    }
+}
+
+/// A page of certificates and the cursor of the next page, if there is one
+///
+/// Responds with `{"items": [...], "next": "<cursor>"}`, `next` is `null` on the last page.
+pub struct CertificatePage {
+    pub certificates: Vec<Certificate>,
+    pub next: Option<PageCursor>,
+}
+
+impl CertificatePage {
+    /// Builds a page out of up to `limit + 1` certificates read in listing order
+    pub fn from_overfetched(mut certificates: Vec<Certificate>, limit: usize) -> Self {
+        let next = if certificates.len() > limit {
+            certificates.truncate(limit);
+            certificates.last().map(PageCursor::of)
+        } else {
+            None
+        };
+        CertificatePage { certificates, next }
+    }
+}
+
+#[derive(Serialize)]
+struct CertificatePageBody<'a> {
+    items: &'a [Certificate],
+    next: Option<String>,
+}
+
+impl Responder for CertificatePage {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        respond_with_json(CertificatePageBody {
+            items: &self.certificates,
+            next: self.next.as_ref().map(PageCursor::encode),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{SubsecRound, Utc};
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use super::PageCursor;
+
+    #[test]
+    fn cursor_should_round_trip() {
+        let cursor = PageCursor {
+            created_date: Utc::now().trunc_subsecs(3),
+            certificate_id: Uuid::new_v4(),
+        };
+
+        assert_eq!(PageCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(PageCursor::decode("not a cursor").is_err());
+    }
+}
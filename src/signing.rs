@@ -0,0 +1,272 @@
+use std::{collections::HashMap, error::Error, fs};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::{SigningKey, VerifyingKey, SECRET_KEY_LENGTH};
+use log::{info, warn};
+use uuid::Uuid;
+
+use crate::domain::{certificate::Certificate, signature::SignatureStatus};
+
+/// Issuer signing keys, looked up by the id of the issuing organization.
+///
+/// The default key is used for organizations that do not have a key of their own.
+#[derive(Default)]
+pub struct Keyring {
+    default_key: Option<SigningKey>,
+    issuer_keys: HashMap<Uuid, SigningKey>,
+}
+
+#[derive(Debug)]
+pub struct KeyringError(pub String);
+
+impl Error for KeyringError {}
+
+impl std::fmt::Display for KeyringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unable to load issuer keys: {}", self.0)
+    }
+}
+
+/// Decodes a base64 encoded 32 byte Ed25519 secret key
+pub fn decode_signing_key(encoded: &str) -> Result<SigningKey, KeyringError> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|err| KeyringError(err.to_string()))?;
+    let secret: [u8; SECRET_KEY_LENGTH] = bytes
+        .try_into()
+        .map_err(|_| KeyringError(format!("key must be {} bytes", SECRET_KEY_LENGTH)))?;
+    Ok(SigningKey::from_bytes(&secret))
+}
+
+impl Keyring {
+    pub fn with_default_key(default_key: SigningKey) -> Self {
+        Keyring {
+            default_key: Some(default_key),
+            issuer_keys: HashMap::new(),
+        }
+    }
+
+    pub fn add_issuer_key(&mut self, organization_id: Uuid, key: SigningKey) {
+        self.issuer_keys.insert(organization_id, key);
+    }
+
+    /// Parses a key file where every line is `<organization id> <base64 key>`.
+    ///
+    /// `default` can be used instead of an organization id, empty lines and lines
+    /// starting with `#` are ignored.
+    pub fn parse(content: &str) -> Result<Keyring, KeyringError> {
+        let mut keyring = Keyring::default();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (issuer, key) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| KeyringError(format!("malformed line `{}`", line)))?;
+            let key = decode_signing_key(key)?;
+            if issuer == "default" {
+                keyring.default_key = Some(key);
+            } else {
+                let organization_id =
+                    Uuid::parse_str(issuer).map_err(|err| KeyringError(err.to_string()))?;
+                keyring.add_issuer_key(organization_id, key);
+            }
+        }
+        Ok(keyring)
+    }
+
+    /// Loads the issuer keys from the environment.
+    ///
+    /// `CRS_SIGNING_KEYS_FILE` points to a key file (see [`Keyring::parse`]) and
+    /// `CRS_SIGNING_KEY` holds a base64 encoded default key. Returns `None` when
+    /// neither is set, in which case certificates are issued unsigned.
+    pub fn from_env() -> Result<Option<Keyring>, KeyringError> {
+        let mut keyring = match dotenvy::var("CRS_SIGNING_KEYS_FILE") {
+            Ok(path) => {
+                let content = fs::read_to_string(&path)
+                    .map_err(|err| KeyringError(format!("{}: {}", path, err)))?;
+                Some(Keyring::parse(&content)?)
+            }
+            Err(_) => None,
+        };
+        if let Ok(encoded) = dotenvy::var("CRS_SIGNING_KEY") {
+            keyring.get_or_insert_with(Keyring::default).default_key =
+                Some(decode_signing_key(&encoded)?);
+        }
+        if keyring.is_some() {
+            info!("Certificate signing is enabled");
+        }
+        Ok(keyring)
+    }
+
+    /// Signing key of the given issuing organization
+    pub fn signing_key_for(&self, organization_id: Uuid) -> Option<&SigningKey> {
+        self.issuer_keys
+            .get(&organization_id)
+            .or(self.default_key.as_ref())
+    }
+
+    /// Verifying key of the given issuing organization
+    pub fn verifying_key_for(&self, organization_id: Uuid) -> Option<VerifyingKey> {
+        self.signing_key_for(organization_id)
+            .map(SigningKey::verifying_key)
+    }
+
+    /// Signs the certificate with its authority's key.
+    ///
+    /// Returns **false** when no key is available for the authority, or the authority
+    /// is unknown.
+    pub fn sign(&self, certificate: &mut Certificate) -> bool {
+        match certificate
+            .authority_id()
+            .and_then(|authority_id| self.signing_key_for(authority_id))
+        {
+            Some(key) => {
+                certificate.sign(key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Checks the certificate signature against its authority's key
+    pub fn verify(&self, certificate: &Certificate) -> SignatureStatus {
+        certificate.signature_status(
+            certificate
+                .authority_id()
+                .and_then(|authority_id| self.verifying_key_for(authority_id))
+                .as_ref(),
+        )
+    }
+}
+
+/// Signs a changed certificate again when signing is enabled. Without a key for its
+/// authority the signature over its previous state is dropped rather than kept, and
+/// the certificate is stored as unsigned.
+pub fn sign_changed(keyring: Option<&Keyring>, certificate: &mut Certificate) {
+    let signed = keyring.is_some_and(|keyring| keyring.sign(certificate));
+    if signed {
+        return;
+    }
+    certificate.signature_required = false;
+    if certificate.signature.take().is_some() {
+        warn!(
+            "No signing key for the issuer of certificate {}, it is stored unsigned",
+            certificate.id.as_uuid()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use chrono::{SubsecRound, Utc};
+    use ed25519_dalek::SigningKey;
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use crate::{
+        domain::{
+            accreditation::{Accreditation, AccreditationStatus},
+            signature::SignatureStatus,
+        },
+        test_helpers::certificate_for,
+    };
+
+    use super::Keyring;
+
+    #[test]
+    fn parse_key_file_should_succeed() {
+        let organization_id = Uuid::new_v4();
+        let content = format!(
+            "# issuer keys\ndefault {}\n\n{} {}\n",
+            STANDARD.encode([1u8; 32]),
+            organization_id,
+            STANDARD.encode([2u8; 32])
+        );
+
+        let keyring = Keyring::parse(&content).unwrap();
+
+        assert_eq!(
+            keyring.signing_key_for(organization_id).unwrap().to_bytes(),
+            [2u8; 32]
+        );
+        assert_eq!(
+            keyring.signing_key_for(Uuid::new_v4()).unwrap().to_bytes(),
+            [1u8; 32]
+        );
+    }
+
+    #[test]
+    fn parse_key_file_with_short_key_should_fail() {
+        let content = format!("default {}", STANDARD.encode([1u8; 16]));
+
+        assert!(Keyring::parse(&content).is_err());
+    }
+
+    #[test]
+    fn signed_certificate_should_verify() {
+        let keyring = Keyring::with_default_key(SigningKey::from_bytes(&[7u8; 32]));
+        let mut certificate = certificate_for(Uuid::new_v4());
+
+        assert_eq!(keyring.verify(&certificate), SignatureStatus::Unsigned);
+        assert_eq!(
+            certificate.signature_status(None),
+            SignatureStatus::Unsigned
+        );
+        assert!(keyring.sign(&mut certificate));
+        assert_eq!(keyring.verify(&certificate), SignatureStatus::Valid);
+
+        certificate.signature = None;
+        assert_eq!(keyring.verify(&certificate), SignatureStatus::Invalid);
+    }
+
+    #[test]
+    fn tampered_certificate_should_not_verify() {
+        let keyring = Keyring::with_default_key(SigningKey::from_bytes(&[7u8; 32]));
+        let mut certificate = certificate_for(Uuid::new_v4());
+        keyring.sign(&mut certificate);
+
+        certificate.name = "Forged certificate".to_string();
+
+        assert_eq!(keyring.verify(&certificate), SignatureStatus::Invalid);
+
+        let mut renamed = certificate_for(Uuid::new_v4());
+        keyring.sign(&mut renamed);
+        renamed.recipient.name.as_mut().unwrap().last_name = "Smith".to_string();
+        assert_eq!(keyring.verify(&renamed), SignatureStatus::Invalid);
+
+        let mut reissued = certificate_for(Uuid::new_v4());
+        keyring.sign(&mut reissued);
+        reissued.authority.as_mut().unwrap().name = "Forged Academy".to_string();
+        assert_eq!(keyring.verify(&reissued), SignatureStatus::Invalid);
+
+        let mut accredited = certificate_for(Uuid::new_v4());
+        accredited.accreditation = Some(Accreditation {
+            name: "ISO 9001".to_string(),
+            institution: "ISO".to_string(),
+            start_date: Utc::now().trunc_subsecs(3),
+            end_date: None,
+            status: AccreditationStatus::Revoked,
+            status_at_issuance: Some(AccreditationStatus::Active),
+            history: Vec::new(),
+        });
+        keyring.sign(&mut accredited);
+        accredited.accreditation.as_mut().unwrap().status = AccreditationStatus::Active;
+        assert_eq!(keyring.verify(&accredited), SignatureStatus::Invalid);
+    }
+
+    #[test]
+    fn certificate_signed_by_other_key_should_not_verify() {
+        let keyring = Keyring::with_default_key(SigningKey::from_bytes(&[7u8; 32]));
+        let forger = Keyring::with_default_key(SigningKey::from_bytes(&[8u8; 32]));
+        let mut certificate = certificate_for(Uuid::new_v4());
+        forger.sign(&mut certificate);
+
+        assert_eq!(keyring.verify(&certificate), SignatureStatus::Invalid);
+        assert_eq!(
+            Keyring::default().verify(&certificate),
+            SignatureStatus::Unverifiable
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use std::future::{ready, Ready};
+
+use actix_web::{dev::Payload, FromRequest, HttpMessage, HttpRequest};
+use uuid::Uuid;
+
+use crate::{
+    auth::Principal,
+    domain::certificate::Certificate,
+    error::{CrsError, FieldError},
+    repository::query::CertificateQuery,
+};
+
+/// The account a request is scoped to, the account of its API key or bearer token.
+///
+/// Several customers share one deployment, so every certificate a request reads or
+/// writes must belong to its account. Certificates of other accounts are reported
+/// as missing rather than forbidden, so their existence is not disclosed.
+///
+/// Requests of users without the admin role are further restricted to the
+/// certificates issued to the user.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tenant {
+    account_id: u32,
+    user_id: Option<Uuid>,
+}
+
+impl Tenant {
+    pub fn account_id(&self) -> u32 {
+        self.account_id
+    }
+
+    /// Ensures a payload or query refers to the account of the tenant
+    pub fn ensure_account(&self, account_id: u32) -> Result<(), CrsError> {
+        if account_id == self.account_id {
+            Ok(())
+        } else {
+            Err(CrsError::Validation {
+                message: "invalid account".to_string(),
+                errors: vec![self.account_mismatch(account_id)],
+            })
+        }
+    }
+
+    /// Violation of an `account_id` referring to another account
+    pub fn account_mismatch(&self, account_id: u32) -> FieldError {
+        FieldError::new(
+            "account_id",
+            "tenant",
+            account_id,
+            format!("must be the account of the request, {}", self.account_id),
+        )
+    }
+
+    /// Ensures a restricted tenant only asks for the certificates of its own user
+    pub fn ensure_user(&self, user_id: Uuid) -> Result<(), CrsError> {
+        match self.user_id {
+            Some(own) if own != user_id => Err(CrsError::Forbidden(
+                "only the certificates of the authenticated user can be read".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Limits a listing query to the certificates of the user of a restricted tenant
+    pub fn restrict(&self, query: &mut CertificateQuery) -> Result<(), CrsError> {
+        if let Some(user_id) = query.user_id {
+            self.ensure_user(user_id)?;
+        }
+        if self.user_id.is_some() {
+            query.user_id = self.user_id;
+        }
+        Ok(())
+    }
+
+    /// Indicates if the tenant may reach the certificate of its account
+    pub fn can_access(&self, certificate: &Certificate) -> bool {
+        certificate.account_id == self.account_id
+            && self
+                .user_id
+                .is_none_or(|user_id| certificate.recipient.id.as_uuid() == user_id)
+    }
+
+    fn from_principal(req: &HttpRequest) -> Result<Self, CrsError> {
+        let extensions = req.extensions();
+        let principal = extensions
+            .get::<Principal>()
+            .ok_or_else(|| CrsError::Unauthorized("request is not authenticated".to_string()))?;
+        let account_id = principal.account_id.ok_or_else(|| {
+            CrsError::Forbidden(format!("{} is not bound to an account", principal.subject))
+        })?;
+        Ok(Tenant {
+            account_id,
+            user_id: principal.user_id,
+        })
+    }
+}
+
+impl FromRequest for Tenant {
+    type Error = CrsError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Tenant::from_principal(req))
+    }
+}
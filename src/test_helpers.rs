@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use actix_web::web;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use ed25519_dalek::{Signer, SigningKey};
+use futures::executor::block_on;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        api_key::{hash_secret, ApiKey, Scope},
+        base::{Address, Email, Id, Name, Phone},
+        certificate::Certificate,
+        organization::Organization,
+        person::Person,
+    },
+    dto::{
+        certificate_dto::CertificateDto, certificate_metadata_dto::CertificateMetadataDto,
+        recipient_dto::RecipientDto,
+    },
+    jwt::JwtVerifier,
+    repository::{
+        in_memory::{InMemoryApiKeyRepository, InMemoryAuditRepository},
+        ApiKeyRepository, AuditRepository, Repositories,
+    },
+};
+
+/// Builds a valid issuing organization
+pub fn organization() -> Organization {
+    Organization {
+        id: Id::parse(Uuid::new_v4()).unwrap(),
+        name: "Acme Academy".to_string(),
+        email: Email::parse("certificates@acme.com".to_string()).unwrap(),
+        phone: Phone::parse("+45 87654321".to_string()).unwrap(),
+        address: Address::default(),
+    }
+}
+
+/// Builds a valid registered recipient with the given id
+pub fn recipient(user_id: Uuid) -> Person {
+    Person {
+        id: Id::parse(user_id).unwrap(),
+        name: Name {
+            first_name: "John".to_string(),
+            middle_name: None,
+            last_name: "Doe".to_string(),
+        },
+        email: Email::parse("john.doe@email.com".to_string()).unwrap(),
+        phone: Some(Phone::parse("12345678".to_string()).unwrap()),
+    }
+}
+
+/// Builds a valid certificate issued to the given user
+pub fn certificate_for(user_id: Uuid) -> Certificate {
+    let authority = organization();
+    Certificate::issue(
+        CertificateDto {
+            account_id: 20,
+            product_id: 15,
+            organization_id: authority.id.as_uuid(),
+            recipient: RecipientDto {
+                id: user_id,
+                first_name: "John".to_string(),
+                last_name: "Doe".to_string(),
+                email: "john.doe@email.com".to_string(),
+                phone: "12345678".to_string(),
+            },
+            metadata: CertificateMetadataDto {
+                score: 100,
+                progress: 1.0,
+                acquired_date: None,
+                accreditation: None,
+                score_scale: None,
+            },
+        },
+        recipient(user_id),
+        authority,
+        None,
+    )
+    .unwrap()
+}
+
+/// In-memory repositories holding a single organization of account 20, whose id is
+/// returned along
+pub async fn repositories_with_organization() -> (Repositories, Uuid) {
+    let repositories = Repositories::in_memory();
+    let authority = organization();
+    repositories
+        .organizations
+        .insert(20, &authority)
+        .await
+        .unwrap();
+    (repositories, authority.id.as_uuid())
+}
+
+/// Secret of a key of account 20 granted every scope
+pub const TEST_API_KEY: &str = "crs_test_key";
+
+/// Secret of a key of account 99 granted every scope
+pub const OTHER_ACCOUNT_API_KEY: &str = "crs_other_account_key";
+
+/// Registers API key storage holding the test keys
+pub fn authenticated(cfg: &mut web::ServiceConfig) {
+    let repository = InMemoryApiKeyRepository::default();
+    for (account_id, secret) in [(20, TEST_API_KEY), (99, OTHER_ACCOUNT_API_KEY)] {
+        let key = ApiKey {
+            id: Id::parse(Uuid::new_v4()).unwrap(),
+            name: format!("account {account_id}"),
+            account_id: Some(account_id),
+            scopes: vec![
+                Scope::CertificatesRead,
+                Scope::CertificatesIssue,
+                Scope::CertificatesUpdate,
+                Scope::CertificatesRevoke,
+                Scope::RegistryRead,
+                Scope::RegistryWrite,
+                Scope::JobsRun,
+                Scope::KeysManage,
+            ],
+            created_date: Utc::now(),
+            rotated_date: None,
+            secret_hash: hash_secret(secret),
+        };
+        // the in-memory storage never waits
+        block_on(repository.insert(&key)).unwrap();
+    }
+    let repository: Arc<dyn ApiKeyRepository> = Arc::new(repository);
+    cfg.app_data(web::Data::from(repository))
+        .app_data(web::Data::new(JwtVerifier::from_jwks(&jwks()).unwrap()));
+}
+
+/// Registers an empty audit log, for apps not configured with [`Repositories`]
+pub fn audited(cfg: &mut web::ServiceConfig) {
+    let repository: Arc<dyn AuditRepository> = Arc::new(InMemoryAuditRepository::default());
+    cfg.app_data(web::Data::from(repository));
+}
+
+/// Secret of the Ed25519 key signing test bearer tokens
+const JWT_SIGNING_KEY: [u8; 32] = [9u8; 32];
+
+/// JWKS document holding the public key of the test bearer tokens
+pub fn jwks() -> String {
+    let public_key = SigningKey::from_bytes(&JWT_SIGNING_KEY).verifying_key();
+    json!({"keys": [{
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "kid": "test",
+        "x": URL_SAFE_NO_PAD.encode(public_key.as_bytes()),
+    }]})
+    .to_string()
+}
+
+/// Signs a bearer token holding the claims, valid for an hour unless they say otherwise
+pub fn bearer_token(claims: serde_json::Value) -> String {
+    let mut claims = claims;
+    if claims.get("exp").is_none() {
+        claims["exp"] = json!(Utc::now().timestamp() + 3600);
+    }
+    let header = json!({"alg": "EdDSA", "typ": "JWT", "kid": "test"});
+    let content = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header.to_string()),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    );
+    let signature = SigningKey::from_bytes(&JWT_SIGNING_KEY).sign(content.as_bytes());
+    format!(
+        "Bearer {content}.{}",
+        URL_SAFE_NO_PAD.encode(signature.to_bytes())
+    )
+}